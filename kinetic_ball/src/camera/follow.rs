@@ -4,7 +4,7 @@ use crate::components::{
     MinimapCamera, PlayerCamera, PlayerDetailCamera, RemoteBall, RemotePlayer,
 };
 use crate::local_players::LocalPlayers;
-use crate::resources::{DynamicSplitState, MyPlayerId};
+use crate::resources::{ChatState, DynamicSplitState, MyPlayerId};
 
 pub fn camera_follow_player_and_ball(
     my_player_id: Res<MyPlayerId>,
@@ -175,8 +175,14 @@ pub fn camera_follow_player_and_ball(
 // Sistema de control de zoom con teclas numéricas
 pub fn camera_zoom_control(
     keyboard: Res<ButtonInput<KeyCode>>,
+    chat: Res<ChatState>,
     mut cameras: Query<&mut Projection, With<PlayerCamera>>,
 ) {
+    // Los números se escriben en el chat, no cambian el zoom
+    if chat.open {
+        return;
+    }
+
     let mut new_scale = None;
 
     // Teclas 1-9 para diferentes niveles de zoom
@@ -84,11 +84,21 @@ pub struct RemotePlayer {
     pub stamin_charge: f32,
     pub active_movement: Option<PlayerMovement>,
     pub mode_cube_active: bool,
+    /// Última posición confirmada por el host (base de la predicción local)
+    pub server_position: Vec2,
+    /// Último input que el host aplicó a este jugador
+    pub last_input_sequence: u32,
 }
 
 #[derive(Component)]
 pub struct RemoteBall;
 
+/// Disco móvil del mapa replicado desde el host
+#[derive(Component)]
+pub struct RemoteDisc {
+    pub index: u32,
+}
+
 #[derive(Component)]
 pub struct Interpolated {
     pub target_position: Vec2,
@@ -97,6 +107,120 @@ pub struct Interpolated {
     pub smoothing: f32,
 }
 
+/// Máximo de snapshots guardados por entidad (~0.5 s a 60 Hz)
+const SNAPSHOT_BUFFER_LEN: usize = 32;
+
+/// Cuánto se extrapola con la última velocidad si dejan de llegar snapshots (ms).
+/// Pasado ese tiempo la entidad se queda quieta hasta el próximo estado
+pub const MAX_EXTRAPOLATION_MS: f64 = 150.0;
+
+/// Estado de una entidad en un tick del host
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot {
+    pub tick: u32,
+    /// Timestamp del host en ms
+    pub server_time: f64,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    /// Solo los jugadores replican rotación
+    pub rotation: Option<f32>,
+}
+
+/// Historial de snapshots de una entidad remota, ordenado por tick.
+/// La entidad se dibuja con un retardo fijo interpolando entre los dos snapshots
+/// que rodean ese instante, así el jitter y el desorden de paquetes no se ven.
+#[derive(Component, Default)]
+pub struct SnapshotBuffer {
+    snapshots: std::collections::VecDeque<Snapshot>,
+}
+
+impl SnapshotBuffer {
+    /// Inserta un snapshot en orden; los duplicados y los ya descartados se ignoran
+    pub fn push(&mut self, snapshot: Snapshot) {
+        if self
+            .snapshots
+            .front()
+            .is_some_and(|first| snapshot.tick < first.tick)
+        {
+            return;
+        }
+        let index = self
+            .snapshots
+            .iter()
+            .rposition(|s| s.tick <= snapshot.tick)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        if index > 0 && self.snapshots[index - 1].tick == snapshot.tick {
+            return;
+        }
+        self.snapshots.insert(index, snapshot);
+        while self.snapshots.len() > SNAPSHOT_BUFFER_LEN {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Olvida todos los snapshots (saltos en un replay)
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// Descarta los snapshots que ya no hacen falta para dibujar `render_time`
+    /// (se conserva el último anterior a ese instante)
+    pub fn prune(&mut self, render_time: f64) {
+        while self.snapshots.len() > 1 && self.snapshots[1].server_time <= render_time {
+            self.snapshots.pop_front();
+        }
+    }
+
+    /// Estado de la entidad en `render_time` (ms del host): interpola entre los
+    /// snapshots vecinos o extrapola con la última velocidad hasta el límite
+    pub fn sample(&self, render_time: f64) -> Option<Snapshot> {
+        let first = self.snapshots.front()?;
+        if render_time <= first.server_time {
+            return Some(*first);
+        }
+
+        for (a, b) in self.snapshots.iter().zip(self.snapshots.iter().skip(1)) {
+            if render_time > b.server_time {
+                continue;
+            }
+            let span = b.server_time - a.server_time;
+            let t = if span > 0.0 {
+                ((render_time - a.server_time) / span) as f32
+            } else {
+                1.0
+            };
+            let rotation = match (a.rotation, b.rotation) {
+                (Some(ra), Some(rb)) => Some(lerp_angle(ra, rb, t)),
+                (_, rb) => rb,
+            };
+            return Some(Snapshot {
+                tick: b.tick,
+                server_time: render_time,
+                position: a.position.lerp(b.position, t),
+                velocity: a.velocity.lerp(b.velocity, t),
+                rotation,
+            });
+        }
+
+        // Sin snapshots nuevos: extrapolar con límite
+        let last = self.snapshots.back()?;
+        let ahead = (render_time - last.server_time).min(MAX_EXTRAPOLATION_MS);
+        Some(Snapshot {
+            server_time: render_time,
+            position: last.position + last.velocity * (ahead / 1000.0) as f32,
+            ..*last
+        })
+    }
+}
+
+/// Interpola ángulos tomando el camino más corto
+fn lerp_angle(from: f32, to: f32, t: f32) -> f32 {
+    let diff =
+        (to - from + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI;
+    from + diff * t
+}
+
 // ============================================================================
 // PLAYER UI COMPONENTS
 // ============================================================================
@@ -158,3 +282,125 @@ pub struct KeyVisual {
     pub player_id: u32,
     pub action: CurveAction,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    /// Snapshot de un tick a 60 Hz con la entidad en x = tick
+    fn snapshot(tick: u32) -> Snapshot {
+        Snapshot {
+            tick,
+            server_time: tick as f64 * 16.0,
+            position: Vec2::new(tick as f32, 0.0),
+            velocity: Vec2::new(1000.0, 0.0),
+            rotation: None,
+        }
+    }
+
+    fn ticks(buffer: &SnapshotBuffer) -> Vec<u32> {
+        buffer.snapshots.iter().map(|s| s.tick).collect()
+    }
+
+    #[test]
+    fn test_out_of_order_snapshots_are_sorted() {
+        let mut buffer = SnapshotBuffer::default();
+        for tick in [1, 3, 2, 5, 4] {
+            buffer.push(snapshot(tick));
+        }
+        assert_eq!(ticks(&buffer), vec![1, 2, 3, 4, 5]);
+
+        // Duplicado
+        buffer.push(snapshot(3));
+        assert_eq!(ticks(&buffer), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_stale_snapshots_are_ignored() {
+        let mut buffer = SnapshotBuffer::default();
+        for tick in 1..=5 {
+            buffer.push(snapshot(tick));
+        }
+        buffer.prune(snapshot(3).server_time);
+        assert_eq!(ticks(&buffer), vec![3, 4, 5]);
+
+        // Un paquete demorado de antes de lo descartado no vuelve a entrar
+        buffer.push(snapshot(2));
+        assert_eq!(ticks(&buffer), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_buffer_keeps_the_newest_snapshots() {
+        let mut buffer = SnapshotBuffer::default();
+        let count = SNAPSHOT_BUFFER_LEN as u32 + 10;
+        for tick in 1..=count {
+            buffer.push(snapshot(tick));
+        }
+        assert_eq!(buffer.snapshots.len(), SNAPSHOT_BUFFER_LEN);
+        assert_eq!(buffer.snapshots.front().unwrap().tick, 11);
+        assert_eq!(buffer.snapshots.back().unwrap().tick, count);
+    }
+
+    #[test]
+    fn test_sample_interpolates_between_neighbours() {
+        let mut buffer = SnapshotBuffer::default();
+        buffer.push(snapshot(10));
+        buffer.push(snapshot(12));
+
+        let sampled = buffer.sample(11.0 * 16.0).unwrap();
+        assert_eq!(sampled.tick, 12);
+        assert!((sampled.position.x - 11.0).abs() < 1e-4);
+
+        // Antes del primero se queda en el primero
+        let sampled = buffer.sample(0.0).unwrap();
+        assert_eq!(sampled.position.x, 10.0);
+    }
+
+    #[test]
+    fn test_extrapolation_is_capped() {
+        let mut buffer = SnapshotBuffer::default();
+        buffer.push(snapshot(10));
+        let last_time = snapshot(10).server_time;
+
+        // 50 ms a 1000 u/s
+        let sampled = buffer.sample(last_time + 50.0).unwrap();
+        assert!((sampled.position.x - 60.0).abs() < 1e-3);
+
+        // Pasado el límite no sigue avanzando
+        let limit_x = 10.0 + (MAX_EXTRAPOLATION_MS / 1000.0) as f32 * 1000.0;
+        let sampled = buffer.sample(last_time + 10_000.0).unwrap();
+        assert!((sampled.position.x - limit_x).abs() < 1e-3);
+        assert_eq!(sampled.tick, 10);
+    }
+
+    #[test]
+    fn test_lerp_angle_takes_the_short_way() {
+        // De 170° a -170° se cruza por ±180°, no por 0°
+        let from = 170f32.to_radians();
+        let to = -170f32.to_radians();
+        let half = lerp_angle(from, to, 0.5);
+        assert!(half.sin().abs() < 1e-4);
+        assert!(half.cos() < 0.0);
+
+        let end = lerp_angle(from, to, 1.0);
+        assert!((end.sin() - to.sin()).abs() < 1e-4);
+        assert!((end.cos() - to.cos()).abs() < 1e-4);
+
+        assert!(lerp_angle(-PI / 4.0, PI / 4.0, 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sample_interpolates_rotation_across_wraparound() {
+        let mut buffer = SnapshotBuffer::default();
+        let mut a = snapshot(1);
+        a.rotation = Some(PI - 0.1);
+        let mut b = snapshot(2);
+        b.rotation = Some(-PI + 0.1);
+        buffer.push(a);
+        buffer.push(b);
+
+        let rotation = buffer.sample(1.5 * 16.0).unwrap().rotation.unwrap();
+        assert!(rotation.cos() < -0.999);
+    }
+}
@@ -9,6 +9,14 @@ pub struct SpawnBallEvent {
     pub velocity: (f32, f32),
 }
 
+/// Evento para solicitar el spawning visual de un disco móvil del mapa
+#[derive(Message)]
+pub struct SpawnDiscEvent {
+    pub index: u32,
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+}
+
 /// Evento para solicitar el spawning visual de un jugador
 #[derive(Message)]
 pub struct SpawnPlayerEvent {
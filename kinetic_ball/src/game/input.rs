@@ -1,9 +1,21 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 
 use crate::components::RemotePlayer;
 use crate::keybindings::{GamepadBindingsMap, GilrsWrapper, KeyBindingsConfig};
 use crate::local_players::{read_local_player_input, LocalPlayers};
-use crate::resources::{MyPlayerId, NetworkChannels};
+use crate::replay::InstantReplay;
+use crate::resources::{ChatState, ClientPrediction, MyPlayerId, NetworkChannels};
+use crate::shared::protocol::PlayerInput;
+
+/// Lo que les quita el control a los jugadores locales
+#[derive(SystemParam)]
+pub struct InputBlockers<'w> {
+    /// Con el chat abierto el teclado escribe
+    pub chat: Res<'w, ChatState>,
+    /// Durante la repetición no se ve la cancha en vivo
+    pub instant_replay: Res<'w, InstantReplay>,
+}
 
 /// Sistema que lee input de todos los jugadores locales y lo envía al servidor
 pub fn handle_multi_player_input(
@@ -16,10 +28,25 @@ pub fn handle_multi_player_input(
     gamepad_bindings_map: Res<GamepadBindingsMap>,
     keybindings: Res<KeyBindingsConfig>,
     players: Query<&RemotePlayer>,
+    blockers: InputBlockers,
+    time: Res<Time>,
+    mut prediction: ResMut<ClientPrediction>,
 ) {
     let Some(ref sender) = channels.sender else {
         return;
     };
+    let dt = time.delta_secs();
+
+    // Mientras se escribe en el chat el teclado no mueve a nadie (los gamepads siguen)
+    let no_keys = ButtonInput::<KeyCode>::default();
+    let keyboard: &ButtonInput<KeyCode> = if blockers.chat.open {
+        &no_keys
+    } else {
+        &keyboard
+    };
+
+    // Durante la repetición se manda input neutro: nadie maneja una esfera que no ve
+    let replaying = blockers.instant_replay.is_playing();
 
     // Si no hay jugadores locales configurados, usar modo legacy (un jugador con teclado)
     if local_players.is_empty() {
@@ -35,10 +62,15 @@ pub fn handle_multi_player_input(
             .unwrap_or(false);
 
         // Leer input del teclado (modo legacy)
-        let input = crate::local_players::read_keyboard_input(&keyboard, &keybindings, is_cube_mode);
+        let input = if replaying {
+            PlayerInput::default()
+        } else {
+            crate::local_players::read_keyboard_input(keyboard, &keybindings, is_cube_mode)
+        };
 
-        // Enviar input con el player_id
-        if let Err(e) = sender.send((my_id, input)) {
+        // Enviar input con el player_id y su secuencia (se guarda para la predicción)
+        let sequence = prediction.record(my_id, input, dt);
+        if let Err(e) = sender.send((my_id, sequence, input)) {
             println!("⚠️ [Bevy] Error enviando input al canal: {:?}", e);
         }
         return;
@@ -78,18 +110,23 @@ pub fn handle_multi_player_input(
             .unwrap_or(false);
 
         // Leer input según el dispositivo asignado
-        let input = read_local_player_input(
-            local_player,
-            &keyboard,
-            &keybindings,
-            &gamepads,
-            gilrs.as_deref(),
-            &gamepad_bindings_map,
-            is_cube_mode,
-        );
+        let input = if replaying {
+            PlayerInput::default()
+        } else {
+            read_local_player_input(
+                local_player,
+                keyboard,
+                &keybindings,
+                &gamepads,
+                gilrs.as_deref(),
+                &gamepad_bindings_map,
+                is_cube_mode,
+            )
+        };
 
-        // Enviar input con el player_id del servidor
-        if let Err(e) = sender.send((server_id, input)) {
+        // Enviar input con el player_id del servidor y su secuencia
+        let sequence = prediction.record(server_id, input, dt);
+        if let Err(e) = sender.send((server_id, sequence, input)) {
             println!(
                 "⚠️ [Bevy] Error enviando input para jugador {} al canal: {:?}",
                 server_id, e
@@ -1,16 +1,37 @@
 use bevy::prelude::*;
 
-use crate::components::{CurveAction, Interpolated, KeyVisual, RemotePlayer, SlideCubeVisual};
+use crate::components::{
+    CurveAction, Interpolated, KeyVisual, RemotePlayer, SlideCubeVisual, SnapshotBuffer,
+};
 use crate::keybindings::{GamepadBindingsMap, GilrsWrapper, KeyBindingsConfig, RawGamepadInput};
 use crate::local_players::{idx_to_gilrs_axis, InputDevice, LocalPlayers};
-use crate::resources::GameTick;
+use crate::resources::{GameTick, SnapshotClock};
 use crate::shared::movements::{get_movement, AnimatedProperty};
 use crate::shared::protocol::GameConfig;
 
 // 3. Sistema de interpolación (Actualizado)
-pub fn interpolate_entities(time: Res<Time>, mut q: Query<(&mut Transform, &Interpolated)>) {
+pub fn interpolate_entities(
+    time: Res<Time>,
+    real_time: Res<Time<Real>>,
+    snapshot_clock: Res<SnapshotClock>,
+    mut q: Query<(&mut Transform, &Interpolated, Option<&mut SnapshotBuffer>)>,
+) {
     let dt = time.delta_secs();
-    for (mut transform, interp) in q.iter_mut() {
+    let render_time = snapshot_clock.render_time(real_time.elapsed_secs_f64() * 1000.0);
+    for (mut transform, interp, buffer) in q.iter_mut() {
+        // Entidades remotas: estado del host de hace `delay_ms`, sin suavizado extra
+        if let (Some(mut buffer), Some(render_time)) = (buffer, render_time) {
+            buffer.prune(render_time);
+            if let Some(snapshot) = buffer.sample(render_time) {
+                transform.translation.x = snapshot.position.x;
+                transform.translation.y = snapshot.position.y;
+                if let Some(rotation) = snapshot.rotation {
+                    transform.rotation = Quat::from_rotation_z(rotation);
+                }
+                continue;
+            }
+        }
+
         // Interpolar posición
         let prediction_offset = interp.target_velocity * dt;
         let effective_target = interp.target_position + prediction_offset;
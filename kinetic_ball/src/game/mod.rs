@@ -1,7 +1,9 @@
 mod setup;
 mod input;
 mod interpolation;
+mod prediction;
 
 pub use setup::{setup, spawn_key_visual_2d, cleanup_game};
 pub use input::handle_multi_player_input;
+pub use prediction::predict_local_players;
 pub use interpolation::{interpolate_entities, process_movements, animate_keys, is_gamepad_binding_active};
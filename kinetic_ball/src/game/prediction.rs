@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+
+use crate::components::{Interpolated, RemotePlayer};
+use crate::resources::ClientPrediction;
+use crate::shared::protocol::GameConfig;
+
+/// Predicción de los jugadores locales: parte de la última posición confirmada por el
+/// host, descarta los inputs que el host ya aplicó y re-aplica los que faltan.
+/// Los demás jugadores siguen con la interpolación normal.
+pub fn predict_local_players(
+    config: Res<GameConfig>,
+    mut prediction: ResMut<ClientPrediction>,
+    mut players_q: Query<(&RemotePlayer, &mut Interpolated, &mut Transform)>,
+) {
+    for (rp, mut interp, mut transform) in players_q.iter_mut() {
+        let Some(pending) = prediction.pending.get_mut(&rp.id) else {
+            continue;
+        };
+
+        // Reconciliación: lo confirmado ya está incluido en server_position
+        while pending
+            .front()
+            .is_some_and(|p| p.sequence <= rp.last_input_sequence)
+        {
+            pending.pop_front();
+        }
+
+        // Durante el slide el host maneja la velocidad: usar su estado tal cual
+        if rp.is_sliding {
+            continue;
+        }
+
+        let mut position = rp.server_position;
+        for p in pending.iter() {
+            position += config.player_velocity(&p.input, rp.mode_cube_active) * p.dt;
+        }
+        let velocity = pending
+            .back()
+            .map(|p| config.player_velocity(&p.input, rp.mode_cube_active))
+            .unwrap_or(Vec2::ZERO);
+
+        interp.target_position = position;
+        interp.target_velocity = velocity;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
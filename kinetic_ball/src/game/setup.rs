@@ -530,6 +530,14 @@ pub fn cleanup_game(
     mut local_players: ResMut<crate::local_players::LocalPlayers>,
     mut network_channels: ResMut<crate::resources::NetworkChannels>,
     mut loaded_map: ResMut<crate::resources::LoadedMap>,
+    mut match_score: ResMut<crate::resources::ClientMatchScore>,
+    mut chat: ResMut<crate::resources::ChatState>,
+    mut prediction: ResMut<crate::resources::ClientPrediction>,
+    mut snapshot_clock: ResMut<crate::resources::SnapshotClock>,
+    mut latencies: ResMut<crate::resources::PlayerLatencies>,
+    mut config: ResMut<GameConfig>,
+    mut recorder: ResMut<crate::replay::ReplayRecorder>,
+    mut instant_replay: ResMut<crate::replay::InstantReplay>,
 ) {
     println!("🧹 Limpiando entidades del juego...");
 
@@ -551,5 +559,29 @@ pub fn cleanup_game(
     // Resetear mapa cargado (para que is_changed() detecte el nuevo al reconectar)
     loaded_map.0 = None;
 
+    // Resetear marcador (el servidor envía el actual al reconectar)
+    *match_score = Default::default();
+
+    // El chat es por sala
+    *chat = Default::default();
+
+    // Las secuencias de input y los inputs pendientes son por conexión
+    *prediction = Default::default();
+
+    // El offset de reloj es del host al que estábamos conectados
+    snapshot_clock.reset();
+
+    // Las latencias son de los jugadores de esta sala
+    latencies.0.clear();
+
+    // Resetear la física aplicada por el mapa anterior
+    *config = GameConfig::default();
+
+    // Lo grabado hasta ahora se guarda; el mapa y los colores son de esta sala
+    recorder.reset();
+
+    // Los snapshots guardados para la repetición son de esta sala
+    *instant_replay = Default::default();
+
     println!("✅ {} entidades del juego limpiadas", count);
 }
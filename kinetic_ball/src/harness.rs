@@ -0,0 +1,500 @@
+// ============================================================================
+// HARNESS DE INTEGRACIÓN: HOST + CLIENTES EN UN SOLO PROCESO
+// ============================================================================
+//
+// Levanta un host sin ventana y N clientes simulados conectados por loopback. Cada
+// cliente usa el mismo hilo de red que el juego (`run_direct_client`): los tests
+// mandan inputs y mensajes de control como lo haría Bevy y revisan los
+// `ServerMessage` que recibe (GameState, SlotsUpdated, errores...).
+
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::assets::DEFAULT_MAP;
+use crate::host;
+use crate::networking::run_direct_client;
+use crate::shared::protocol::{
+    ControlMessage, MatchRules, PlayerInput, PlayerState, ServerMessage,
+};
+use crate::shared::MatchSlots;
+use crate::transport::{loopback, LoopbackConnector, LoopbackTransport};
+
+/// Cuánto se espera un mensaje antes de dar el test por fallido
+const WAIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Host corriendo en un hilo de este proceso. Se cierra cuando se van todos los
+/// clientes y se suelta el `TestHost`, que espera a que el hilo termine
+pub struct TestHost {
+    connector: Option<LoopbackConnector>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TestHost {
+    /// Host con el mapa embebido y partido sin límites
+    pub fn start() -> Self {
+        Self::start_with_rules(MatchRules {
+            time_limit_secs: 0,
+            score_limit: 0,
+        })
+    }
+
+    pub fn start_with_rules(rules: MatchRules) -> Self {
+        let (transport, connector) = loopback();
+        let thread = std::thread::spawn(move || {
+            host::host_loopback(None, DEFAULT_MAP, 1.0, rules, transport);
+        });
+        Self {
+            connector: Some(connector),
+            thread: Some(thread),
+        }
+    }
+
+    fn connector(&self) -> &LoopbackConnector {
+        self.connector.as_ref().expect("el host ya se cerró")
+    }
+
+    /// Cliente del juego que entra con un jugador y espera su WELCOME
+    pub fn join(&self, name: &str) -> TestClient {
+        let transport = self.connector().connect();
+        let (network_tx, network_rx) = mpsc::channel();
+        let (input_tx, input_rx) = mpsc::channel();
+        let (control_tx, control_rx) = mpsc::channel();
+        let player_names = vec![name.to_string()];
+        std::thread::spawn(move || {
+            run_direct_client(transport, player_names, network_tx, input_rx, control_rx);
+        });
+
+        let mut client = TestClient {
+            player_id: 0,
+            network_rx,
+            input_tx,
+            control_tx,
+            sequence: 0,
+            slots: None,
+        };
+        client.player_id = client.wait_for("WELCOME", |msg| match msg {
+            ServerMessage::Welcome { player_id, .. } => Some(*player_id),
+            _ => None,
+        });
+        client
+    }
+
+    /// Conexión sin cliente del juego, para mandar mensajes a mano (ej. un JOIN de
+    /// una versión vieja)
+    pub fn connect_raw(&self) -> LoopbackTransport {
+        self.connector().connect()
+    }
+}
+
+impl Drop for TestHost {
+    fn drop(&mut self) {
+        // Sin connector, el host se cierra cuando se desconecta el último cliente
+        self.connector = None;
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        // Si el test ya falló no hace falta esperar (y un panic acá abortaría)
+        if std::thread::panicking() {
+            return;
+        }
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+        while !thread.is_finished() {
+            assert!(
+                Instant::now() < deadline,
+                "el host no se cerró en {:?}",
+                WAIT_TIMEOUT
+            );
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        if thread.join().is_err() {
+            panic!("el hilo del host terminó con un panic");
+        }
+    }
+}
+
+/// Cliente simulado: un jugador local y lo que le llega del host
+pub struct TestClient {
+    pub player_id: u32,
+    network_rx: mpsc::Receiver<ServerMessage>,
+    input_tx: mpsc::Sender<(u32, u32, PlayerInput)>,
+    control_tx: mpsc::Sender<ControlMessage>,
+    /// Secuencia del último input enviado
+    sequence: u32,
+    /// Últimos slots recibidos
+    slots: Option<MatchSlots>,
+}
+
+impl TestClient {
+    /// Consume mensajes hasta que `matcher` devuelve algo; falla si no llega a tiempo
+    pub fn wait_for<T>(
+        &mut self,
+        what: &str,
+        mut matcher: impl FnMut(&ServerMessage) -> Option<T>,
+    ) -> T {
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let msg = match self.network_rx.recv_timeout(remaining) {
+                Ok(msg) => msg,
+                Err(_) => panic!(
+                    "jugador {}: no llegó {} en {:?}",
+                    self.player_id, what, WAIT_TIMEOUT
+                ),
+            };
+            if let ServerMessage::SlotsUpdated(slots) = &msg {
+                self.slots = Some(slots.clone());
+            }
+            if let Some(found) = matcher(&msg) {
+                return found;
+            }
+        }
+    }
+
+    /// Espera unos slots que cumplan `condition` (incluye los ya recibidos)
+    pub fn wait_for_slots(&mut self, condition: impl Fn(&MatchSlots) -> bool) -> MatchSlots {
+        if let Some(slots) = self.slots.clone().filter(|slots| condition(slots)) {
+            return slots;
+        }
+        self.wait_for("SlotsUpdated", |msg| match msg {
+            ServerMessage::SlotsUpdated(slots) if condition(slots) => Some(slots.clone()),
+            _ => None,
+        })
+    }
+
+    /// Espera un GameState en el que `condition` se cumple para el jugador `player_id`
+    pub fn wait_for_player(
+        &mut self,
+        player_id: u32,
+        condition: impl Fn(&PlayerState) -> bool,
+    ) -> PlayerState {
+        self.wait_for("GameState", |msg| match msg {
+            ServerMessage::GameState { players, .. } => players
+                .iter()
+                .find(|p| p.id == player_id && condition(p))
+                .cloned(),
+            _ => None,
+        })
+    }
+
+    /// Deja pasar `ticks` snapshots del host (el host ya procesó lo enviado antes)
+    pub fn wait_ticks(&mut self, ticks: u32) {
+        let start = self.wait_for("GameState", |msg| match msg {
+            ServerMessage::GameState { tick, .. } => Some(*tick),
+            _ => None,
+        });
+        self.wait_for("GameState", |msg| match msg {
+            ServerMessage::GameState { tick, .. } => (*tick >= start + ticks).then_some(()),
+            _ => None,
+        });
+    }
+
+    /// Envía un input de este jugador, como un frame del juego
+    pub fn send_input(&mut self, input: PlayerInput) {
+        self.sequence += 1;
+        let _ = self.input_tx.send((self.player_id, self.sequence, input));
+    }
+
+    /// Reproduce un guion de inputs, uno por snapshot recibido
+    pub fn play(&mut self, script: &[PlayerInput]) {
+        for input in script {
+            self.send_input(*input);
+            self.wait_ticks(1);
+        }
+    }
+
+    /// Mensaje de control como los del panel de admin o el chat
+    pub fn send_control(&self, msg: ControlMessage) {
+        let _ = self.control_tx.send(msg);
+    }
+}
+
+impl Drop for TestClient {
+    fn drop(&mut self) {
+        // Igual que al salir del juego: el hilo de red termina y el host lo quita
+        let _ = self.control_tx.send(ControlMessage::Leave {
+            player_id: self.player_id,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::protocol::{BotDifficulty, GameDataMessage, ProtocolVersion};
+    use crate::transport::{Channel, PeerEvent, PeerId, Transport};
+
+    fn holding(input: PlayerInput, ticks: usize) -> Vec<PlayerInput> {
+        vec![input; ticks]
+    }
+
+    #[test]
+    fn test_join_assigns_players_to_teams() {
+        let host = TestHost::start();
+        let mut admin = host.join("Admin");
+        let mut guest = host.join("Guest");
+        assert_ne!(admin.player_id, guest.player_id);
+
+        // El primero en entrar es admin; cada uno va de titular al equipo con menos
+        let (admin_id, guest_id) = (admin.player_id, guest.player_id);
+        let slots = admin.wait_for_slots(|s| s.is_starter(admin_id) && s.is_starter(guest_id));
+        assert!(slots.is_admin(admin_id));
+        assert!(!slots.is_admin(guest_id));
+        assert_ne!(
+            slots.get_team_index(admin_id),
+            slots.get_team_index(guest_id)
+        );
+
+        // Los dos aparecen en el GameState que recibe el otro
+        guest.wait_for_player(admin_id, |_| true);
+        admin.wait_for_player(guest_id, |_| true);
+    }
+
+    #[test]
+    fn test_scripted_input_moves_the_player() {
+        let host = TestHost::start();
+        let mut client = host.join("Runner");
+        let id = client.player_id;
+        let start = client.wait_for_player(id, |_| true).position;
+
+        let up = PlayerInput {
+            move_up: true,
+            ..Default::default()
+        };
+        client.play(&holding(up, 30));
+        client.play(&holding(PlayerInput::default(), 5));
+
+        let moved = client.wait_for_player(id, |_| true);
+        assert!(
+            moved.position.y > start.y + 10.0,
+            "el jugador no subió: {:?} -> {:?}",
+            start,
+            moved.position
+        );
+        assert!(moved.last_input_sequence > 0);
+    }
+
+    /// Espera la conexión con el host en un transporte crudo
+    fn raw_host_id(transport: &mut LoopbackTransport) -> PeerId {
+        match transport.update_peers().as_slice() {
+            [PeerEvent::Connected(peer_id)] => *peer_id,
+            events => panic!("se esperaba la conexión con el host: {:?}", events),
+        }
+    }
+
+    /// Lee mensajes de control de un transporte crudo hasta que `matcher` devuelve algo
+    fn raw_wait_for<T>(
+        transport: &mut LoopbackTransport,
+        what: &str,
+        mut matcher: impl FnMut(ControlMessage) -> Option<T>,
+    ) -> T {
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+        loop {
+            assert!(Instant::now() < deadline, "no llegó {}", what);
+            for (_, data) in transport.receive(Channel::Reliable) {
+                if let Some(found) = bincode::deserialize(&data).ok().and_then(&mut matcher) {
+                    return found;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// JOIN a mano (opcionalmente con un token de reconexión); devuelve el
+    /// player_id y el token del WELCOME
+    fn raw_join(
+        transport: &mut LoopbackTransport,
+        host_id: PeerId,
+        reconnect_token: Option<u64>,
+    ) -> (u32, u64) {
+        let join = ControlMessage::Join {
+            player_name: "Crudo".to_string(),
+            client_version: Some(ProtocolVersion::current()),
+            reconnect_token,
+        };
+        transport.send(
+            Channel::Reliable,
+            host_id,
+            bincode::serialize(&join).unwrap(),
+        );
+        raw_wait_for(transport, "WELCOME", |msg| match msg {
+            ControlMessage::Welcome {
+                player_id,
+                reconnect_token,
+                ..
+            } => Some((player_id, reconnect_token)),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_admin_can_kick_a_player() {
+        let host = TestHost::start();
+        let mut admin = host.join("Admin");
+        let mut guest = host.connect_raw();
+        let host_id = raw_host_id(&mut guest);
+        let (guest_id, token) = raw_join(&mut guest, host_id, None);
+        admin.wait_for_slots(|s| s.is_starter(guest_id));
+
+        admin.send_control(ControlMessage::KickPlayer {
+            player_id: guest_id,
+        });
+        raw_wait_for(&mut guest, "Kicked", |msg| match msg {
+            ControlMessage::Kicked { player_id } if player_id == guest_id => Some(()),
+            _ => None,
+        });
+        admin.wait_for("PlayerDisconnected", |msg| match msg {
+            ServerMessage::PlayerDisconnected { player_id } if *player_id == guest_id => Some(()),
+            _ => None,
+        });
+        let slots = admin.wait_for_slots(|s| s.find_player(guest_id) == (None, None));
+        assert!(!slots.is_spectator(guest_id));
+
+        // El expulsado ya no controla al jugador: sus inputs no lo traen de vuelta
+        let up = PlayerInput {
+            move_up: true,
+            ..Default::default()
+        };
+        for sequence in 1..=30 {
+            let input = GameDataMessage::Input {
+                player_id: guest_id,
+                sequence,
+                input: up,
+            };
+            guest.send(
+                Channel::Unreliable,
+                host_id,
+                bincode::serialize(&input).unwrap(),
+            );
+            admin.wait_for("GameState", |msg| match msg {
+                ServerMessage::GameState { players, .. } => {
+                    assert!(players.iter().all(|p| p.id != guest_id));
+                    Some(())
+                }
+                _ => None,
+            });
+        }
+
+        // Y su token no lo recupera: al volver entra como un jugador nuevo
+        drop(guest);
+        let mut rejoined = host.connect_raw();
+        let host_id = raw_host_id(&mut rejoined);
+        let (rejoined_id, _) = raw_join(&mut rejoined, host_id, Some(token));
+        assert_ne!(rejoined_id, guest_id);
+    }
+
+    #[test]
+    fn test_non_admin_cannot_kick() {
+        let host = TestHost::start();
+        let mut admin = host.join("Admin");
+        let mut guest = host.join("Guest");
+        let admin_id = admin.player_id;
+        admin.wait_for_slots(|s| s.is_starter(guest.player_id));
+
+        guest.send_control(ControlMessage::KickPlayer {
+            player_id: admin_id,
+        });
+        guest.wait_ticks(30);
+
+        // El admin sigue en el partido
+        admin.wait_for_player(admin_id, |_| true);
+        let slots = guest.wait_for_slots(|_| true);
+        assert!(slots.is_starter(admin_id));
+        assert!(slots.is_admin(admin_id));
+    }
+
+    #[test]
+    fn test_kicking_a_bot_removes_it() {
+        let host = TestHost::start();
+        let mut admin = host.join("Admin");
+        admin.send_control(ControlMessage::AddBot {
+            team_index: 1,
+            difficulty: BotDifficulty::Easy,
+        });
+        let slots = admin.wait_for_slots(|s| !s.bots.is_empty());
+        let bot_id = *slots.bots.keys().next().unwrap();
+
+        // Un bot no puede ser admin
+        admin.send_control(ControlMessage::ToggleAdmin {
+            player_id: bot_id,
+            is_admin: true,
+        });
+        admin.send_control(ControlMessage::KickPlayer { player_id: bot_id });
+        let slots = admin.wait_for_slots(|s| !s.is_starter(bot_id));
+        assert!(slots.bots.is_empty());
+        assert!(!slots.is_admin(bot_id));
+    }
+
+    #[test]
+    fn test_admin_can_move_a_player() {
+        let host = TestHost::start();
+        let mut admin = host.join("Admin");
+        let mut guest = host.join("Guest");
+        let guest_id = guest.player_id;
+        admin.wait_for_slots(|s| s.is_starter(guest_id));
+
+        // A suplente del equipo 0
+        admin.send_control(ControlMessage::MovePlayer {
+            player_id: guest_id,
+            team_index: Some(0),
+            is_starter: Some(false),
+        });
+        let slots = guest.wait_for_slots(|s| s.is_substitute(guest_id));
+        assert_eq!(slots.get_team_index(guest_id), Some(0));
+
+        // Y de vuelta como titular del equipo 1
+        admin.send_control(ControlMessage::MovePlayer {
+            player_id: guest_id,
+            team_index: Some(1),
+            is_starter: Some(true),
+        });
+        guest.wait_for_slots(|s| s.is_starter(guest_id) && s.get_team_index(guest_id) == Some(1));
+        guest.wait_for_player(guest_id, |p| p.team_index == 1);
+    }
+
+    #[test]
+    fn test_old_client_gets_version_mismatch() {
+        let host = TestHost::start();
+        let mut transport = host.connect_raw();
+        let host_id = raw_host_id(&mut transport);
+
+        let old = ProtocolVersion::new(0, 0, 1);
+        let join = ControlMessage::Join {
+            player_name: "Viejo".to_string(),
+            client_version: Some(old),
+            reconnect_token: None,
+        };
+        transport.send(
+            Channel::Reliable,
+            host_id,
+            bincode::serialize(&join).unwrap(),
+        );
+
+        let (client_version, min_required) =
+            raw_wait_for(&mut transport, "VersionMismatch", |msg| match msg {
+                ControlMessage::VersionMismatch {
+                    client_version,
+                    min_required,
+                    ..
+                } => Some((client_version, min_required)),
+                ControlMessage::Welcome { .. } => panic!("el host aceptó un cliente viejo"),
+                _ => None,
+            });
+        assert_eq!(client_version, old);
+        assert_eq!(min_required, ProtocolVersion::current());
+
+        // Rechazado: no recibe snapshots del partido
+        std::thread::sleep(Duration::from_millis(100));
+        let snapshots = transport
+            .receive(Channel::Unreliable)
+            .into_iter()
+            .filter(|(_, data)| {
+                matches!(
+                    bincode::deserialize::<GameDataMessage>(data),
+                    Ok(GameDataMessage::Snapshot(_))
+                )
+            })
+            .count();
+        assert_eq!(snapshots, 0);
+    }
+}
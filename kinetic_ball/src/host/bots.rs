@@ -0,0 +1,459 @@
+// ============================================================================
+// BOTS - JUGADORES CONTROLADOS POR EL HOST
+// ============================================================================
+//
+// Cada bot decide cada `reaction_secs` a dónde ir y qué tiro preparar, mirando
+// la física del host (pelota, compañeros, rivales y arcos). En cada tick traduce
+// esa decisión a un PlayerInput, con las mismas teclas que un jugador: correr,
+// cargar la patada (con o sin curva) y barrer en modo cubo.
+
+use std::collections::HashMap;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+
+use super::host::{Ball, GameInputManager, HostMatchSlots, LoadedMap, MatchPhase, Player, Sphere};
+use crate::shared::map::Map;
+use crate::shared::protocol::{BotDifficulty, GameConfig, PlayerInput};
+
+/// Máximo de bots por sala
+pub const MAX_BOTS: usize = 8;
+
+/// Distancia mínima al objetivo para seguir moviéndose
+const ARRIVE_DISTANCE: f32 = 6.0;
+
+/// Desde esta distancia a la pelota se suelta el sprint para poder patear
+const SPRINT_STOP_DISTANCE: f32 = 220.0;
+
+/// Lo que cambia entre dificultades
+struct Skill {
+    /// Segundos entre decisiones
+    reaction_secs: f32,
+    /// Error máximo de puntería (radianes)
+    aim_error: f32,
+    /// Carga de la patada (0 a 1)
+    shot_power: f32,
+    /// Stamina mínima para correr
+    sprint_stamina: f32,
+    /// Tira con curva cuando un rival tapa el tiro
+    curves: bool,
+    /// Barre (modo cubo) al rival que tiene la pelota
+    slides: bool,
+}
+
+fn skill(difficulty: BotDifficulty) -> Skill {
+    match difficulty {
+        BotDifficulty::Easy => Skill {
+            reaction_secs: 0.4,
+            aim_error: 0.3,
+            shot_power: 0.55,
+            sprint_stamina: 0.9,
+            curves: false,
+            slides: false,
+        },
+        BotDifficulty::Normal => Skill {
+            reaction_secs: 0.2,
+            aim_error: 0.12,
+            shot_power: 0.8,
+            sprint_stamina: 0.5,
+            curves: true,
+            slides: false,
+        },
+        BotDifficulty::Hard => Skill {
+            reaction_secs: 0.08,
+            aim_error: 0.04,
+            shot_power: 1.0,
+            sprint_stamina: 0.3,
+            curves: true,
+            slides: true,
+        },
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Curve {
+    None,
+    Left,
+    Right,
+}
+
+/// Tiro preparado: se carga al acercarse a la pelota y sale al tocarla
+#[derive(Clone, Copy)]
+struct Shot {
+    direction: Vec2,
+    power: f32,
+    curve: Curve,
+}
+
+/// Pasos de una barrida (cada uno dura un tick salvo `Sliding`)
+#[derive(Clone, Copy)]
+enum Tackle {
+    EnterCube,
+    Slide,
+    Sliding,
+    ExitCube,
+}
+
+#[derive(Default)]
+struct BotBrain {
+    /// Segundos hasta la próxima decisión
+    think_timer: f32,
+    target: Vec2,
+    sprint: bool,
+    shot: Option<Shot>,
+    tackle: Option<Tackle>,
+    /// Input del tick anterior (para soltar y volver a apretar botones)
+    last_input: PlayerInput,
+}
+
+/// Memoria de cada bot (por player_id)
+#[derive(Resource, Default)]
+pub struct BotBrains(HashMap<u32, BotBrain>);
+
+/// Lo que ve un bot al decidir
+struct Situation {
+    me: Vec2,
+    stamina: f32,
+    ball: Vec2,
+    ball_velocity: Vec2,
+    teammates: Vec<Vec2>,
+    opponents: Vec<Vec2>,
+    own_goal: Vec2,
+    target_goal: Vec2,
+    /// Medio ancho del arco rival
+    goal_half_size: f32,
+    /// Medio ancho de la cancha
+    half_width: f32,
+}
+
+/// Centro y medio ancho del arco que defiende un equipo
+fn goal_of(team_index: u8, map: Option<&Map>, half_width: f32) -> (Vec2, f32) {
+    let goal = map.and_then(|m| {
+        m.goals
+            .iter()
+            .find(|g| g.defending_team() == Some(team_index))
+    });
+    match goal {
+        Some(goal) => {
+            let p0 = Vec2::new(goal.p0[0], goal.p0[1]);
+            let p1 = Vec2::new(goal.p1[0], goal.p1[1]);
+            ((p0 + p1) / 2.0, p0.distance(p1) / 2.0)
+        }
+        // Equipo 0 defiende la izquierda
+        None => {
+            let side = if team_index == 0 { -1.0 } else { 1.0 };
+            (Vec2::new(side * half_width, 0.0), 100.0)
+        }
+    }
+}
+
+/// Dirección de movimiento en las 8 direcciones del teclado
+fn move_towards(input: &mut PlayerInput, direction: Vec2) {
+    if direction.length() < ARRIVE_DISTANCE {
+        return;
+    }
+    let dir = direction.normalize();
+    // ~22.5°: por debajo de eso el eje no se aprieta
+    input.move_right = dir.x > 0.38;
+    input.move_left = dir.x < -0.38;
+    input.move_up = dir.y > 0.38;
+    input.move_down = dir.y < -0.38;
+}
+
+/// Decide a dónde ir y qué tiro preparar
+fn think(brain: &mut BotBrain, situation: &Situation, skill: &Skill, config: &GameConfig) {
+    let s = situation;
+    let mut rng = rand::thread_rng();
+    let reach = config.sphere_radius + config.ball_radius;
+    let my_distance = s.me.distance(s.ball);
+
+    // El más cercano a la pelota de su equipo va a buscarla
+    let chaser = s
+        .teammates
+        .iter()
+        .all(|mate| mate.distance(s.ball) > my_distance);
+    // Del resto, el más cercano al arco propio cubre atrás
+    let mate_chasing = s
+        .teammates
+        .iter()
+        .min_by(|a, b| a.distance(s.ball).total_cmp(&b.distance(s.ball)));
+    let defender = s
+        .teammates
+        .iter()
+        .filter(|mate| Some(*mate) != mate_chasing)
+        .all(|mate| mate.distance(s.own_goal) > s.me.distance(s.own_goal));
+
+    brain.shot = None;
+    brain.sprint = false;
+
+    if !chaser {
+        brain.target = if defender {
+            s.own_goal + (s.ball - s.own_goal) * 0.3
+        } else {
+            // Apoyo: adelante de la pelota y del lado contrario
+            let ahead = s.ball + (s.target_goal - s.ball) * 0.35;
+            Vec2::new(ahead.x, -s.ball.y * 0.5)
+        };
+        brain.sprint = s.me.distance(brain.target) > 400.0 && s.stamina > skill.sprint_stamina;
+        return;
+    }
+
+    // Anticipar un poco el movimiento de la pelota
+    let lead = (my_distance / config.player_speed_walking).min(0.5);
+    let ball = s.ball + s.ball_velocity * lead;
+
+    // Apuntar al palo más lejano del rival más cercano al arco (el arquero)
+    let keeper_y = s
+        .opponents
+        .iter()
+        .min_by(|a, b| {
+            a.distance(s.target_goal)
+                .total_cmp(&b.distance(s.target_goal))
+        })
+        .map_or(0.0, |keeper| keeper.y - s.target_goal.y);
+    let corner = if keeper_y > 0.0 { -0.6 } else { 0.6 };
+    let aim = s.target_goal + Vec2::Y * corner * s.goal_half_size;
+    let mut direction = Vec2::from_angle(rng.gen_range(-skill.aim_error..=skill.aim_error))
+        .rotate((aim - ball).normalize_or_zero());
+
+    // Rival en la línea de tiro: se abre el tiro y la curva lo cierra
+    let mut curve = Curve::None;
+    let shot_distance = ball.distance(aim);
+    let blocker = s.opponents.iter().find(|o| {
+        let along = (**o - ball).dot(direction);
+        along > 0.0
+            && along < shot_distance
+            && (**o - ball).perp_dot(direction).abs() < config.sphere_radius * 2.0
+    });
+    let curved = skill.curves && shot_distance < s.half_width * 1.2;
+    if let Some(blocker) = blocker.filter(|_| curved) {
+        if direction.perp_dot(*blocker - ball) > 0.0 {
+            // Rival a la izquierda: sale a la derecha y dobla a la izquierda
+            direction = Vec2::from_angle(-0.35).rotate(direction);
+            curve = Curve::Left;
+        } else {
+            direction = Vec2::from_angle(0.35).rotate(direction);
+            curve = Curve::Right;
+        }
+    }
+
+    // Ponerse detrás de la pelota en la línea de tiro; si está del lado
+    // equivocado, rodearla
+    let behind = ball - direction * reach * 1.1;
+    let offset = s.me - ball;
+    brain.target = if offset.dot(direction) > -reach * 0.5 {
+        let side = if direction.perp_dot(offset) > 0.0 {
+            1.0
+        } else {
+            -1.0
+        };
+        ball - direction * reach * 2.5 + direction.perp() * side * reach * 2.0
+    } else if (ball - s.me).normalize_or_zero().dot(direction) > 0.85 && my_distance < reach * 3.0 {
+        // Alineado: caminar a través de la pelota
+        ball + direction * reach
+    } else {
+        behind
+    };
+
+    brain.shot = Some(Shot {
+        direction,
+        power: skill.shot_power,
+        curve,
+    });
+    brain.sprint = my_distance > SPRINT_STOP_DISTANCE && s.stamina > skill.sprint_stamina;
+
+    // Barrida al rival que tiene la pelota
+    let opponent_has_ball = s
+        .opponents
+        .iter()
+        .any(|o| o.distance(s.ball) < reach + 20.0 && o.distance(s.ball) < my_distance);
+    if skill.slides
+        && brain.tackle.is_none()
+        && opponent_has_ball
+        && my_distance < reach * 3.0
+        && s.stamina > config.slide_stamin_cost + 0.15
+    {
+        brain.tackle = Some(Tackle::EnterCube);
+    }
+}
+
+/// Traduce la decisión del bot al input de este tick
+fn act(
+    brain: &mut BotBrain,
+    player: &Player,
+    me: Vec2,
+    ball: Vec2,
+    config: &GameConfig,
+) -> PlayerInput {
+    let mut input = PlayerInput::default();
+    move_towards(&mut input, brain.target - me);
+
+    if let Some(step) = brain.tackle {
+        // Modo cubo, barrida hacia la pelota (el jugador siempre la mira) y de vuelta a esfera
+        brain.tackle = match step {
+            Tackle::EnterCube => {
+                input.mode = !player.mode_cube_active;
+                Some(Tackle::Slide)
+            }
+            Tackle::Slide if player.mode_cube_active => {
+                input.kick = true;
+                Some(Tackle::Sliding)
+            }
+            Tackle::Slide => None,
+            Tackle::Sliding if player.is_sliding => Some(Tackle::Sliding),
+            Tackle::Sliding => Some(Tackle::ExitCube),
+            Tackle::ExitCube => {
+                input.mode = player.mode_cube_active;
+                None
+            }
+        };
+        brain.last_input = input;
+        return input;
+    }
+
+    input.sprint = brain.sprint && me.distance(ball) > SPRINT_STOP_DISTANCE;
+
+    // Cargar la patada al acercarse; se suelta al llegar a la potencia buscada y
+    // sale al tocar la pelota (carga memorizada)
+    if let Some(shot) = brain.shot {
+        let kick_range = config.sphere_radius + config.ball_radius + 80.0;
+        let charging = player.kick_charging && player.kick_charge.x < shot.power;
+        let ready = !player.kick_charging && player.kick_charge.x <= 0.0;
+        let last_pressed =
+            brain.last_input.kick || brain.last_input.curve_left || brain.last_input.curve_right;
+        let aligned = (ball - me).normalize_or_zero().dot(shot.direction) > 0.7;
+        let press =
+            me.distance(ball) < kick_range && aligned && (charging || (ready && !last_pressed));
+        if press {
+            match shot.curve {
+                Curve::None => input.kick = true,
+                Curve::Left => input.curve_left = true,
+                Curve::Right => input.curve_right = true,
+            }
+        }
+    }
+
+    brain.last_input = input;
+    input
+}
+
+/// Posición y velocidad de los cuerpos que cumplen el filtro `F`
+type Bodies<'w, 's, F> = Query<'w, 's, (&'static Transform, &'static Velocity), F>;
+
+/// Lo que miran los bots: el mapa, los jugadores con sus esferas y la pelota
+#[derive(SystemParam)]
+pub struct BotView<'w, 's> {
+    pub loaded_map: Res<'w, LoadedMap>,
+    pub players: Query<'w, 's, &'static Player>,
+    pub sphere_query: Bodies<'w, 's, With<Sphere>>,
+    pub ball_query: Bodies<'w, 's, (With<Ball>, Without<Sphere>)>,
+}
+
+/// Decide el input de cada bot a partir del estado de la física
+pub fn drive_bots(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    phase: Res<MatchPhase>,
+    match_slots: Res<HostMatchSlots>,
+    mut game_input: ResMut<GameInputManager>,
+    mut brains: ResMut<BotBrains>,
+    view: BotView,
+) {
+    let BotView {
+        loaded_map,
+        players,
+        sphere_query,
+        ball_query,
+    } = view;
+
+    // Olvidar bots que ya no están
+    brains
+        .0
+        .retain(|id, _| game_input.bot_difficulty(*id).is_some());
+
+    let Ok((ball_transform, ball_velocity)) = ball_query.single() else {
+        return;
+    };
+    let ball = ball_transform.translation.truncate();
+    let map = loaded_map.0.as_ref();
+    let half_width = map
+        .and_then(|m| m.width)
+        .map(|w| w / 2.0)
+        .unwrap_or(config.arena_width / 2.0);
+
+    // Posición de cada titular en el campo
+    let positions: Vec<(u32, u8, Vec2)> = players
+        .iter()
+        .filter(|p| match_slots.0.is_starter(p.id))
+        .filter_map(|p| {
+            let team = match_slots.0.get_team_index(p.id)?;
+            let (transform, _) = sphere_query.get(p.sphere).ok()?;
+            Some((p.id, team, transform.translation.truncate()))
+        })
+        .collect();
+
+    for player in players.iter() {
+        let Some(difficulty) = game_input.bot_difficulty(player.id) else {
+            continue;
+        };
+        let brain = brains.0.entry(player.id).or_default();
+        let Some(&(_, team, me)) = positions.iter().find(|(id, ..)| *id == player.id) else {
+            // Suplente o espectador: quieto
+            brain.tackle = None;
+            game_input.set_bot_input(player.id, PlayerInput::default());
+            continue;
+        };
+
+        // Solo se juega con la pelota en juego; en el saque solo se mueve el que saca
+        let plays = match *phase {
+            MatchPhase::Playing => true,
+            MatchPhase::Kickoff { kicking_team } => {
+                kicking_team == team
+                    && positions
+                        .iter()
+                        .filter(|(_, t, _)| *t == team)
+                        .all(|(_, _, p)| p.distance(ball) >= me.distance(ball))
+            }
+            _ => false,
+        };
+        if !plays {
+            *brain = BotBrain::default();
+            game_input.set_bot_input(player.id, PlayerInput::default());
+            continue;
+        }
+
+        let skill = skill(difficulty);
+        brain.think_timer -= time.delta_secs();
+        if brain.think_timer <= 0.0 {
+            brain.think_timer = skill.reaction_secs;
+            let (own_goal, _) = goal_of(team, map, half_width);
+            let (target_goal, goal_half_size) = goal_of(1 - team, map, half_width);
+            let situation = Situation {
+                me,
+                stamina: player.stamin,
+                ball,
+                ball_velocity: ball_velocity.linvel,
+                teammates: positions
+                    .iter()
+                    .filter(|(id, t, _)| *t == team && *id != player.id)
+                    .map(|(_, _, p)| *p)
+                    .collect(),
+                opponents: positions
+                    .iter()
+                    .filter(|(_, t, _)| *t != team)
+                    .map(|(_, _, p)| *p)
+                    .collect(),
+                own_goal,
+                target_goal,
+                goal_half_size,
+                half_width,
+            };
+            think(brain, &situation, &skill, &config);
+        }
+
+        let input = act(brain, player, me, ball, &config);
+        game_input.set_bot_input(player.id, input);
+    }
+}
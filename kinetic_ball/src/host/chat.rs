@@ -0,0 +1,319 @@
+// ============================================================================
+// CHAT - VALIDACIÓN, LÍMITE DE MENSAJES Y REENVÍO
+// ============================================================================
+
+use crate::shared::protocol::{ChatKind, ChatMessage, ControlMessage, CHAT_MAX_LEN};
+use crate::shared::MatchSlots;
+use crate::transport::Channel;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use matchbox_socket::PeerId;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::host::{NetworkSender, OutgoingMessage, Player};
+
+/// Mensajes permitidos por jugador dentro de la ventana
+const CHAT_RATE_MESSAGES: usize = 4;
+
+/// Ventana del límite de mensajes, en segundos
+const CHAT_RATE_WINDOW_SECS: f64 = 5.0;
+
+/// Historial reciente de mensajes por jugador para limitar el spam
+#[derive(Resource, Default)]
+pub struct ChatRateLimiter {
+    sent: HashMap<u32, VecDeque<f64>>,
+}
+
+impl ChatRateLimiter {
+    /// Registra un mensaje si el jugador no superó el límite
+    pub fn allow(&mut self, player_id: u32, now: f64) -> bool {
+        let history = self.sent.entry(player_id).or_default();
+        while history
+            .front()
+            .is_some_and(|t| now - t >= CHAT_RATE_WINDOW_SECS)
+        {
+            history.pop_front();
+        }
+        if history.len() >= CHAT_RATE_MESSAGES {
+            return false;
+        }
+        history.push_back(now);
+        true
+    }
+
+    /// Olvida el historial de un jugador que salió de la sala
+    pub fn forget(&mut self, player_id: u32) {
+        self.sent.remove(&player_id);
+    }
+}
+
+/// Envía un mensaje de control por el canal reliable a los peers indicados (None = todos)
+fn send_chat(
+    network_tx: &NetworkSender,
+    message: ChatMessage,
+    recipients: Option<HashSet<PeerId>>,
+) {
+    let Ok(data) = bincode::serialize(&ControlMessage::Chat(message)) else {
+        return;
+    };
+    match recipients {
+        None => {
+            let _ = network_tx.0.send(OutgoingMessage::Broadcast {
+                channel: Channel::Reliable,
+                data,
+            });
+        }
+        Some(peers) => {
+            for peer_id in peers {
+                let _ = network_tx.0.send(OutgoingMessage::ToOne {
+                    peer_id,
+                    channel: Channel::Reliable,
+                    data: data.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Aviso del host a un solo peer
+fn notify(network_tx: &NetworkSender, peer_id: PeerId, text: &str) {
+    let message = ChatMessage {
+        sender_id: None,
+        sender_name: "Host".to_string(),
+        team_index: None,
+        kind: ChatKind::System,
+        text: text.to_string(),
+    };
+    send_chat(network_tx, message, Some(HashSet::from([peer_id])));
+}
+
+/// Reenvío de chat del host: canal de salida, límite de mensajes y reloj
+#[derive(SystemParam)]
+pub struct ChatRelay<'w> {
+    pub network_tx: Res<'w, NetworkSender>,
+    pub limiter: ResMut<'w, ChatRateLimiter>,
+    pub time: Res<'w, Time>,
+}
+
+impl ChatRelay<'_> {
+    /// Valida un mensaje de chat de un cliente y lo reenvía a su audiencia.
+    /// El jugador tiene que pertenecer al peer que lo envía; los anuncios son solo de admins
+    /// y los mensajes de equipo llegan a los peers con jugadores en ese equipo (o espectadores).
+    pub fn relay(
+        &mut self,
+        match_slots: &MatchSlots,
+        players: &[&Player],
+        peer_id: PeerId,
+        player_id: u32,
+        kind: ChatKind,
+        text: String,
+    ) {
+        let Some(sender) = players
+            .iter()
+            .find(|p| p.id == player_id && p.peer_id == peer_id)
+        else {
+            println!(
+                "⚠️ Chat descartado: jugador {} no pertenece al peer {:?}",
+                player_id, peer_id
+            );
+            return;
+        };
+
+        let text: String = text.trim().chars().take(CHAT_MAX_LEN).collect();
+        if text.is_empty() || kind == ChatKind::System {
+            return;
+        }
+
+        if kind == ChatKind::Announcement && !match_slots.is_admin(player_id) {
+            notify(&self.network_tx, peer_id, "Solo los admins pueden hacer anuncios");
+            return;
+        }
+
+        if !self.limiter.allow(player_id, self.time.elapsed_secs_f64()) {
+            notify(&self.network_tx, peer_id, "Estás enviando mensajes muy rápido");
+            return;
+        }
+
+        let team_index = match_slots.get_team_index(player_id);
+        let recipients = match kind {
+            ChatKind::Team => Some(
+                players
+                    .iter()
+                    .filter(|p| match_slots.get_team_index(p.id) == team_index)
+                    .map(|p| p.peer_id)
+                    .collect(),
+            ),
+            _ => None,
+        };
+
+        println!("💬 [{:?}] {}: {}", kind, sender.name, text);
+
+        send_chat(
+            &self.network_tx,
+            ChatMessage {
+                sender_id: Some(player_id),
+                sender_name: sender.name.clone(),
+                team_index,
+                kind,
+                text,
+            },
+            recipients,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::engine::spawn_physics;
+    use crate::shared::protocol::GameConfig;
+    use crate::transport::next_peer_id;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::sync::mpsc;
+
+    /// Sala con los jugadores 1 y 3 en rojo, el 2 en azul y el 4 de espectador
+    struct Room {
+        world: World,
+        outgoing: mpsc::Receiver<OutgoingMessage>,
+        slots: MatchSlots,
+        peers: Vec<PeerId>,
+    }
+
+    impl Room {
+        fn new() -> Self {
+            let mut world = World::new();
+            let (network_tx, outgoing) = mpsc::channel();
+            world.insert_resource(NetworkSender(network_tx));
+            world.insert_resource(ChatRateLimiter::default());
+            world.insert_resource(Time::<()>::default());
+            world.insert_resource(GameConfig::default());
+
+            let peers: Vec<PeerId> = (0..4).map(|_| next_peer_id()).collect();
+            let mut slots = MatchSlots::default();
+            slots.add_spectator(4);
+            let spawned = peers.clone();
+            let slots = world
+                .run_system_once(move |mut commands: Commands, config: Res<GameConfig>| {
+                    let mut slots = slots.clone();
+                    for (id, peer_id) in (1..).zip(&spawned) {
+                        let name = format!("P{}", id);
+                        spawn_physics(&mut commands, id, name, *peer_id, &config, None, &mut slots);
+                    }
+                    slots
+                })
+                .unwrap();
+            Self {
+                world,
+                outgoing,
+                slots,
+                peers,
+            }
+        }
+
+        /// Envía un mensaje como `player_id` desde el peer del jugador `from`; devuelve
+        /// (destinatario, mensaje) de lo que salió del host (None = broadcast)
+        fn chat(
+            &mut self,
+            from: u32,
+            player_id: u32,
+            kind: ChatKind,
+            text: &str,
+        ) -> Vec<(Option<PeerId>, ChatMessage)> {
+            let slots = self.slots.clone();
+            let peer_id = self.peers[from as usize - 1];
+            let text = text.to_string();
+            self.world
+                .run_system_once(move |mut chat: ChatRelay, players: Query<&Player>| {
+                    let players: Vec<&Player> = players.iter().collect();
+                    chat.relay(&slots, &players, peer_id, player_id, kind, text.clone());
+                })
+                .unwrap();
+
+            self.outgoing
+                .try_iter()
+                .map(|message| {
+                    let (to, data) = match message {
+                        OutgoingMessage::ToOne { peer_id, data, .. } => (Some(peer_id), data),
+                        OutgoingMessage::Broadcast { data, .. } => (None, data),
+                    };
+                    match bincode::deserialize(&data).unwrap() {
+                        ControlMessage::Chat(message) => (to, message),
+                        other => panic!("se esperaba un chat: {:?}", other),
+                    }
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_window_rolls_over() {
+        let mut limiter = ChatRateLimiter::default();
+        for i in 0..CHAT_RATE_MESSAGES {
+            assert!(limiter.allow(1, i as f64 * 0.5));
+        }
+        assert!(!limiter.allow(1, 2.0));
+        // Cada jugador tiene su propio límite
+        assert!(limiter.allow(2, 2.0));
+
+        // El primer mensaje sale de la ventana justo a los CHAT_RATE_WINDOW_SECS
+        assert!(!limiter.allow(1, CHAT_RATE_WINDOW_SECS - 0.01));
+        assert!(limiter.allow(1, CHAT_RATE_WINDOW_SECS));
+        assert!(!limiter.allow(1, CHAT_RATE_WINDOW_SECS + 0.1));
+
+        limiter.forget(1);
+        assert!(limiter.allow(1, CHAT_RATE_WINDOW_SECS + 0.1));
+    }
+
+    #[test]
+    fn test_team_messages_reach_only_the_team() {
+        let mut room = Room::new();
+
+        let sent = room.chat(3, 3, ChatKind::Team, "  vamos  ");
+        let mut recipients: Vec<PeerId> = sent.iter().filter_map(|(to, _)| *to).collect();
+        recipients.sort();
+        let mut red = vec![room.peers[0], room.peers[2]];
+        red.sort();
+        assert_eq!(recipients, red);
+        assert!(sent
+            .iter()
+            .all(|(_, m)| m.text == "vamos" && m.team_index == Some(0)));
+
+        // Los espectadores hablan entre ellos
+        let sent = room.chat(4, 4, ChatKind::Team, "hola");
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, Some(room.peers[3]));
+        assert_eq!(sent[0].1.team_index, None);
+
+        let sent = room.chat(2, 2, ChatKind::All, "hola a todos");
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, None);
+    }
+
+    #[test]
+    fn test_invalid_messages_are_not_relayed() {
+        let mut room = Room::new();
+
+        // Jugador que no es del peer que envía
+        assert!(room.chat(1, 2, ChatKind::All, "suplantado").is_empty());
+        assert!(room.chat(1, 1, ChatKind::All, "   ").is_empty());
+        assert!(room.chat(1, 1, ChatKind::System, "aviso falso").is_empty());
+
+        // Anuncio sin ser admin: solo vuelve un aviso del host
+        let sent = room.chat(1, 1, ChatKind::Announcement, "atención");
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, Some(room.peers[0]));
+        assert_eq!(sent[0].1.kind, ChatKind::System);
+    }
+
+    #[test]
+    fn test_flooding_players_are_warned() {
+        let mut room = Room::new();
+        for _ in 0..CHAT_RATE_MESSAGES {
+            assert_eq!(room.chat(2, 2, ChatKind::All, "spam")[0].1.kind, ChatKind::All);
+        }
+        let sent = room.chat(2, 2, ChatKind::All, "spam");
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].0, Some(room.peers[1]));
+        assert_eq!(sent[0].1.kind, ChatKind::System);
+    }
+}
@@ -2,6 +2,7 @@
 // SISTEMAS DE FÍSICA DEL JUEGO
 // ============================================================================
 
+use crate::shared::map::Map;
 use crate::shared::movements::{get_movement, movement_ids};
 use crate::shared::protocol::PlayerMovement;
 use crate::shared::{GameConfig, TICK_RATE};
@@ -9,18 +10,34 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use matchbox_socket::PeerId;
 
-use super::host::{Ball, GameInputManager, GameTick, Player, SlideCube, Sphere};
+use super::host::{Ball, GameInputManager, GameTick, LoadedMap, Player, SlideCube, Sphere};
 use super::input::GameAction;
+use super::match_state::team_spawn_position;
+use crate::host::map::collision;
 use crate::host::map::converter::MapConverter;
 use crate::host::map::loader;
 use rand::Rng;
 
 const DEFAULT_MAP: &str = include_str!("../../assets/cancha_grande.hbs");
 
-pub fn setup_map(mut commands: Commands) {
-    let map = loader::load_map_from_str(DEFAULT_MAP, "default_map").unwrap();
+pub fn setup_map(
+    mut commands: Commands,
+    loaded_map: Res<LoadedMap>,
+    ball_query: Query<Entity, With<Ball>>,
+) {
     let converter = MapConverter::new();
-    converter.spawn_map_geometry(&mut commands, &map);
+    // La pelota es el disco 0 para los joints del mapa
+    let ball = ball_query.iter().next();
+
+    // Usar el mapa cargado (el mismo que se envía en Welcome y del que salen los goles)
+    if let Some(map) = &loaded_map.0 {
+        converter.spawn_map_geometry(&mut commands, map, ball);
+        info!("Mapa '{}' spawneado en el host", map.name);
+        return;
+    }
+
+    let map = loader::load_map_from_str(DEFAULT_MAP, "default_map").unwrap();
+    converter.spawn_map_geometry(&mut commands, &map, ball);
     info!("Mapa por defecto spawneado en el host");
 }
 
@@ -52,15 +69,27 @@ pub fn spawn_physics(
     name: String,
     peer_id: PeerId,
     config: &Res<GameConfig>,
+    map: Option<&Map>,
     match_slots: &mut crate::shared::MatchSlots,
-) {
-    // Add player as starter by default (alternating teams based on ID)
-    let team_index = (id % 2) as u8;
-    match_slots.add_starter(id, team_index);
-    // Spawn física del jugador (Sphere)
-    let spawn_x = ((id % 3) as f32 - 1.0) * 200.0;
-    let spawn_y = ((id / 3) as f32 - 1.0) * 200.0;
+) -> Entity {
+    // Add player as starter on the team with fewer starters, unless they already
+    // have a slot (players restored after a host migration)
+    let team_index = match match_slots.find_player(id) {
+        (Some(team_index), _) => team_index,
+        _ if match_slots.is_spectator(id) => 0,
+        _ => {
+            let team_index = match_slots.team_with_fewer_starters();
+            match_slots.add_starter(id, team_index);
+            team_index
+        }
+    };
+
+    // Spawn física del jugador (Sphere) en su mitad de cancha
+    let slot = match_slots.starter_slot(id).unwrap_or(0);
+    let spawn = team_spawn_position(team_index, slot, config, map);
+    let (spawn_x, spawn_y) = (spawn.x, spawn.y);
 
+    let groups = collision::player_groups(team_index, None);
     let sphere_entity = commands
         .spawn((
             Sphere,
@@ -69,9 +98,9 @@ pub fn spawn_physics(
             RigidBody::Dynamic,
             Collider::ball(config.sphere_radius),
             Velocity::zero(),
-            // Jugador: colisiona con todo EXCEPTO líneas solo-pelota (GROUP_5)
-            CollisionGroups::new(Group::GROUP_4, Group::ALL ^ Group::GROUP_5),
-            SolverGroups::new(Group::GROUP_4, Group::ALL ^ Group::GROUP_5),
+            // Jugador: grupo de su equipo, colisiona con pelota, jugadores y paredes
+            groups,
+            SolverGroups::new(groups.memberships, groups.filters),
             Friction {
                 coefficient: config.sphere_friction,
                 combine_rule: CoefficientCombineRule::Min,
@@ -102,9 +131,6 @@ pub fn spawn_physics(
         ))
         .id();
 
-    // Asignar equipo basado en ID (par = 0, impar = 1)
-    let team_index = (id % 2) as u8;
-
     // Spawn lógica del jugador (Player) - Usando peer_id ahora
     commands.spawn(Player {
         sphere: sphere_entity,
@@ -116,6 +142,7 @@ pub fn spawn_physics(
         kick_memory_timer: 0.0,
         peer_id,
         is_ready: false,
+        disconnected_at: None,
         not_interacting: false,
         is_sliding: false,
         slide_direction: Vec2::ZERO,
@@ -131,6 +158,7 @@ pub fn spawn_physics(
     });
 
     println!("✅ Jugador {} spawneado: {}", id, name);
+    sphere_entity
 }
 
 pub fn move_players(
@@ -168,24 +196,20 @@ pub fn move_players(
 
             if movement.length() > 0.0 {
                 let run_stamin_cost = time.delta_secs() * config.run_stamin_coeficient_cost;
+                let sprint = game_input.is_pressed(player_id, GameAction::Sprint);
+                let cube_mode = player.mode_cube_active;
 
-                let move_coeficient = if player.mode_cube_active
-                    && game_input.is_pressed(player_id, GameAction::Sprint)
-                {
+                if cube_mode && sprint {
                     player.stamin -= (1.0 + config.run_cube_coeficient - config.run_coeficient)
                         * run_stamin_cost;
-                    config.run_cube_coeficient
-                } else if player.mode_cube_active
-                    || game_input.is_pressed(player_id, GameAction::Sprint)
-                {
+                } else if cube_mode || sprint {
                     player.stamin -= run_stamin_cost;
-                    config.run_coeficient
-                } else {
-                    1.0
-                };
+                }
 
-                velocity.linvel =
-                    movement.normalize_or_zero() * config.player_speed_walking * move_coeficient;
+                // Misma fórmula que usa el cliente para predecir (GameConfig::player_velocity)
+                velocity.linvel = movement.normalize_or_zero()
+                    * config.player_speed_walking
+                    * config.move_coeficient(sprint, cube_mode);
             } else {
                 velocity.linvel = Vec2::ZERO;
             }
@@ -197,7 +221,7 @@ pub fn move_players(
 pub fn handle_collision_player(
     game_input: Res<GameInputManager>,
     mut player_query: Query<&mut Player>,
-    mut sphere_query: Query<&mut SolverGroups, With<Sphere>>,
+    mut sphere_query: Query<(&CollisionGroups, &mut SolverGroups), With<Sphere>>,
 ) {
     for mut player in player_query.iter_mut() {
         // En modo cubo siempre ignora colisión, en modo normal solo con StopInteract
@@ -205,13 +229,14 @@ pub fn handle_collision_player(
             player.mode_cube_active || game_input.is_pressed(player.id, GameAction::StopInteract);
         player.not_interacting = should_ignore_ball;
 
-        if let Ok(mut solver_groups) = sphere_query.get_mut(player.sphere) {
+        if let Ok((collision_groups, mut solver_groups)) = sphere_query.get_mut(player.sphere) {
+            solver_groups.memberships = collision_groups.memberships;
             if should_ignore_ball {
-                // No respuesta física con pelota (GROUP_3), sí con jugadores (GROUP_4) y paredes
-                solver_groups.filters = Group::ALL ^ Group::GROUP_3;
+                // No respuesta física con pelota, sí con jugadores y paredes
+                solver_groups.filters = collision_groups.filters & !collision::BALL;
             } else {
-                // Modo normal: respuesta física con todos
-                solver_groups.filters = Group::ALL;
+                // Modo normal: respuesta física con todo lo que colisiona
+                solver_groups.filters = collision_groups.filters;
             }
         }
     }
@@ -627,10 +652,11 @@ fn changing_mode(
 
         // Cubo grande con colisiones
         let cube_size = config.sphere_radius * 1.2;
+        let groups = collision::player_groups(player.team_index, None);
         commands.entity(cube_entity).insert((
             Collider::cuboid(cube_size, cube_size),
-            CollisionGroups::new(Group::GROUP_4, Group::ALL ^ Group::GROUP_5),
-            SolverGroups::new(Group::GROUP_4, Group::ALL ^ Group::GROUP_5),
+            groups,
+            SolverGroups::new(groups.memberships, groups.filters),
             Restitution {
                 coefficient: 0.8,
                 combine_rule: CoefficientCombineRule::Max,
@@ -863,7 +889,10 @@ pub fn execute_slide(
                     let size = (config.sphere_radius / 1.5) * player.slide_cube_scale;
                     commands.entity(cube_entity).insert((
                         Collider::cuboid(size / 2.0, size / 2.0),
-                        CollisionGroups::new(Group::GROUP_4, Group::GROUP_3),
+                        CollisionGroups::new(
+                            collision::team_group(player.team_index),
+                            collision::BALL,
+                        ),
                         Restitution {
                             coefficient: 1.5,
                             combine_rule: CoefficientCombineRule::Max,
@@ -1,13 +1,26 @@
+use crate::shared::handoff::HostHandoff;
+use crate::shared::map::{Map, MAX_MAP_SCALE, MIN_MAP_SCALE};
+use crate::shared::snapshot::{PlayerInfo, SnapshotEncoder};
 use crate::shared::*;
+use crate::transport::{loopback, Channel, LoopbackTransport, UdpTransport};
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use matchbox_socket::PeerId;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 
+use super::bots::{drive_bots, BotBrains};
+use super::chat::ChatRateLimiter;
 use super::engine::*;
-use super::input::{GameAction, InputSource, NetworkInputSource};
+use super::input::{BotInputSource, GameAction, InputSource, NetworkInputSource};
+use super::latency::{broadcast_latency, PeerLatency};
+use super::match_state::*;
+use super::migration::{
+    replicate_to_successor, restore_handoff_bodies, restore_handoff_players, HostMigration,
+    PendingHandoff,
+};
 use super::network::*;
+use super::reconnect::{expire_disconnected_players, ReconnectSessions};
 
 /// Resource for managing player slots in the match
 #[derive(Resource, Default)]
@@ -21,14 +34,122 @@ pub fn host(
     server_host: String,
     room_name: String,
     max_players: u8,
+    rules: MatchRules,
+    access: RoomAccess,
 ) {
     println!("🎮 Haxball Host - Iniciando...");
+    println!(
+        "⏱️  Reglas: tiempo {}s, goles {} (0 = sin límite)",
+        rules.time_limit_secs, rules.score_limit
+    );
+    if access.password.as_deref().is_some_and(|p| !p.is_empty()) {
+        println!("🔒 Sala con contraseña");
+    }
+    if access.private {
+        println!("🙈 Sala privada: no aparece en la lista, se entra por ID");
+    }
 
     // Clone map path for later use in proxy registration
     let map_name_for_proxy = map.clone();
 
+    let (game_config, loaded_map) = load_game_config(map, default_map_content, scale);
+
+    let registration = RoomRegistration {
+        room,
+        server_host,
+        room_name,
+        max_players,
+        map_name: map_name_for_proxy,
+        access,
+        takeover_token: None,
+    };
+    run_host(
+        game_config,
+        loaded_map,
+        rules,
+        HostLink::Proxy(registration),
+        None,
+    );
+}
+
+/// Host en la red local: los clientes se conectan por UDP directo a `bind_addr`,
+/// sin proxy ni señalización
+pub fn host_lan(
+    map: Option<String>,
+    default_map_content: &'static str,
+    scale: f32,
+    bind_addr: String,
+    rules: MatchRules,
+) {
+    println!("🎮 Haxball Host - Red local");
+    let transport = match UdpTransport::listen(bind_addr.as_str()) {
+        Ok(transport) => transport,
+        Err(e) => {
+            eprintln!("❌ No se pudo escuchar en {}: {}", bind_addr, e);
+            return;
+        }
+    };
+    if let Ok(addr) = transport.local_addr() {
+        println!("🏠 Escuchando clientes en {}", addr);
+    }
+    let (game_config, loaded_map) = load_game_config(map, default_map_content, scale);
+    run_host(
+        game_config,
+        loaded_map,
+        rules,
+        HostLink::Lan(transport),
+        None,
+    );
+}
+
+/// Partido de práctica sin conexión: el host corre en un hilo de este proceso y
+/// habla solo con el cliente que recibe el transporte devuelto (sin proxy ni WebRTC)
+pub fn practice(
+    map: Option<String>,
+    default_map_content: &'static str,
+    scale: f32,
+    rules: MatchRules,
+) -> LoopbackTransport {
+    println!("🏋️ Haxball Host - Práctica sin conexión");
+    // Sin connectors vivos, el host se cierra cuando el cliente se va
+    let (host_transport, connector) = loopback();
+    let client_transport = connector.connect();
+    std::thread::spawn(move || {
+        host_loopback(map, default_map_content, scale, rules, host_transport);
+    });
+    client_transport
+}
+
+/// Host cuyos clientes llegan por un loopback en este proceso (práctica y tests).
+/// Bloquea hasta que el loopback se cierra
+pub fn host_loopback(
+    map: Option<String>,
+    default_map_content: &'static str,
+    scale: f32,
+    rules: MatchRules,
+    transport: LoopbackTransport,
+) {
+    let (game_config, loaded_map) = load_game_config(map, default_map_content, scale);
+    run_host(
+        game_config,
+        loaded_map,
+        rules,
+        HostLink::Local(transport),
+        None,
+    );
+}
+
+/// Carga el mapa (externo o el embebido por defecto) y arma la configuración del juego
+fn load_game_config(
+    map: Option<String>,
+    default_map_content: &str,
+    scale: f32,
+) -> (GameConfig, Option<Map>) {
+    // Mismo rango que ofrece el menú de crear sala
+    let scale = scale.clamp(MIN_MAP_SCALE, MAX_MAP_SCALE);
+
     // Configurar GameConfig con el mapa
-    let (game_config, loaded_map) = if let Some(map_path) = map {
+    let (mut game_config, loaded_map) = if let Some(map_path) = map {
         // Cargar mapa externo
         println!("🗺️  Cargando mapa: {}", map_path);
 
@@ -83,43 +204,128 @@ pub fn host(
         (config, loaded_map)
     };
 
-    println!("🌐 Conectando al proxy en: {}", server_host);
+    // Física propia del mapa (playerPhysics / ballPhysics)
+    if let Some(m) = &loaded_map {
+        m.apply_physics(&mut game_config);
+    }
+
+    (game_config, loaded_map)
+}
+
+/// Toma la sala de un host que se fue, siguiendo el partido con el estado que
+/// replicó a este cliente (migración de host)
+pub fn resume_host(handoff: HostHandoff, map: Option<Map>, server_host: String) {
+    println!(
+        "🧭 Tomando la sala '{}' como nuevo host (tick {})",
+        handoff.room_id, handoff.tick
+    );
+    let registration = RoomRegistration {
+        room: handoff.room_id.clone(),
+        server_host,
+        room_name: handoff.room_name.clone(),
+        max_players: handoff.max_players,
+        map_name: handoff.map_name.clone(),
+        // El proxy conserva la contraseña y la visibilidad de la sala
+        access: RoomAccess::default(),
+        takeover_token: Some(handoff.takeover_token.clone()),
+    };
+    run_host(
+        handoff.config.clone(),
+        map,
+        handoff.rules,
+        HostLink::Proxy(registration),
+        Some(handoff),
+    );
+}
+
+/// Cómo llegan los clientes al host
+enum HostLink {
+    /// Sala registrada en el proxy, clientes por WebRTC
+    Proxy(RoomRegistration),
+    /// Red local: clientes por UDP directo
+    Lan(UdpTransport),
+    /// Práctica sin conexión: un único cliente en este proceso
+    Local(LoopbackTransport),
+}
 
+fn run_host(
+    game_config: GameConfig,
+    loaded_map: Option<Map>,
+    rules: MatchRules,
+    link: HostLink,
+    handoff: Option<HostHandoff>,
+) {
     let (network_tx, network_rx) = mpsc::channel();
     let (outgoing_tx, outgoing_rx) = mpsc::channel();
 
-    // Clonar loaded_map para usarlo en ambos lugares
     // La versión mínima del cliente es la versión actual del protocolo
     let min_version = protocol::ProtocolVersion::current();
     println!("📋 Versión del servidor: {}", min_version);
     let network_state = Arc::new(Mutex::new(NetworkState {
-        next_player_id: 1,
+        next_player_id: handoff.as_ref().map_or(1, |h| h.next_player_id),
         min_client_version: min_version,
+        player_owners: std::collections::HashMap::new(),
+        reported_spoofs: std::collections::HashSet::new(),
+        sessions: handoff
+            .as_ref()
+            .map_or_else(ReconnectSessions::default, |h| {
+                ReconnectSessions::restore(&h.sessions, std::time::Instant::now())
+            }),
+        proxy_token: None,
+        successor: None,
+        takeover_token: None,
+        host_candidates: std::collections::HashSet::new(),
     }));
-
-    // Iniciar servidor WebRTC (se conecta al proxy)
-    let room = room.clone();
-    let server_host = server_host.clone();
-    let room_name = room_name.clone();
-    let max_players = max_players;
-    let map_name = map_name_for_proxy;
-    std::thread::spawn(move || {
-        start_webrtc_server(
-            network_tx,
-            network_state,
-            room,
-            outgoing_rx,
-            server_host,
-            room_name,
-            max_players,
-            map_name,
-        );
-    });
+    let thread_state = network_state.clone();
+    let migration = match link {
+        HostLink::Proxy(registration) => {
+            println!("🌐 Conectando al proxy en: {}", registration.server_host);
+            let migration = HostMigration::new(&registration);
+
+            // Iniciar servidor WebRTC (se conecta al proxy)
+            std::thread::spawn(move || {
+                start_webrtc_server(network_tx, thread_state, outgoing_rx, registration);
+            });
+            migration
+        }
+        HostLink::Lan(transport) => {
+            std::thread::spawn(move || {
+                start_direct_server(network_tx, thread_state, outgoing_rx, transport);
+            });
+            HostMigration::offline()
+        }
+        HostLink::Local(transport) => {
+            std::thread::spawn(move || {
+                start_direct_server(network_tx, thread_state, outgoing_rx, transport);
+            });
+            HostMigration::offline()
+        }
+    };
 
     // Initialize MatchSlots - first player (player_id 1) will be admin
     let mut initial_slots = MatchSlots::default();
     initial_slots.add_admin(1); // First player to join is admin
 
+    // Tras una migración, el partido sigue donde lo dejó el host anterior
+    let (tick, score, clock, phase) = match &handoff {
+        Some(h) => {
+            initial_slots = h.slots.clone();
+            (
+                h.tick,
+                MatchScore {
+                    goals: h.score,
+                    ..Default::default()
+                },
+                MatchClock {
+                    elapsed_secs: h.clock_elapsed_secs,
+                    overtime: h.overtime,
+                },
+                MatchPhase::from(h.phase),
+            )
+        }
+        None => Default::default(),
+    };
+
     App::new()
         .add_plugins(
             MinimalPlugins.set(bevy::app::ScheduleRunnerPlugin::run_loop(
@@ -131,36 +337,70 @@ pub fn host(
         .insert_resource(NetworkReceiver(Arc::new(Mutex::new(network_rx))))
         .insert_resource(NetworkSender(outgoing_tx))
         .insert_resource(LoadedMap(loaded_map.clone()))
-        .insert_resource(GameTick(0))
+        .insert_resource(GameTick(tick))
         .insert_resource(BroadcastTimer(Timer::from_seconds(
             1.0 / 60.0,
             TimerMode::Repeating,
         ))) // 60 Hz
         .insert_resource(HostMatchSlots(initial_slots))
+        .insert_resource(score)
+        .insert_resource(phase)
+        .insert_resource(clock)
+        .insert_resource(rules)
+        .insert_resource(SharedNetworkState(network_state))
+        .insert_resource(migration)
+        .insert_resource(PendingHandoff(handoff))
         .init_resource::<GameInputManager>()
-        .add_systems(Startup, (configure_rapier, setup_game, setup_map).chain())
+        .init_resource::<ChatRateLimiter>()
+        .init_resource::<BotBrains>()
+        .init_resource::<HostSnapshots>()
+        .init_resource::<PeerLatency>()
+        .add_systems(
+            Startup,
+            (
+                configure_rapier,
+                setup_game,
+                setup_map,
+                restore_handoff_bodies,
+                restore_handoff_players,
+            )
+                .chain(),
+        )
         .add_systems(
             FixedUpdate,
             (
-                update_input_manager,
-                process_network_messages,
-                look_at_ball,
-                toggle_mode,
-                detect_slide,
-                execute_slide,
-                move_players,
-                handle_collision_player,
-                charge_kick,
-                prepare_kick_ball,
-                detect_contact_and_kick,
-                apply_magnus_effect,
-                attract_ball,
-                push_ball_on_contact,
-                update_kick_memory_timer,
-                auto_touch_ball_while_running,
-                dash_first_touch_ball,
-                broadcast_game_state,
-                recover_stamin,
+                (
+                    update_input_manager,
+                    process_network_messages,
+                    drive_bots,
+                    expire_disconnected_players,
+                    update_match_phase,
+                    update_player_collision_groups,
+                    look_at_ball,
+                    toggle_mode,
+                    detect_slide,
+                    execute_slide,
+                    move_players,
+                    handle_collision_player,
+                    charge_kick,
+                    prepare_kick_ball,
+                )
+                    .chain(),
+                (
+                    detect_contact_and_kick,
+                    apply_magnus_effect,
+                    attract_ball,
+                    push_ball_on_contact,
+                    update_kick_memory_timer,
+                    auto_touch_ball_while_running,
+                    dash_first_touch_ball,
+                    detect_goals,
+                    broadcast_game_state,
+                    broadcast_latency,
+                    replicate_to_successor,
+                    recover_stamin,
+                )
+                    .chain(),
             )
                 .chain(),
         )
@@ -177,6 +417,10 @@ pub struct NetworkReceiver(pub Arc<Mutex<mpsc::Receiver<NetworkEvent>>>);
 #[derive(Resource)]
 pub struct NetworkSender(pub mpsc::Sender<OutgoingMessage>);
 
+/// Estado del hilo de red, compartido para replicarlo al sucesor del host
+#[derive(Resource)]
+pub struct SharedNetworkState(pub Arc<Mutex<NetworkState>>);
+
 #[derive(Resource)]
 pub struct GameTick(pub u32);
 
@@ -186,16 +430,69 @@ pub struct BroadcastTimer(pub Timer);
 #[derive(Resource)]
 pub struct LoadedMap(pub Option<crate::shared::map::Map>);
 
-/// GameInputManager usando NetworkInputSource
+/// Snapshots enviados (baselines para los deltas) y roster de jugadores
+#[derive(Resource, Default)]
+pub struct HostSnapshots {
+    pub encoder: SnapshotEncoder<PeerId>,
+    /// Último roster armado (nombre y equipo de los titulares)
+    pub roster: Vec<PlayerInfo>,
+    /// Peers que ya recibieron el roster actual
+    pub roster_sent: std::collections::HashSet<PeerId>,
+}
+
+impl HostSnapshots {
+    /// Olvida el estado de un peer desconectado
+    pub fn forget(&mut self, peer_id: &PeerId) {
+        self.encoder.forget(peer_id);
+        self.roster_sent.remove(peer_id);
+    }
+}
+
+/// Fase del partido en el host
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Default)]
+pub enum MatchPhase {
+    /// Esperando titulares para el primer saque
+    #[default]
+    PreKickoff,
+    /// Pelota en el centro, esperando el toque de saque del equipo `kicking_team`
+    Kickoff { kicking_team: u8 },
+    /// Juego en curso
+    Playing,
+    /// Festejo tras un gol; al terminar, saca el equipo que recibió el gol
+    GoalCelebration { timer: f32, conceding_team: u8 },
+    /// Partido terminado; tras `timer` segundos se reinicia uno nuevo
+    Ended { timer: f32, winner: u8 },
+}
+
+/// Reloj del partido: solo avanza con la pelota en juego
+#[derive(Resource, Default)]
+pub struct MatchClock {
+    pub elapsed_secs: f32,
+    /// Prórroga con gol de oro (empate al cumplirse el tiempo)
+    pub overtime: bool,
+}
+
+/// Marcador del partido (índice 0 = red, 1 = blue)
+#[derive(Resource, Default)]
+pub struct MatchScore {
+    pub goals: [u32; 2],
+    /// La pelota está dentro de un arco; evita contar el mismo gol en varios ticks
+    pub ball_in_goal: bool,
+}
+
+/// GameInputManager: NetworkInputSource para los jugadores conectados y
+/// BotInputSource para los bots
 #[derive(Resource)]
 pub struct GameInputManager {
     sources: std::collections::HashMap<u32, NetworkInputSource>,
+    bots: std::collections::HashMap<u32, BotInputSource>,
 }
 
 impl GameInputManager {
     pub fn new() -> Self {
         Self {
             sources: std::collections::HashMap::new(),
+            bots: std::collections::HashMap::new(),
         }
     }
 
@@ -203,33 +500,63 @@ impl GameInputManager {
         self.sources.insert(player_id, NetworkInputSource::new());
     }
 
+    pub fn add_bot(&mut self, player_id: u32, difficulty: BotDifficulty) {
+        self.bots.insert(player_id, BotInputSource::new(difficulty));
+    }
+
     pub fn remove_player(&mut self, player_id: u32) {
         self.sources.remove(&player_id);
+        self.bots.remove(&player_id);
     }
 
-    pub fn update_input(&mut self, player_id: u32, input: PlayerInput) {
+    /// Aplica un input recibido; descarta los que llegan desordenados (canal unreliable)
+    pub fn update_input(&mut self, player_id: u32, sequence: u32, input: PlayerInput) {
         if let Some(source) = self.sources.get_mut(&player_id) {
-            source.set_input(input);
+            source.set_input(sequence, input);
         }
     }
 
-    pub fn is_pressed(&self, player_id: u32, action: GameAction) -> bool {
+    /// Aplica el input que decidió un bot
+    pub fn set_bot_input(&mut self, player_id: u32, input: PlayerInput) {
+        if let Some(bot) = self.bots.get_mut(&player_id) {
+            bot.set_input(input);
+        }
+    }
+
+    /// Dificultad del bot (None = no es un bot)
+    pub fn bot_difficulty(&self, player_id: u32) -> Option<BotDifficulty> {
+        self.bots.get(&player_id).map(|b| b.difficulty())
+    }
+
+    /// Última secuencia de input aplicada para el jugador (0 = ninguna)
+    pub fn last_sequence(&self, player_id: u32) -> u32 {
         self.sources
             .get(&player_id)
+            .map(|s| s.last_sequence())
+            .unwrap_or(0)
+    }
+
+    fn source(&self, player_id: u32) -> Option<&dyn InputSource> {
+        match self.sources.get(&player_id) {
+            Some(source) => Some(source),
+            None => self.bots.get(&player_id).map(|b| b as &dyn InputSource),
+        }
+    }
+
+    pub fn is_pressed(&self, player_id: u32, action: GameAction) -> bool {
+        self.source(player_id)
             .map(|s| s.is_pressed(action))
             .unwrap_or(false)
     }
 
     pub fn just_pressed(&self, player_id: u32, action: GameAction) -> bool {
-        self.sources
-            .get(&player_id)
+        self.source(player_id)
             .map(|s| s.just_pressed(action))
             .unwrap_or(false)
     }
 
     pub fn just_released(&self, player_id: u32, action: GameAction) -> bool {
-        self.sources
-            .get(&player_id)
+        self.source(player_id)
             .map(|s| s.just_released(action))
             .unwrap_or(false)
     }
@@ -238,6 +565,9 @@ impl GameInputManager {
         for source in self.sources.values_mut() {
             InputSource::update(source);
         }
+        for bot in self.bots.values_mut() {
+            InputSource::update(bot);
+        }
     }
 }
 
@@ -259,6 +589,8 @@ pub struct Player {
     pub kick_memory_timer: f32, // Timer de 1 segundo para potencia memorizada
     pub peer_id: PeerId,        // Matchbox peer ID para enviar mensajes
     pub is_ready: bool,
+    /// Se cortó la conexión de su peer (segundos de juego); se guarda su lugar por un rato
+    pub disconnected_at: Option<f64>,
 
     pub not_interacting: bool,
     // Barrida/Slide
@@ -297,6 +629,14 @@ pub struct Ball {
     pub angular_velocity: f32,
 }
 
+// Disco móvil del mapa (invMass > 0), se replica a los clientes
+#[derive(Component)]
+pub struct DynamicDisc {
+    pub index: u32,
+    pub spawn_position: Vec2,
+    pub spawn_velocity: Vec2,
+}
+
 // ============================================================================
 // NETWORK STATE
 // ============================================================================
@@ -305,6 +645,51 @@ pub struct NetworkState {
     pub next_player_id: u32,
     /// Versión mínima del cliente requerida por este servidor
     pub min_client_version: protocol::ProtocolVersion,
+    /// Peer dueño de cada player_id (un peer puede tener varios jugadores locales)
+    pub player_owners: std::collections::HashMap<u32, PeerId>,
+    /// Intentos de usar un jugador ajeno ya logueados (para no llenar el log a 60 Hz)
+    pub reported_spoofs: std::collections::HashSet<(PeerId, u32)>,
+    /// Tokens de reconexión de los jugadores
+    pub sessions: ReconnectSessions,
+    /// Token de la sala en el proxy (solo lo usa el hilo de red, nunca sale del host)
+    pub proxy_token: Option<String>,
+    /// Sucesor elegido por el host (lo actualiza la replicación del partido)
+    pub successor: Option<PeerId>,
+    /// Token de toma que el proxy emitió para el sucesor (peer al que corresponde)
+    pub takeover_token: Option<(PeerId, String)>,
+    /// Peers que se ofrecieron como sucesores del host
+    pub host_candidates: std::collections::HashSet<PeerId>,
+}
+
+impl NetworkState {
+    /// Verifica que el peer sea dueño del jugador que nombra un mensaje.
+    /// Si no lo es, el mensaje se descarta y se loguea (una vez por peer y jugador)
+    pub fn check_owner(&mut self, peer_id: PeerId, player_id: u32, what: &str) -> bool {
+        if self.player_owners.get(&player_id) == Some(&peer_id) {
+            return true;
+        }
+        if self.reported_spoofs.insert((peer_id, player_id)) {
+            println!(
+                "🚨 {} rechazado: peer {:?} no es dueño del jugador {}",
+                what, peer_id, player_id
+            );
+        }
+        false
+    }
+
+    /// Olvida los jugadores de un peer que se desconectó (sus tokens de reconexión
+    /// siguen sirviendo durante el período de gracia)
+    pub fn remove_peer(&mut self, peer_id: PeerId) {
+        let now = std::time::Instant::now();
+        for (player_id, owner) in &self.player_owners {
+            if *owner == peer_id {
+                self.sessions.mark_dropped(*player_id, now);
+            }
+        }
+        self.player_owners.retain(|_, owner| *owner != peer_id);
+        self.reported_spoofs.retain(|(peer, _)| *peer != peer_id);
+        self.host_candidates.remove(&peer_id);
+    }
 }
 
 pub enum NetworkEvent {
@@ -312,6 +697,9 @@ pub enum NetworkEvent {
         id: u32,
         name: String,
         peer_id: PeerId, // Matchbox peer ID
+        reconnect_token: u64,
+        /// Reconexión con token válido: recupera el jugador guardado si sigue en el juego
+        resumed: bool,
     },
     PlayerInput {
         peer_id: PeerId, // Buscar por peer_id en lugar de por id
@@ -320,11 +708,16 @@ pub enum NetworkEvent {
     /// Input identificado por player_id directamente (para multijugador local)
     PlayerInputById {
         player_id: u32,
+        sequence: u32,
         input: PlayerInput,
     },
     PlayerDisconnected {
         peer_id: PeerId, // Buscar por peer_id en lugar de por id
     },
+    /// El cliente decodificó el snapshot de `tick`
+    SnapshotAck { peer_id: PeerId, tick: u32 },
+    /// Respuesta a un ping del host: RTT medido en el hilo de red
+    LatencySample { peer_id: PeerId, rtt_ms: f32 },
     PlayerReady {
         peer_id: PeerId, // Buscar por peer_id en lugar de por id
     },
@@ -350,6 +743,29 @@ pub enum NetworkEvent {
         player_id: u32,
         is_admin: bool,
     },
+    /// Chat message from a player (validated and relayed by the host)
+    Chat {
+        peer_id: PeerId,
+        player_id: u32,
+        kind: ChatKind,
+        text: String,
+    },
+    /// Admin asks for an instant replay on every client
+    RequestReplay { admin_peer_id: PeerId },
+    /// Admin adds a bot to a team
+    AddBot {
+        admin_peer_id: PeerId,
+        team_index: u8,
+        difficulty: BotDifficulty,
+    },
+    /// Admin removes a bot
+    RemoveBot {
+        admin_peer_id: PeerId,
+        player_id: u32,
+    },
+    /// El hilo de red terminó (ej. salió el cliente de la práctica o no se pudo
+    /// registrar la sala en el proxy): el host se cierra
+    Shutdown,
 }
 
 /// Mensajes salientes del servidor a los clientes
@@ -357,11 +773,11 @@ pub enum OutgoingMessage {
     /// Enviar a un peer específico por un canal específico
     ToOne {
         peer_id: PeerId,
-        channel: usize, // 0 = reliable, 1 = unreliable
+        channel: Channel,
         data: Vec<u8>,
     },
     /// Enviar a todos los peers conectados
-    Broadcast { channel: usize, data: Vec<u8> },
+    Broadcast { channel: Channel, data: Vec<u8> },
 }
 
 // ============================================================================
@@ -374,9 +790,16 @@ fn configure_rapier(mut rapier_config: Query<&mut RapierConfiguration>) {
     }
 }
 
-fn setup_game(mut commands: Commands, config: Res<GameConfig>) {
+fn setup_game(mut commands: Commands, config: Res<GameConfig>, loaded_map: Res<LoadedMap>) {
     println!("⚽ Configurando juego...");
 
+    // Pelota: ball/kick/score, colisiona con todo (salvo que ballPhysics diga otra cosa)
+    let ball_physics = loaded_map.0.as_ref().and_then(|m| m.ball_physics.as_ref());
+    let ball_groups = super::map::collision::ball_groups(
+        ball_physics.and_then(|b| b.c_mask.as_ref()),
+        ball_physics.and_then(|b| b.c_group.as_ref()),
+    );
+
     // Crear pelota
     commands.spawn((
         Ball {
@@ -387,9 +810,8 @@ fn setup_game(mut commands: Commands, config: Res<GameConfig>) {
         RigidBody::Dynamic,
         Collider::ball(config.ball_radius),
         Velocity::zero(),
-        // Pelota: colisiona con todo EXCEPTO líneas solo-jugadores (GROUP_6)
-        CollisionGroups::new(Group::GROUP_3, Group::ALL ^ Group::GROUP_6),
-        SolverGroups::new(Group::GROUP_3, Group::ALL ^ Group::GROUP_6),
+        ball_groups,
+        SolverGroups::new(ball_groups.memberships, ball_groups.filters),
         AdditionalMassProperties::Mass(config.ball_mass),
         Friction {
             coefficient: config.ball_friction,
@@ -0,0 +1,64 @@
+/// BotInputSource - Implementación de InputSource para los bots del host
+/// El input lo escribe cada tick el sistema `drive_bots` a partir del estado de la física
+use super::core::{GameAction, InputSource};
+use crate::shared::protocol::{BotDifficulty, PlayerInput};
+
+pub struct BotInputSource {
+    current: PlayerInput,
+    previous: PlayerInput,
+    difficulty: BotDifficulty,
+}
+
+impl BotInputSource {
+    pub fn new(difficulty: BotDifficulty) -> Self {
+        Self {
+            current: PlayerInput::default(),
+            previous: PlayerInput::default(),
+            difficulty,
+        }
+    }
+
+    pub fn difficulty(&self) -> BotDifficulty {
+        self.difficulty
+    }
+
+    /// Input decidido por el bot para este tick
+    pub fn set_input(&mut self, input: PlayerInput) {
+        self.current = input;
+    }
+}
+
+/// Estado de una acción en un input
+fn action_pressed(input: &PlayerInput, action: GameAction) -> bool {
+    match action {
+        GameAction::MoveUp => input.move_up,
+        GameAction::MoveDown => input.move_down,
+        GameAction::MoveLeft => input.move_left,
+        GameAction::MoveRight => input.move_right,
+        GameAction::Kick => input.kick,
+        GameAction::CurveLeft => input.curve_left,
+        GameAction::CurveRight => input.curve_right,
+        GameAction::StopInteract => input.stop_interact,
+        GameAction::Dash => input.dash,
+        GameAction::Sprint => input.sprint,
+        GameAction::Mode => input.mode,
+    }
+}
+
+impl InputSource for BotInputSource {
+    fn is_pressed(&self, action: GameAction) -> bool {
+        action_pressed(&self.current, action)
+    }
+
+    fn just_pressed(&self, action: GameAction) -> bool {
+        action_pressed(&self.current, action) && !action_pressed(&self.previous, action)
+    }
+
+    fn just_released(&self, action: GameAction) -> bool {
+        !action_pressed(&self.current, action) && action_pressed(&self.previous, action)
+    }
+
+    fn update(&mut self) {
+        self.previous = self.current;
+    }
+}
@@ -1,9 +1,11 @@
 // Módulo de abstracción de input para el servidor
-// con NetworkInputSource
+// con NetworkInputSource y BotInputSource
 
+pub mod bot;
 pub mod core;
 pub mod network;
 
 // Re-exportar los tipos principales
+pub use bot::BotInputSource;
 pub use core::{GameAction, InputSource};
 pub use network::NetworkInputSource;
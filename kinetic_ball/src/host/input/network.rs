@@ -6,6 +6,8 @@ use crate::shared::protocol::PlayerInput;
 pub struct NetworkInputSource {
     current: PlayerInput,
     previous: PlayerInput,
+    /// Secuencia del input actual (el cliente la usa para reconciliar su predicción)
+    sequence: u32,
 }
 
 impl NetworkInputSource {
@@ -13,13 +15,23 @@ impl NetworkInputSource {
         Self {
             current: PlayerInput::default(),
             previous: PlayerInput::default(),
+            sequence: 0,
         }
     }
 
-    /// Actualiza el input con uno nuevo recibido de la red
-    pub fn set_input(&mut self, input: PlayerInput) {
+    /// Actualiza el input con uno nuevo recibido de la red.
+    /// Los inputs más viejos que el actual se ignoran.
+    pub fn set_input(&mut self, sequence: u32, input: PlayerInput) {
+        if sequence < self.sequence {
+            return;
+        }
+        self.sequence = sequence;
         self.current = input;
     }
+
+    pub fn last_sequence(&self) -> u32 {
+        self.sequence
+    }
 }
 
 impl InputSource for NetworkInputSource {
@@ -76,6 +88,6 @@ impl InputSource for NetworkInputSource {
     }
 
     fn update(&mut self) {
-        self.previous = self.current.clone();
+        self.previous = self.current;
     }
 }
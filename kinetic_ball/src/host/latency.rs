@@ -0,0 +1,148 @@
+// ============================================================================
+// LATENCIA - RTT Y JITTER POR PEER
+// ============================================================================
+
+use crate::shared::protocol::{ControlMessage, PlayerLatency};
+use crate::transport::Channel;
+use bevy::prelude::*;
+use matchbox_socket::PeerId;
+use std::collections::HashMap;
+
+use super::host::{NetworkSender, OutgoingMessage, Player};
+
+/// Cada cuánto se comparte la latencia de los jugadores con los clientes
+const LATENCY_BROADCAST_SECS: f32 = 2.0;
+
+/// RTT suavizado y su variación (como el estimador de TCP, RFC 6298)
+#[derive(Debug, Clone, Copy)]
+pub struct LatencyStats {
+    pub rtt_ms: f32,
+    pub jitter_ms: f32,
+}
+
+impl LatencyStats {
+    fn new(sample_ms: f32) -> Self {
+        Self {
+            rtt_ms: sample_ms,
+            jitter_ms: sample_ms / 2.0,
+        }
+    }
+
+    fn add_sample(&mut self, sample_ms: f32) {
+        self.jitter_ms += ((self.rtt_ms - sample_ms).abs() - self.jitter_ms) / 4.0;
+        self.rtt_ms += (sample_ms - self.rtt_ms) / 8.0;
+    }
+}
+
+/// Latencia medida de cada peer (todos sus jugadores locales comparten la conexión)
+#[derive(Resource)]
+pub struct PeerLatency {
+    stats: HashMap<PeerId, LatencyStats>,
+    broadcast_timer: Timer,
+}
+
+impl Default for PeerLatency {
+    fn default() -> Self {
+        Self {
+            stats: HashMap::new(),
+            broadcast_timer: Timer::from_seconds(LATENCY_BROADCAST_SECS, TimerMode::Repeating),
+        }
+    }
+}
+
+impl PeerLatency {
+    /// Registra una muestra de RTT (respuesta a un ping del host)
+    pub fn record(&mut self, peer_id: PeerId, rtt_ms: f32) {
+        self.stats
+            .entry(peer_id)
+            .and_modify(|stats| stats.add_sample(rtt_ms))
+            .or_insert_with(|| LatencyStats::new(rtt_ms));
+    }
+
+    pub fn forget(&mut self, peer_id: &PeerId) {
+        self.stats.remove(peer_id);
+    }
+}
+
+/// Envía periódicamente el RTT y jitter de cada jugador a todos los clientes
+pub fn broadcast_latency(
+    time: Res<Time>,
+    mut latency: ResMut<PeerLatency>,
+    players: Query<&Player>,
+    network_tx: Res<NetworkSender>,
+) {
+    latency.broadcast_timer.tick(time.delta());
+    if !latency.broadcast_timer.just_finished() {
+        return;
+    }
+
+    let mut entries: Vec<PlayerLatency> = players
+        .iter()
+        .filter_map(|player| {
+            let stats = latency.stats.get(&player.peer_id)?;
+            Some(PlayerLatency {
+                player_id: player.id,
+                rtt_ms: stats.rtt_ms.round() as u16,
+                jitter_ms: stats.jitter_ms.round() as u16,
+            })
+        })
+        .collect();
+    if entries.is_empty() {
+        return;
+    }
+    entries.sort_by_key(|entry| entry.player_id);
+
+    if let Ok(data) = bincode::serialize(&ControlMessage::LatencyUpdated(entries)) {
+        let _ = network_tx.0.send(OutgoingMessage::Broadcast {
+            channel: Channel::Reliable,
+            data,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::next_peer_id;
+
+    #[test]
+    fn test_first_sample_seeds_the_estimate() {
+        let stats = LatencyStats::new(80.0);
+        assert_eq!(stats.rtt_ms, 80.0);
+        assert_eq!(stats.jitter_ms, 40.0);
+    }
+
+    #[test]
+    fn test_spikes_are_smoothed() {
+        let mut stats = LatencyStats::new(80.0);
+        stats.add_sample(240.0);
+        // 1/8 de la diferencia para el RTT, 1/4 para el jitter
+        assert_eq!(stats.rtt_ms, 100.0);
+        assert_eq!(stats.jitter_ms, 70.0);
+    }
+
+    #[test]
+    fn test_steady_rtt_converges_and_jitter_decays() {
+        let mut stats = LatencyStats::new(200.0);
+        for _ in 0..100 {
+            stats.add_sample(50.0);
+        }
+        assert!((stats.rtt_ms - 50.0).abs() < 0.01);
+        assert!(stats.jitter_ms < 0.01);
+    }
+
+    #[test]
+    fn test_peers_are_tracked_separately() {
+        let (a, b) = (next_peer_id(), next_peer_id());
+        let mut latency = PeerLatency::default();
+        latency.record(a, 40.0);
+        latency.record(b, 120.0);
+        latency.record(a, 120.0);
+        assert_eq!(latency.stats[&a].rtt_ms, 50.0);
+        assert_eq!(latency.stats[&b].rtt_ms, 120.0);
+
+        latency.forget(&a);
+        latency.record(a, 90.0);
+        assert_eq!(latency.stats[&a].rtt_ms, 90.0);
+    }
+}
@@ -0,0 +1,149 @@
+// ============================================================================
+// MODELO DE COLISIONES DE HAXBALL SOBRE GRUPOS DE RAPIER
+// ============================================================================
+//
+// HaxBall hace colisionar dos objetos A y B si (A.cMask & B.cGroup) y
+// (B.cMask & A.cGroup). Rapier usa la misma regla con memberships/filters,
+// así que cada flag de HaxBall se mapea a un grupo propio.
+
+use bevy_rapier2d::prelude::*;
+
+pub const BALL: Group = Group::GROUP_1;
+pub const RED: Group = Group::GROUP_2;
+pub const BLUE: Group = Group::GROUP_3;
+pub const RED_KO: Group = Group::GROUP_4;
+pub const BLUE_KO: Group = Group::GROUP_5;
+pub const WALL: Group = Group::GROUP_6;
+pub const KICK: Group = Group::GROUP_7;
+pub const SCORE: Group = Group::GROUP_8;
+pub const C0: Group = Group::GROUP_9;
+pub const C1: Group = Group::GROUP_10;
+pub const C2: Group = Group::GROUP_11;
+pub const C3: Group = Group::GROUP_12;
+
+/// Todos los flags de HaxBall ("all")
+pub const ALL: Group = BALL
+    .union(RED)
+    .union(BLUE)
+    .union(RED_KO)
+    .union(BLUE_KO)
+    .union(WALL)
+    .union(KICK)
+    .union(SCORE)
+    .union(C0)
+    .union(C1)
+    .union(C2)
+    .union(C3);
+
+/// cMask por defecto de los jugadores en HaxBall: ball, red, blue y wall
+pub const PLAYER_MASK: Group = BALL.union(RED).union(BLUE).union(WALL);
+
+/// cGroup por defecto de la pelota en HaxBall
+pub const BALL_GROUP: Group = BALL.union(KICK).union(SCORE);
+
+/// Convertir una lista de flags de HaxBall ("ball", "red", "c0", "all", ...) a grupos
+pub fn parse_flags(flags: &[String]) -> Group {
+    let mut result = Group::NONE;
+
+    for flag in flags {
+        result |= match flag.as_str() {
+            "ball" => BALL,
+            "red" => RED,
+            "blue" => BLUE,
+            "redKO" => RED_KO,
+            "blueKO" => BLUE_KO,
+            "wall" => WALL,
+            "kick" => KICK,
+            "score" => SCORE,
+            "c0" => C0,
+            "c1" => C1,
+            "c2" => C2,
+            "c3" => C3,
+            "all" => ALL,
+            _ => Group::NONE, // Flag desconocido o vacío (""), ignorar
+        };
+    }
+
+    result
+}
+
+/// Grupos de un objeto del mapa: si no define cMask/cGroup se usan los defaults de HaxBall
+pub fn map_object_groups(
+    c_mask: Option<&Vec<String>>,
+    c_group: Option<&Vec<String>>,
+    default_group: Group,
+) -> CollisionGroups {
+    let memberships = c_group.map(|g| parse_flags(g)).unwrap_or(default_group);
+    let filters = c_mask.map(|m| parse_flags(m)).unwrap_or(ALL);
+    CollisionGroups::new(memberships, filters)
+}
+
+/// Grupo del equipo (0 = red, 1 = blue)
+pub fn team_group(team_index: u8) -> Group {
+    if team_index == 0 {
+        RED
+    } else {
+        BLUE
+    }
+}
+
+/// Flag de saque del equipo (0 = redKO, 1 = blueKO)
+pub fn team_kickoff_group(team_index: u8) -> Group {
+    if team_index == 0 {
+        RED_KO
+    } else {
+        BLUE_KO
+    }
+}
+
+/// Grupos de un jugador en cancha. Durante el saque todos los jugadores suman a su
+/// cMask el flag KO del equipo que saca, así las barreras del mapa (kickOffBarrier)
+/// bloquean la mitad de cancha y el semicírculo del rival.
+pub fn player_groups(team_index: u8, kickoff_team: Option<u8>) -> CollisionGroups {
+    let mut filters = PLAYER_MASK;
+    if let Some(kicking_team) = kickoff_team {
+        filters |= team_kickoff_group(kicking_team);
+    }
+    CollisionGroups::new(team_group(team_index), filters)
+}
+
+/// Grupos de la pelota (ballPhysics puede redefinir cMask/cGroup)
+pub fn ball_groups(c_mask: Option<&Vec<String>>, c_group: Option<&Vec<String>>) -> CollisionGroups {
+    map_object_groups(c_mask, c_group, BALL_GROUP)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_flags_combines_known_flags() {
+        assert_eq!(parse_flags(&flags(&["ball", "red"])), BALL | RED);
+        assert_eq!(parse_flags(&flags(&["c0", "c3", "kick"])), C0 | C3 | KICK);
+        assert_eq!(parse_flags(&flags(&["all"])), ALL);
+        assert_eq!(parse_flags(&[]), Group::NONE);
+    }
+
+    #[test]
+    fn test_parse_flags_ignores_unknown_flags() {
+        assert_eq!(parse_flags(&flags(&["", "Red", "goal", "c4"])), Group::NONE);
+        assert_eq!(parse_flags(&flags(&["blueKO", "spectators"])), BLUE_KO);
+    }
+
+    #[test]
+    fn test_map_objects_use_haxball_defaults() {
+        let groups = map_object_groups(None, None, WALL);
+        assert_eq!(groups.memberships, WALL);
+        assert_eq!(groups.filters, ALL);
+
+        let mask = flags(&["ball"]);
+        let group = flags(&["wall", "unknown"]);
+        let groups = map_object_groups(Some(&mask), Some(&group), WALL);
+        assert_eq!(groups.memberships, WALL);
+        assert_eq!(groups.filters, BALL);
+    }
+}
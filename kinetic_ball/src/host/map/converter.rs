@@ -1,6 +1,19 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use crate::shared::map::{CurveConfig, Map, Segment, Vertex};
+use super::collision;
+use crate::host::host::DynamicDisc;
+use crate::shared::map::{CurveConfig, Joint, Map, Segment, Vertex};
+
+/// Ticks por segundo de HaxBall, para convertir la rigidez de los joints
+const HAXBALL_TICK_RATE: f32 = 60.0;
+
+/// Motor que sostiene el largo de un joint rígido: Rapier no tiene largo mínimo
+/// en los límites de distancia, así que se empuja hacia el largo fijo
+const RIGID_JOINT_STIFFNESS: f32 = 1.0e6;
+const RIGID_JOINT_DAMPING: f32 = 2.0e3;
+
+/// Masa de un disco con invMass = 1 (la misma que la pelota por defecto)
+const HAXBALL_UNIT_MASS: f32 = 0.1;
 
 pub struct MapConverter {
     curve_config: CurveConfig,
@@ -14,20 +27,20 @@ impl MapConverter {
     }
 
     /// Spawnear toda la geometría del mapa en el mundo ECS
-    pub fn spawn_map_geometry(&self, commands: &mut Commands, map: &Map) {
+    /// `ball` es la entidad de la pelota (disco 0 para los joints)
+    pub fn spawn_map_geometry(&self, commands: &mut Commands, map: &Map, ball: Option<Entity>) {
         println!("🗺️  Spawning map geometry: {}", map.name);
 
         // Spawnear segmentos (paredes)
         for (i, segment) in map.segments.iter().enumerate() {
-            // Skip segmentos invisibles - no tienen física, solo son decorativos
-            if !segment.is_visible() {
-                println!("  ⊘ Segment {} (invisible): skipping physics", i);
-                continue;
-            }
-
+            // Los segmentos invisibles también tienen física (ballArea, kickOffBarrier);
+            // los decorativos se declaran con cMask vacío
             if let Some(collider) = self.segment_to_collider(segment, &map.vertexes) {
-                let collision_groups = self
-                    .compute_collision_groups(segment.c_mask.as_ref(), segment.c_group.as_ref());
+                let collision_groups = self.compute_collision_groups(
+                    segment.c_mask.as_ref(),
+                    segment.c_group.as_ref(),
+                    collision::WALL,
+                );
 
                 let restitution = segment.b_coef;
 
@@ -49,33 +62,153 @@ impl MapConverter {
             }
         }
 
-        // Spawnear discos
+        // Spawnear discos (el índice 0 de los joints es la pelota)
+        let mut disc_entities: Vec<Option<(Entity, Vec2)>> = vec![ball.map(|e| (e, Vec2::ZERO))];
         for (i, disc) in map.discs.iter().enumerate() {
-            let collision_groups =
-                self.compute_collision_groups(disc.c_mask.as_ref(), disc.c_group.as_ref());
+            let collision_groups = self.compute_collision_groups(
+                disc.c_mask.as_ref(),
+                disc.c_group.as_ref(),
+                collision::ALL,
+            );
+
+            let position = Vec2::new(disc.pos[0], disc.pos[1]);
+            let entity = commands
+                .spawn((
+                    RigidBody::Fixed,
+                    Collider::ball(disc.radius),
+                    collision_groups,
+                    Restitution::coefficient(disc.b_coef),
+                    Transform::from_xyz(position.x, position.y, 0.0),
+                    GlobalTransform::default(),
+                ))
+                .id();
+            disc_entities.push(Some((entity, position)));
+
+            // Discos con invMass > 0: obstáculos móviles simulados en el host
+            if disc.is_dynamic() {
+                let velocity = Vec2::from(disc.initial_velocity());
+                commands.entity(entity).insert((
+                    RigidBody::Dynamic,
+                    Velocity::linear(velocity),
+                    AdditionalMassProperties::Mass(HAXBALL_UNIT_MASS / disc.inv_mass),
+                    Damping {
+                        linear_damping: disc.linear_damping(),
+                        angular_damping: 0.0,
+                    },
+                    Ccd::enabled(),
+                    DynamicDisc {
+                        index: i as u32,
+                        spawn_position: position,
+                        spawn_velocity: velocity,
+                    },
+                ));
+            }
+
+            println!(
+                "  ✓ Disc {}: pos=({:.0}, {:.0}), r={:.0}{}",
+                i,
+                disc.pos[0],
+                disc.pos[1],
+                disc.radius,
+                if disc.is_dynamic() { " (móvil)" } else { "" }
+            );
+        }
+
+        // Spawnear planos (paredes infinitas)
+        for (i, plane) in map.planes.iter().enumerate() {
+            let normal = Vec2::new(plane.normal[0], plane.normal[1]).normalize_or_zero();
+            let Some(collider) = Collider::halfspace(normal) else {
+                println!("  ⊘ Plane {}: normal inválida, skipping", i);
+                continue;
+            };
+
+            let collision_groups = self.compute_collision_groups(
+                plane.c_mask.as_ref(),
+                plane.c_group.as_ref(),
+                collision::WALL,
+            );
 
             commands.spawn((
                 RigidBody::Fixed,
-                Collider::ball(disc.radius),
+                collider,
                 collision_groups,
-                Restitution::coefficient(disc.b_coef),
-                Transform::from_xyz(disc.pos[0], disc.pos[1], 0.0),
+                Restitution::coefficient(plane.b_coef),
+                Transform::from_translation((normal * plane.dist).extend(0.0)),
                 GlobalTransform::default(),
             ));
 
             println!(
-                "  ✓ Disc {}: pos=({:.0}, {:.0}), r={:.0}",
-                i, disc.pos[0], disc.pos[1], disc.radius
+                "  ✓ Plane {}: normal=({:.2}, {:.2}), dist={:.0}",
+                i, normal.x, normal.y, plane.dist
             );
         }
 
+        // Spawnear joints entre discos
+        for (i, joint) in map.joints.iter().enumerate() {
+            let (Some(Some((e0, p0))), Some(Some((e1, p1)))) =
+                (disc_entities.get(joint.d0), disc_entities.get(joint.d1))
+            else {
+                println!("  ⊘ Joint {}: disco inexistente, skipping", i);
+                continue;
+            };
+
+            let joint_component = self.joint_to_impulse_joint(joint, *e0, p0.distance(*p1));
+
+            // Un hijo por joint: un cuerpo puede tener varios joints
+            commands.entity(*e1).with_children(|parent| {
+                parent.spawn(joint_component);
+            });
+
+            println!("  ✓ Joint {}: d{} ↔ d{}", i, joint.d0, joint.d1);
+        }
+
         println!(
-            "✅ Spawned {} segments, {} discs",
+            "✅ Spawned {} segments, {} discs, {} planes, {} joints",
             map.segments.len(),
-            map.discs.len()
+            map.discs.len(),
+            map.planes.len(),
+            map.joints.len()
         );
     }
 
+    /// Convertir joint de HaxBall a joint de Rapier (rígido = distancia límite,
+    /// numérico = resorte)
+    fn joint_to_impulse_joint(
+        &self,
+        joint: &Joint,
+        parent: Entity,
+        initial_distance: f32,
+    ) -> ImpulseJoint {
+        let [min_length, max_length] = joint.length.unwrap_or([initial_distance, initial_distance]);
+
+        match joint.strength {
+            None => {
+                let mut rigid = GenericJointBuilder::new(JointAxesMask::empty())
+                    .coupled_axes(JointAxesMask::LIN_AXES)
+                    .limits(JointAxis::LinX, [min_length, max_length]);
+                // Largo fijo: el límite sólo frena al estirarse, el motor evita que se acorte.
+                // Con un rango [min, max] se respeta sólo el máximo
+                if min_length >= max_length {
+                    rigid = rigid
+                        .motor_position(
+                            JointAxis::LinX,
+                            max_length,
+                            RIGID_JOINT_STIFFNESS,
+                            RIGID_JOINT_DAMPING,
+                        )
+                        .motor_model(JointAxis::LinX, MotorModel::AccelerationBased);
+                }
+                ImpulseJoint::new(parent, TypedJoint::GenericJoint(rigid.build()))
+            }
+            Some(strength) => {
+                let rest_length = (min_length + max_length) * 0.5;
+                let stiffness = strength * HAXBALL_TICK_RATE * HAXBALL_TICK_RATE;
+                let spring = SpringJointBuilder::new(rest_length, stiffness, 0.0);
+                ImpulseJoint::new(parent, spring)
+            }
+        }
+    }
+
     /// Convertir segmento a collider (maneja rectos y curvos)
     fn segment_to_collider(&self, segment: &Segment, vertices: &[Vertex]) -> Option<Collider> {
         let v0 = &vertices[segment.v0];
@@ -142,12 +275,11 @@ impl MapConverter {
         // Ángulo inicial (de center a p0)
         let start_angle = (p0.y - center.y).atan2(p0.x - center.x);
 
-        // Determinar dirección de barrido
-        let angle_step = if curve > 0.0 {
-            -total_angle / num_segments as f32
-        } else {
-            total_angle / num_segments as f32
-        };
+        // Barrer de p0 a p1 por el lado opuesto al centro (el arco "panza" hacia -perp
+        // con curva positiva), así el último punto cae en p1
+        let bulge = if curve > 0.0 { -perp } else { perp };
+        let direction = (p0 - center).perp_dot(bulge).signum();
+        let angle_step = direction * total_angle / num_segments as f32;
 
         // Generar puntos
         for i in 0..=num_segments {
@@ -167,81 +299,119 @@ impl MapConverter {
         &self,
         cmask: Option<&Vec<String>>,
         cgroup: Option<&Vec<String>>,
+        default_group: Group,
     ) -> CollisionGroups {
-        // Por defecto: sin colisión (líneas decorativas)
-        let mut memberships = Group::GROUP_1;
-        let mut filters = Group::NONE;
-
-        // Parsear cMask primero para determinar memberships especiales
-        if let Some(masks) = cmask {
-            filters = self.parse_group_filters(masks);
-
-            // Usar memberships especiales según cMask
-            if masks.iter().any(|m| m == "ball") && !masks.iter().any(|m| m == "red" || m == "blue")
-            {
-                // Solo pelota: usar GROUP_5
-                memberships = Group::GROUP_5;
-            } else if masks.iter().any(|m| m == "red" || m == "blue")
-                && !masks.iter().any(|m| m == "ball")
-            {
-                // Solo jugadores: usar GROUP_6
-                memberships = Group::GROUP_6;
-            }
-        }
-
-        // Si tiene cGroup explícito, usarlo (override)
-        if let Some(groups) = cgroup {
-            let parsed = self.parse_group_membership(groups);
-            if parsed != Group::NONE {
-                memberships = parsed;
-            }
-        }
-
-        CollisionGroups::new(memberships, filters)
+        collision::map_object_groups(cmask, cgroup, default_group)
     }
+}
 
-    fn parse_group_membership(&self, groups: &[String]) -> Group {
-        let mut result = Group::NONE;
+impl Default for MapConverter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        for group in groups {
-            match group.as_str() {
-                "wall" => result |= Group::GROUP_1,
-                "kick" => result |= Group::GROUP_2,
-                "ball" => result |= Group::GROUP_3,
-                "red" | "blue" => result |= Group::GROUP_4,
-                _ => {} // Grupo desconocido, ignorar
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn curved_segments_join_both_vertices() {
+        let converter = MapConverter::new();
+        let (p0, p1) = (Vec2::new(0.0, 120.0), Vec2::new(0.0, -120.0));
+
+        // Curva positiva: panza hacia -perp (x < 0 en este sentido); negativa, al revés
+        for (curve, side) in [(180.0, -1.0), (-180.0, 1.0), (120.0, -1.0)] {
+            let points = converter.approximate_curve(p0, p1, curve);
+            assert!(points.first().unwrap().distance(p0) < 1e-3);
+            assert!(points.last().unwrap().distance(p1) < 1e-3);
+            let middle = points[points.len() / 2];
+            assert!(middle.x * side > 0.0, "curva {}: {:?}", curve, middle);
         }
+    }
 
-        if result == Group::NONE {
-            result = Group::GROUP_1; // Por defecto: pared
+    /// Simula dos discos unidos por el joint, el segundo a `distance` del primero y
+    /// lanzado con `velocity`; devuelve la distancia mínima y máxima entre ellos
+    fn simulate_joint(joint: &Joint, distance: f32, velocity: f32) -> (f32, f32) {
+        use bevy_rapier2d::rapier::prelude::*;
+
+        let converter = MapConverter::new();
+        let impulse_joint =
+            converter.joint_to_impulse_joint(joint, Entity::PLACEHOLDER, distance);
+        let raw = impulse_joint.data.as_ref().raw;
+
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+        let mut impulse_joints = ImpulseJointSet::new();
+        let a = bodies.insert(RigidBodyBuilder::dynamic());
+        let b = bodies.insert(
+            RigidBodyBuilder::dynamic()
+                .translation(vector![distance, 0.0])
+                .linvel(vector![velocity, 0.0]),
+        );
+        for body in [a, b] {
+            colliders.insert_with_parent(ColliderBuilder::ball(10.0), body, &mut bodies);
         }
-
-        result
+        impulse_joints.insert(a, b, raw, true);
+
+        let mut pipeline = PhysicsPipeline::new();
+        let mut islands = IslandManager::new();
+        let mut broad_phase = DefaultBroadPhase::new();
+        let mut narrow_phase = NarrowPhase::new();
+        let mut multibody_joints = MultibodyJointSet::new();
+        let mut ccd_solver = CCDSolver::new();
+        let params = IntegrationParameters::default();
+
+        let (mut min, mut max) = (distance, distance);
+        for _ in 0..120 {
+            pipeline.step(
+                &vector![0.0, 0.0],
+                &params,
+                &mut islands,
+                &mut broad_phase,
+                &mut narrow_phase,
+                &mut bodies,
+                &mut colliders,
+                &mut impulse_joints,
+                &mut multibody_joints,
+                &mut ccd_solver,
+                &(),
+                &(),
+            );
+            let current = (bodies[b].translation() - bodies[a].translation()).norm();
+            min = min.min(current);
+            max = max.max(current);
+        }
+        (min, max)
     }
 
-    fn parse_group_filters(&self, masks: &[String]) -> Group {
-        let mut result = Group::NONE;
-
-        for mask in masks {
-            match mask.as_str() {
-                "wall" => result |= Group::GROUP_1,
-                "kick" => result |= Group::GROUP_2,
-                "ball" => result |= Group::GROUP_3,
-                "red" | "blue" | "c0" | "c1" => result |= Group::GROUP_4,
-                "all" => result = Group::ALL,
-                _ => {}
-            }
+    #[test]
+    fn rigid_joints_keep_a_fixed_length() {
+        let rigid = Joint {
+            d0: 1,
+            d1: 2,
+            length: None,
+            strength: None,
+            color: None,
+        };
+        for velocity in [-600.0, 600.0] {
+            let (min, max) = simulate_joint(&rigid, 100.0, velocity);
+            assert!(min > 97.0 && max < 103.0, "{}: [{}, {}]", velocity, min, max);
         }
-
-        // Si cMask está vacío o no definido, NO colisionar con nada
-        // Solo las líneas con cMask explícito deberían tener física
-        result
     }
-}
 
-impl Default for MapConverter {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn rigid_joints_with_a_range_only_limit_the_maximum() {
+        let rope = Joint {
+            d0: 1,
+            d1: 2,
+            length: Some([50.0, 100.0]),
+            strength: None,
+            color: None,
+        };
+        let (min, max) = simulate_joint(&rope, 100.0, -600.0);
+        assert!(min < 60.0, "{}", min);
+        let (_, max_out) = simulate_joint(&rope, 100.0, 600.0);
+        assert!(max < 103.0 && max_out < 103.0, "{} {}", max, max_out);
     }
 }
@@ -1,4 +1,5 @@
 use crate::shared::map::Map;
+use serde_json::Value;
 use std::path::Path;
 
 #[derive(Debug)]
@@ -33,24 +34,101 @@ pub fn load_map<P: AsRef<Path>>(path: P) -> Result<Map, MapLoadError> {
 
 /// Cargar un mapa desde un string (para mapas embebidos)
 pub fn load_map_from_str(content: &str, name: &str) -> Result<Map, MapLoadError> {
-    // Intentar JSON5 primero (estándar de HaxBall)
-    if let Ok(map) = json5::from_str::<Map>(content) {
-        validate_map(&map)?;
-        println!("✅ Loaded map from JSON5: {}", name);
-        return Ok(map);
+    // Intentar JSON5 primero (estándar de HaxBall), fallback a JSON regular
+    let (mut raw, format) = if let Ok(value) = json5::from_str::<Value>(content) {
+        (value, "JSON5")
+    } else if let Ok(value) = serde_json::from_str::<Value>(content) {
+        (value, "JSON")
+    } else {
+        return Err(MapLoadError::ParseError(format!(
+            "Failed to parse {} as JSON5 or JSON",
+            name
+        )));
+    };
+
+    resolve_haxball_extensions(&mut raw);
+
+    let map = serde_json::from_value::<Map>(raw)
+        .map_err(|e| MapLoadError::ParseError(format!("{}: {}", name, e)))?;
+    validate_map(&map)?;
+    println!("✅ Loaded map from {}: {}", format, name);
+    Ok(map)
+}
+
+/// Resolver las extensiones de HaxBall que no mapean directo a nuestra estructura:
+/// traits, `ballPhysics: "disc0"` y las formas alternativas de `length`/`strength` en joints
+fn resolve_haxball_extensions(raw: &mut Value) {
+    let Some(root) = raw.as_object_mut() else {
+        return;
+    };
+
+    // Aplicar traits: las propiedades del trait se usan sólo si el objeto no las define
+    let traits = root
+        .remove("traits")
+        .and_then(|t| t.as_object().cloned())
+        .unwrap_or_default();
+    for key in ["vertexes", "segments", "discs", "planes", "joints"] {
+        if let Some(Value::Array(items)) = root.get_mut(key) {
+            for item in items.iter_mut() {
+                apply_trait(item, &traits);
+            }
+        }
     }
 
-    // Fallback a JSON regular
-    if let Ok(map) = serde_json::from_str::<Map>(content) {
-        validate_map(&map)?;
-        println!("✅ Loaded map from JSON: {}", name);
-        return Ok(map);
+    // "ballPhysics": "disc0" -> el primer disco del mapa es la pelota
+    if root.get("ballPhysics").and_then(Value::as_str) == Some("disc0") {
+        let disc0 = match root.get_mut("discs") {
+            Some(Value::Array(discs)) if !discs.is_empty() => Some(discs.remove(0)),
+            _ => None,
+        };
+        match disc0 {
+            Some(disc) => {
+                root.insert("ballPhysics".to_string(), disc);
+            }
+            None => {
+                root.remove("ballPhysics");
+            }
+        }
+    }
+
+    // Joints: length puede ser null, número o [min, max]; strength puede ser "rigid" o número
+    if let Some(Value::Array(joints)) = root.get_mut("joints") {
+        for joint in joints.iter_mut().filter_map(Value::as_object_mut) {
+            let length = match joint.get("length") {
+                Some(Value::Number(n)) => {
+                    let l = n.as_f64().unwrap_or(0.0);
+                    serde_json::json!([l, l])
+                }
+                Some(Value::Array(range)) if range.len() == 2 => Value::Array(range.clone()),
+                _ => Value::Null,
+            };
+            joint.insert("length".to_string(), length);
+
+            let strength = match joint.get("strength") {
+                Some(Value::Number(n)) => Value::Number(n.clone()),
+                _ => Value::Null,
+            };
+            joint.insert("strength".to_string(), strength);
+        }
     }
+}
 
-    Err(MapLoadError::ParseError(format!(
-        "Failed to parse {} as JSON5 or JSON",
-        name
-    )))
+/// Completar un objeto con las propiedades de su trait (si tiene uno)
+fn apply_trait(item: &mut Value, traits: &serde_json::Map<String, Value>) {
+    let Some(obj) = item.as_object_mut() else {
+        return;
+    };
+    let Some(trait_props) = obj
+        .get("trait")
+        .and_then(Value::as_str)
+        .and_then(|name| traits.get(name))
+        .and_then(Value::as_object)
+    else {
+        return;
+    };
+    for (key, value) in trait_props {
+        obj.entry(key.clone()).or_insert_with(|| value.clone());
+    }
 }
 
 /// Validar la geometría del mapa
@@ -68,6 +146,19 @@ fn validate_map(map: &Map) -> Result<(), MapLoadError> {
         }
     }
 
+    // Verificar que los joints referencien discos existentes (0 = pelota)
+    for (i, joint) in map.joints.iter().enumerate() {
+        if joint.d0 > map.discs.len() || joint.d1 > map.discs.len() {
+            return Err(MapLoadError::InvalidGeometry(format!(
+                "Joint {} references invalid disc (d0={}, d1={}, total discs={})",
+                i,
+                joint.d0,
+                joint.d1,
+                map.discs.len() + 1
+            )));
+        }
+    }
+
     // Verificar que los discos tengan radios positivos
     for (i, disc) in map.discs.iter().enumerate() {
         if disc.radius <= 0.0 {
@@ -80,3 +171,103 @@ fn validate_map(map: &Map) -> Result<(), MapLoadError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mapa mínimo con traits, pelota `disc0` y las tres formas de joint
+    const EXTENDED_MAP: &str = r#"{
+        name: "Extensiones",
+        traits: {
+            post: { radius: 8, invMass: 0, color: "ffcc00" },
+            wall: { bCoef: 0.1, cMask: ["ball"] },
+        },
+        vertexes: [ { x: -100, y: 0 }, { x: 100, y: 0 } ],
+        segments: [
+            { v0: 0, v1: 1, trait: "wall" },
+            { v0: 0, v1: 1, trait: "wall", bCoef: 2 },
+        ],
+        ballPhysics: "disc0",
+        discs: [
+            { pos: [0, 0], radius: 6.5, invMass: 1.5, bCoef: 0.4 },
+            { pos: [-50, 0], trait: "post" },
+            { pos: [50, 0], trait: "post", radius: 4 },
+        ],
+        joints: [
+            { d0: 1, d1: 2, strength: "rigid" },
+            { d0: 0, d1: 1, length: 30, strength: 0.2 },
+            { d0: 0, d1: 2, length: [10, 40] },
+        ],
+    }"#;
+
+    #[test]
+    fn bundled_maps_load() {
+        for (name, content) in [
+            ("futsal_fah", include_str!("../../../../maps/futsal_fah.hbs")),
+            ("futsal_bazinga", include_str!("../../../../maps/futsal_bazinga.hbs")),
+            ("cancha_grande", include_str!("../../../assets/cancha_grande.hbs")),
+        ] {
+            let map = load_map_from_str(content, name).unwrap();
+            assert!(!map.segments.is_empty(), "{}", name);
+        }
+
+        let fah = load_map_from_str(include_str!("../../../../maps/futsal_fah.hbs"), "fah").unwrap();
+        assert_eq!(fah.ball_physics.unwrap().radius, Some(6.25));
+    }
+
+    #[test]
+    fn traits_fill_only_missing_properties() {
+        let map = load_map_from_str(EXTENDED_MAP, "extensiones").unwrap();
+        assert_eq!(map.segments[0].b_coef, 0.1);
+        assert_eq!(map.segments[0].c_mask, Some(vec!["ball".to_string()]));
+        assert_eq!(map.segments[1].b_coef, 2.0);
+
+        let post = &map.discs[0];
+        assert_eq!(post.radius, 8.0);
+        assert_eq!(post.inv_mass, 0.0);
+        assert_eq!(post.color.as_deref(), Some("ffcc00"));
+        assert_eq!(map.discs[1].radius, 4.0);
+    }
+
+    #[test]
+    fn disc0_becomes_the_ball() {
+        let map = load_map_from_str(EXTENDED_MAP, "extensiones").unwrap();
+        assert_eq!(map.discs.len(), 2);
+        let ball = map.ball_physics.unwrap();
+        assert_eq!(ball.radius, Some(6.5));
+        assert_eq!(ball.inv_mass, Some(1.5));
+        assert_eq!(ball.b_coef, Some(0.4));
+
+        // Sin discos, "disc0" queda como la pelota por defecto
+        let empty = r#"{ name: "Vacío", ballPhysics: "disc0" }"#;
+        assert!(load_map_from_str(empty, "vacío").unwrap().ball_physics.is_none());
+    }
+
+    #[test]
+    fn joint_lengths_and_strengths_are_normalized() {
+        let map = load_map_from_str(EXTENDED_MAP, "extensiones").unwrap();
+        let [rigid, spring, range] = &map.joints[..] else {
+            panic!("se esperaban 3 joints");
+        };
+
+        // "rigid" sin largo: distancia inicial fija
+        assert_eq!(rigid.length, None);
+        assert_eq!(rigid.strength, None);
+
+        assert_eq!(spring.length, Some([30.0, 30.0]));
+        assert_eq!(spring.strength, Some(0.2));
+
+        assert_eq!(range.length, Some([10.0, 40.0]));
+        assert_eq!(range.strength, None);
+    }
+
+    #[test]
+    fn joints_to_missing_discs_are_rejected() {
+        let map = EXTENDED_MAP.replace("{ d0: 1, d1: 2, strength", "{ d0: 1, d1: 3, strength");
+        assert!(matches!(
+            load_map_from_str(&map, "extensiones"),
+            Err(MapLoadError::InvalidGeometry(_))
+        ));
+    }
+}
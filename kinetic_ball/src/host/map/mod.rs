@@ -1,3 +1,4 @@
+pub mod collision;
 pub mod converter;
 pub mod loader;
 
@@ -0,0 +1,669 @@
+// ============================================================================
+// ESTADO DEL PARTIDO - GOLES, MARCADOR, SAQUES Y RELOJ
+// ============================================================================
+
+use crate::shared::map::Map;
+use crate::shared::protocol::{ControlMessage, GameConfig, MatchRules};
+use crate::shared::replay::GOAL_REPLAY_SECS;
+use crate::shared::MatchSlots;
+use crate::transport::Channel;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use super::host::{
+    Ball, DynamicDisc, HostMatchSlots, LoadedMap, MatchClock, MatchPhase, MatchScore,
+    NetworkSender, OutgoingMessage, Player, Sphere,
+};
+use super::map::collision;
+
+/// Segundos de festejo tras un gol antes de reiniciar con el saque: los clientes
+/// ven la repetición del gol entera (más un margen por la latencia)
+const GOAL_CELEBRATION_SECS: f32 = GOAL_REPLAY_SECS + 1.0;
+
+/// Segundos que se muestra el resultado antes de empezar un partido nuevo
+const MATCH_END_SECS: f32 = 10.0;
+
+/// Posición de saque de un jugador según su equipo y su orden dentro del equipo.
+/// Usa los `redSpawnPoints`/`blueSpawnPoints` del mapa si alcanzan; si no, genera
+/// una formación a `spawnDistance` del centro (o a un 40% del medio ancho).
+pub fn team_spawn_position(
+    team_index: u8,
+    slot: usize,
+    config: &GameConfig,
+    map: Option<&Map>,
+) -> Vec2 {
+    if let Some(point) = map.and_then(|m| m.spawn_points(team_index).get(slot)) {
+        return Vec2::new(point[0], point[1]);
+    }
+    fallback_formation_position(team_index, slot, config, map)
+}
+
+/// Formación por defecto: el primero va al frente; los siguientes se alternan arriba y abajo
+fn fallback_formation_position(
+    team_index: u8,
+    slot: usize,
+    config: &GameConfig,
+    map: Option<&Map>,
+) -> Vec2 {
+    let half_width = map
+        .and_then(|m| m.width)
+        .map(|w| w / 2.0)
+        .unwrap_or(config.arena_width / 2.0);
+    let distance = map
+        .and_then(|m| m.spawn_distance)
+        .unwrap_or(half_width * 0.4);
+    let side = if team_index == 0 { -1.0 } else { 1.0 };
+
+    // 0, +1, -1, +2, -2, ...
+    let step = slot.div_ceil(2) as f32;
+    let sign = if slot % 2 == 1 { 1.0 } else { -1.0 };
+    let y = step * sign * config.sphere_radius * 2.5;
+
+    Vec2::new(side * distance, y)
+}
+
+/// Discos móviles del mapa (disjunta de jugadores y pelota)
+type DiscQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Transform, &'static mut Velocity, &'static DynamicDisc),
+    (Without<Sphere>, Without<Ball>),
+>;
+
+/// Esferas de los jugadores (disjunta de la pelota)
+type SphereQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Transform, &'static mut Velocity),
+    (With<Sphere>, Without<Ball>),
+>;
+
+/// Pelota (disjunta de los jugadores)
+type BallQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        &'static mut Velocity,
+        &'static mut ExternalImpulse,
+        &'static mut Ball,
+    ),
+    Without<Sphere>,
+>;
+
+/// Fase, marcador y reloj del partido, con las reglas que lo terminan
+#[derive(SystemParam)]
+pub struct MatchProgress<'w> {
+    pub rules: Res<'w, MatchRules>,
+    pub phase: ResMut<'w, MatchPhase>,
+    pub score: ResMut<'w, MatchScore>,
+    pub clock: ResMut<'w, MatchClock>,
+}
+
+/// Cuerpos que se acomodan en cada saque: jugadores, pelota y discos móviles
+#[derive(SystemParam)]
+pub struct KickoffBodies<'w, 's> {
+    pub players: Query<'w, 's, &'static mut Player>,
+    pub sphere_query: SphereQuery<'w, 's>,
+    pub ball_query: BallQuery<'w, 's>,
+    pub disc_query: DiscQuery<'w, 's>,
+}
+
+impl KickoffBodies<'_, '_> {
+    /// Algún titular del equipo que saca está tocando la pelota (misma distancia de
+    /// contacto que usan la patada y el empuje)
+    fn kicking_team_touches_ball(&self, config: &GameConfig, slots: &MatchSlots, team: u8) -> bool {
+        let Ok((ball_transform, ..)) = self.ball_query.single() else {
+            return false;
+        };
+        let ball = ball_transform.translation.truncate();
+        let contact_radius = config.sphere_radius + config.ball_radius + 5.0;
+
+        self.players
+            .iter()
+            .filter(|p| slots.is_starter(p.id) && slots.get_team_index(p.id) == Some(team))
+            .filter_map(|p| self.sphere_query.get(p.sphere).ok())
+            .any(|(transform, _)| transform.translation.truncate().distance(ball) < contact_radius)
+    }
+}
+
+/// Lleva la pelota al centro y a los titulares a sus posiciones de saque
+fn reset_positions(
+    config: &GameConfig,
+    map: Option<&Map>,
+    slots: &MatchSlots,
+    bodies: &mut KickoffBodies,
+) {
+    let KickoffBodies {
+        players,
+        sphere_query,
+        ball_query,
+        disc_query,
+    } = bodies;
+
+    if let Ok((mut transform, mut velocity, mut impulse, mut ball)) = ball_query.single_mut() {
+        transform.translation.x = 0.0;
+        transform.translation.y = 0.0;
+        *velocity = Velocity::zero();
+        *impulse = ExternalImpulse::default();
+        ball.angular_velocity = 0.0;
+    }
+
+    // Los discos móviles vuelven a su posición y velocidad inicial, como en HaxBall
+    for (mut transform, mut velocity, disc) in disc_query.iter_mut() {
+        transform.translation.x = disc.spawn_position.x;
+        transform.translation.y = disc.spawn_position.y;
+        *velocity = Velocity::linear(disc.spawn_velocity);
+    }
+
+    for (team_index, team) in slots.teams.iter().enumerate() {
+        // Orden estable para que cada jugador repita su lugar en cada saque
+        let mut starters: Vec<u32> = team.starters.iter().copied().collect();
+        starters.sort_unstable();
+
+        for (slot, player_id) in starters.iter().enumerate() {
+            let Some(mut player) = players.iter_mut().find(|p| p.id == *player_id) else {
+                continue;
+            };
+            player.kick_charge = Vec2::ZERO;
+            player.kick_charging = false;
+            player.kick_memory_timer = 0.0;
+
+            if let Ok((mut transform, mut velocity)) = sphere_query.get_mut(player.sphere) {
+                let pos = team_spawn_position(team_index as u8, slot, config, map);
+                transform.translation.x = pos.x;
+                transform.translation.y = pos.y;
+                *velocity = Velocity::zero();
+            }
+        }
+    }
+}
+
+/// Equipo que saca: `preferred` si tiene titulares; si no, el otro (None = nadie en cancha)
+fn kickoff_team(slots: &MatchSlots, preferred: u8) -> Option<u8> {
+    [preferred, 1 - preferred]
+        .into_iter()
+        .find(|team| !slots.teams[*team as usize].starters.is_empty())
+}
+
+/// Envía el marcador a todos los clientes
+fn broadcast_score(network_tx: &NetworkSender, score: &MatchScore, scoring_team: Option<u8>) {
+    let score_msg = ControlMessage::ScoreUpdated {
+        score: score.goals,
+        scoring_team,
+    };
+    if let Ok(data) = bincode::serialize(&score_msg) {
+        let _ = network_tx.0.send(OutgoingMessage::Broadcast {
+            channel: Channel::Reliable,
+            data,
+        });
+    }
+}
+
+/// Máquina de estados del partido: saque inicial, juego, festejo, nuevo saque
+/// y fin del partido por tiempo o por goles
+pub fn update_match_phase(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    loaded_map: Res<LoadedMap>,
+    match_slots: Res<HostMatchSlots>,
+    network_tx: Res<NetworkSender>,
+    progress: MatchProgress,
+    mut bodies: KickoffBodies,
+) {
+    let MatchProgress {
+        rules,
+        mut phase,
+        mut score,
+        mut clock,
+    } = progress;
+
+    match *phase {
+        MatchPhase::PreKickoff => {
+            let Some(kicking_team) = kickoff_team(&match_slots.0, 0) else {
+                return;
+            };
+            reset_positions(&config, loaded_map.0.as_ref(), &match_slots.0, &mut bodies);
+            *phase = MatchPhase::Kickoff { kicking_team };
+            println!("🏁 Saque inicial: saca el equipo {}", kicking_team);
+        }
+        MatchPhase::Kickoff { kicking_team } => {
+            // Si el equipo que saca se quedó sin titulares el saque pasa al otro
+            // (el rival no puede cruzar las barreras) o se espera a que vuelva a haber
+            match kickoff_team(&match_slots.0, kicking_team) {
+                Some(team) if team != kicking_team => {
+                    *phase = MatchPhase::Kickoff { kicking_team: team };
+                    println!(
+                        "🏁 El equipo {} no tiene titulares: saca el equipo {}",
+                        kicking_team, team
+                    );
+                    return;
+                }
+                Some(_) => {}
+                None => {
+                    *phase = MatchPhase::PreKickoff;
+                    return;
+                }
+            }
+
+            // El saque termina cuando un jugador del equipo que saca toca la pelota
+            // (el rival no llega: las barreras del mapa lo frenan)
+            if bodies.kicking_team_touches_ball(&config, &match_slots.0, kicking_team) {
+                *phase = MatchPhase::Playing;
+                println!("▶️  Saque del equipo {} realizado, en juego", kicking_team);
+            }
+        }
+        MatchPhase::Playing => {
+            // El reloj solo corre con la pelota en juego
+            clock.elapsed_secs += time.delta_secs();
+
+            let time_limit = rules.time_limit_secs as f32;
+            if rules.time_limit_secs == 0 || clock.overtime || clock.elapsed_secs < time_limit {
+                return;
+            }
+
+            if score.goals[0] == score.goals[1] {
+                clock.overtime = true;
+                println!("⏱️  Tiempo cumplido con empate: prórroga con gol de oro");
+            } else {
+                let winner = if score.goals[0] > score.goals[1] { 0 } else { 1 };
+                *phase = MatchPhase::Ended {
+                    timer: MATCH_END_SECS,
+                    winner,
+                };
+                println!(
+                    "🏆 Fin del partido por tiempo: gana el equipo {} ({} - {})",
+                    winner, score.goals[0], score.goals[1]
+                );
+            }
+        }
+        MatchPhase::GoalCelebration {
+            timer,
+            conceding_team,
+        } => {
+            let remaining = timer - time.delta_secs();
+            if remaining > 0.0 {
+                *phase = MatchPhase::GoalCelebration {
+                    timer: remaining,
+                    conceding_team,
+                };
+                return;
+            }
+
+            reset_positions(&config, loaded_map.0.as_ref(), &match_slots.0, &mut bodies);
+            score.ball_in_goal = false;
+            *phase = MatchPhase::Kickoff {
+                kicking_team: conceding_team,
+            };
+            println!("🏁 Saque: saca el equipo {}", conceding_team);
+        }
+        MatchPhase::Ended { timer, winner } => {
+            let remaining = timer - time.delta_secs();
+            if remaining > 0.0 {
+                *phase = MatchPhase::Ended {
+                    timer: remaining,
+                    winner,
+                };
+                return;
+            }
+
+            // Nuevo partido: marcador y reloj a cero
+            *score = MatchScore::default();
+            *clock = MatchClock::default();
+            broadcast_score(&network_tx, &score, None);
+            *phase = MatchPhase::PreKickoff;
+            println!("🔁 Comienza un nuevo partido");
+        }
+    }
+}
+
+/// Mantiene los grupos de colisión de cada jugador según MatchSlots: titulares con el
+/// grupo de su equipo (más el flag KO durante el saque), el resto sin colisiones
+pub fn update_player_collision_groups(
+    phase: Res<MatchPhase>,
+    match_slots: Res<HostMatchSlots>,
+    players: Query<&Player>,
+    mut groups_query: Query<&mut CollisionGroups>,
+) {
+    let kickoff_team = match *phase {
+        MatchPhase::Kickoff { kicking_team } => Some(kicking_team),
+        _ => None,
+    };
+
+    for player in players.iter() {
+        let desired = match match_slots.0.get_team_index(player.id) {
+            Some(team_index) if match_slots.0.is_starter(player.id) => {
+                collision::player_groups(team_index, kickoff_team)
+            }
+            _ => CollisionGroups::new(Group::NONE, Group::NONE),
+        };
+
+        // En modo cubo el cubo es el cuerpo que choca
+        let mut bodies = vec![player.sphere];
+        if player.mode_cube_active {
+            bodies.push(player.slide_cube);
+        }
+
+        for entity in bodies {
+            if let Ok(mut groups) = groups_query.get_mut(entity) {
+                if groups.memberships != desired.memberships || groups.filters != desired.filters {
+                    *groups = desired;
+                }
+            }
+        }
+    }
+}
+
+/// Detecta cuando la pelota cruza completamente una línea de gol del mapa
+/// y actualiza el marcador, notificando a todos los clientes.
+pub fn detect_goals(
+    config: Res<GameConfig>,
+    loaded_map: Res<LoadedMap>,
+    ball_query: Query<&Transform, With<Ball>>,
+    network_tx: Res<NetworkSender>,
+    progress: MatchProgress,
+) {
+    let MatchProgress {
+        rules,
+        mut phase,
+        mut score,
+        clock,
+    } = progress;
+
+    // Solo cuentan los goles con la pelota en juego
+    if *phase != MatchPhase::Playing {
+        return;
+    }
+
+    let Some(map) = &loaded_map.0 else {
+        return;
+    };
+    let Ok(ball_transform) = ball_query.single() else {
+        return;
+    };
+
+    let ball_pos = [ball_transform.translation.x, ball_transform.translation.y];
+
+    let crossed_goal = map
+        .goals
+        .iter()
+        .find(|goal| goal.is_fully_crossed(ball_pos, config.ball_radius));
+
+    let Some(goal) = crossed_goal else {
+        // La pelota volvió a la cancha: se puede contar el próximo gol
+        score.ball_in_goal = false;
+        return;
+    };
+
+    if score.ball_in_goal {
+        return;
+    }
+    score.ball_in_goal = true;
+
+    let Some(scoring_team) = goal.scoring_team() else {
+        println!("⚠️  Gol en arco con equipo desconocido: '{}'", goal.team);
+        return;
+    };
+
+    score.goals[scoring_team as usize] += 1;
+    println!(
+        "⚽ ¡GOL del equipo {}! Marcador: {} - {}",
+        scoring_team, score.goals[0], score.goals[1]
+    );
+
+    // Gol de oro en prórroga o límite de goles alcanzado: termina el partido
+    let reached_limit =
+        rules.score_limit > 0 && score.goals[scoring_team as usize] >= rules.score_limit;
+    if clock.overtime || reached_limit {
+        *phase = MatchPhase::Ended {
+            timer: MATCH_END_SECS,
+            winner: scoring_team,
+        };
+        println!("🏆 Fin del partido: gana el equipo {}", scoring_team);
+    } else {
+        *phase = MatchPhase::GoalCelebration {
+            timer: GOAL_CELEBRATION_SECS,
+            conceding_team: 1 - scoring_team,
+        };
+    }
+
+    broadcast_score(&network_tx, &score, Some(scoring_team));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::time::Duration;
+
+    /// Partido sin mapa ni jugadores spawneados: solo la pelota y los recursos
+    fn match_world(slots: MatchSlots, rules: MatchRules, phase: MatchPhase) -> World {
+        let mut world = World::new();
+        let (network_tx, _) = std::sync::mpsc::channel();
+        world.insert_resource(Time::<()>::default());
+        world.insert_resource(GameConfig::default());
+        world.insert_resource(LoadedMap(None));
+        world.insert_resource(HostMatchSlots(slots));
+        world.insert_resource(NetworkSender(network_tx));
+        world.insert_resource(rules);
+        world.insert_resource(phase);
+        world.insert_resource(MatchScore::default());
+        world.insert_resource(MatchClock::default());
+        world.spawn((
+            Transform::default(),
+            Velocity::zero(),
+            ExternalImpulse::default(),
+            Ball {
+                angular_velocity: 0.0,
+            },
+        ));
+        world
+    }
+
+    /// Avanza el reloj `secs` y corre un tick de la máquina de estados
+    fn step(world: &mut World, secs: f32) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(secs));
+        world.run_system_once(update_match_phase).unwrap();
+    }
+
+    fn phase(world: &World) -> MatchPhase {
+        *world.resource::<MatchPhase>()
+    }
+
+    fn only_blue() -> MatchSlots {
+        let mut slots = MatchSlots::default();
+        slots.add_starter(1, 1);
+        slots
+    }
+
+    const NO_LIMITS: MatchRules = MatchRules {
+        time_limit_secs: 0,
+        score_limit: 0,
+    };
+
+    fn both_teams() -> MatchSlots {
+        let mut slots = only_blue();
+        slots.add_starter(2, 0);
+        slots
+    }
+
+    /// Mapa mínimo con el arco rojo en x = -100
+    fn goal_map() -> Map {
+        crate::host::map::load_map_from_str(
+            r#"{ name: "Arco", goals: [{ p0: [-100, -50], p1: [-100, 50], team: "red" }] }"#,
+            "arco",
+        )
+        .unwrap()
+    }
+
+    /// Deja la pelota en `x` y corre la detección de goles
+    fn detect_at(world: &mut World, x: f32) {
+        let mut ball = world.query_filtered::<&mut Transform, With<Ball>>();
+        ball.single_mut(world).unwrap().translation.x = x;
+        world.run_system_once(detect_goals).unwrap();
+    }
+
+    #[test]
+    fn test_first_kickoff_goes_to_a_team_with_starters() {
+        let mut world = match_world(only_blue(), NO_LIMITS, MatchPhase::PreKickoff);
+        step(&mut world, 0.1);
+        assert_eq!(phase(&world), MatchPhase::Kickoff { kicking_team: 1 });
+
+        let mut empty = match_world(MatchSlots::default(), NO_LIMITS, MatchPhase::PreKickoff);
+        step(&mut empty, 0.1);
+        assert_eq!(phase(&empty), MatchPhase::PreKickoff);
+    }
+
+    #[test]
+    fn test_kickoff_moves_to_the_other_team_when_kickers_leave() {
+        // El equipo que recibió el gol se quedó sin titulares durante el festejo
+        let mut world = match_world(
+            only_blue(),
+            NO_LIMITS,
+            MatchPhase::GoalCelebration {
+                timer: 0.05,
+                conceding_team: 0,
+            },
+        );
+        step(&mut world, 0.1);
+        step(&mut world, 0.1);
+        assert_eq!(phase(&world), MatchPhase::Kickoff { kicking_team: 1 });
+
+        // Sin titulares en ningún equipo se vuelve a esperar
+        world.resource_mut::<HostMatchSlots>().0.remove_player(1);
+        step(&mut world, 0.1);
+        assert_eq!(phase(&world), MatchPhase::PreKickoff);
+    }
+
+    #[test]
+    fn test_clock_only_runs_while_playing() {
+        let rules = MatchRules {
+            time_limit_secs: 60,
+            score_limit: 0,
+        };
+        let mut world = match_world(both_teams(), rules, MatchPhase::Kickoff { kicking_team: 0 });
+        step(&mut world, 1.0);
+        assert_eq!(world.resource::<MatchClock>().elapsed_secs, 0.0);
+
+        world.insert_resource(MatchPhase::Playing);
+        step(&mut world, 1.0);
+        assert_eq!(world.resource::<MatchClock>().elapsed_secs, 1.0);
+
+        world.insert_resource(MatchPhase::GoalCelebration {
+            timer: 5.0,
+            conceding_team: 0,
+        });
+        step(&mut world, 1.0);
+        assert_eq!(world.resource::<MatchClock>().elapsed_secs, 1.0);
+    }
+
+    #[test]
+    fn test_time_up_on_a_tie_goes_to_golden_goal() {
+        let rules = MatchRules {
+            time_limit_secs: 2,
+            score_limit: 0,
+        };
+        let mut world = match_world(both_teams(), rules, MatchPhase::Playing);
+        world.resource_mut::<MatchScore>().goals = [1, 1];
+        step(&mut world, 2.5);
+        assert!(world.resource::<MatchClock>().overtime);
+        assert_eq!(phase(&world), MatchPhase::Playing);
+
+        // En prórroga el primer gol termina el partido
+        world.insert_resource(LoadedMap(Some(goal_map())));
+        detect_at(&mut world, -200.0);
+        assert_eq!(world.resource::<MatchScore>().goals, [1, 2]);
+        assert_eq!(
+            phase(&world),
+            MatchPhase::Ended {
+                timer: MATCH_END_SECS,
+                winner: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_time_up_with_a_lead_ends_the_match() {
+        let rules = MatchRules {
+            time_limit_secs: 2,
+            score_limit: 0,
+        };
+        let mut world = match_world(both_teams(), rules, MatchPhase::Playing);
+        world.resource_mut::<MatchScore>().goals = [0, 3];
+        step(&mut world, 2.5);
+        assert!(!world.resource::<MatchClock>().overtime);
+        assert_eq!(
+            phase(&world),
+            MatchPhase::Ended {
+                timer: MATCH_END_SECS,
+                winner: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_detect_goals_stops_at_the_score_limit() {
+        let rules = MatchRules {
+            time_limit_secs: 0,
+            score_limit: 2,
+        };
+        let mut world = match_world(both_teams(), rules, MatchPhase::Playing);
+        world.insert_resource(LoadedMap(Some(goal_map())));
+
+        // Primer gol: festejo y saca el equipo que lo recibió
+        detect_at(&mut world, -200.0);
+        assert_eq!(world.resource::<MatchScore>().goals, [0, 1]);
+        assert_eq!(
+            phase(&world),
+            MatchPhase::GoalCelebration {
+                timer: GOAL_CELEBRATION_SECS,
+                conceding_team: 0
+            }
+        );
+
+        // La pelota sigue dentro del arco: no se cuenta dos veces
+        world.insert_resource(MatchPhase::Playing);
+        detect_at(&mut world, -200.0);
+        assert_eq!(world.resource::<MatchScore>().goals, [0, 1]);
+
+        // Vuelve a la cancha y entra de nuevo: se alcanza el límite
+        detect_at(&mut world, 0.0);
+        detect_at(&mut world, -200.0);
+        assert_eq!(world.resource::<MatchScore>().goals, [0, 2]);
+        assert_eq!(
+            phase(&world),
+            MatchPhase::Ended {
+                timer: MATCH_END_SECS,
+                winner: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_ended_resets_to_pre_kickoff() {
+        let mut world = match_world(
+            both_teams(),
+            NO_LIMITS,
+            MatchPhase::Ended {
+                timer: 1.0,
+                winner: 0,
+            },
+        );
+        world.resource_mut::<MatchScore>().goals = [3, 1];
+        world.resource_mut::<MatchClock>().elapsed_secs = 90.0;
+        world.resource_mut::<MatchClock>().overtime = true;
+
+        step(&mut world, 0.5);
+        assert!(matches!(phase(&world), MatchPhase::Ended { winner: 0, .. }));
+        assert_eq!(world.resource::<MatchScore>().goals, [3, 1]);
+
+        step(&mut world, 0.6);
+        assert_eq!(phase(&world), MatchPhase::PreKickoff);
+        assert_eq!(world.resource::<MatchScore>().goals, [0, 0]);
+        assert_eq!(world.resource::<MatchClock>().elapsed_secs, 0.0);
+        assert!(!world.resource::<MatchClock>().overtime);
+    }
+}
@@ -0,0 +1,333 @@
+// ============================================================================
+// MIGRACIÓN DE HOST - REPLICACIÓN AL SUCESOR Y RESTAURACIÓN
+// ============================================================================
+
+use crate::shared::handoff::{
+    HandoffBody, HandoffPhase, HandoffPlayer, HostHandoff, HANDOFF_INTERVAL_SECS,
+};
+use crate::shared::protocol::{ControlMessage, GameConfig};
+use crate::transport::Channel;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use matchbox_socket::PeerId;
+
+use super::engine::spawn_physics;
+use super::host::{
+    Ball, DynamicDisc, GameInputManager, GameTick, HostMatchSlots, LoadedMap,
+    MatchPhase, MatchScore, NetworkSender, OutgoingMessage, Player, SharedNetworkState,
+};
+use super::network::{FieldBodies, MatchStatus, RoomRegistration};
+
+/// Datos de la sala y sucesor elegido para replicar el estado del partido
+#[derive(Resource)]
+pub struct HostMigration {
+    room_id: String,
+    room_name: String,
+    max_players: u8,
+    map_name: Option<String>,
+    timer: Timer,
+    successor: Option<PeerId>,
+}
+
+impl HostMigration {
+    pub fn new(registration: &RoomRegistration) -> Self {
+        Self {
+            room_id: registration.room.clone(),
+            room_name: registration.room_name.clone(),
+            max_players: registration.max_players,
+            map_name: registration.map_name.clone(),
+            timer: Timer::from_seconds(HANDOFF_INTERVAL_SECS, TimerMode::Repeating),
+            successor: None,
+        }
+    }
+
+    /// Sin proxy (práctica o red local): no hay sala que un sucesor pueda tomar
+    pub fn offline() -> Self {
+        Self {
+            room_id: String::new(),
+            room_name: String::new(),
+            max_players: 0,
+            map_name: None,
+            timer: Timer::from_seconds(HANDOFF_INTERVAL_SECS, TimerMode::Repeating),
+            successor: None,
+        }
+    }
+}
+
+/// Estado recibido del host anterior, pendiente de cargar en el mundo
+#[derive(Resource, Default)]
+pub struct PendingHandoff(pub Option<HostHandoff>);
+
+impl From<MatchPhase> for HandoffPhase {
+    fn from(phase: MatchPhase) -> Self {
+        match phase {
+            MatchPhase::PreKickoff => HandoffPhase::PreKickoff,
+            MatchPhase::Kickoff { kicking_team } => HandoffPhase::Kickoff { kicking_team },
+            MatchPhase::Playing => HandoffPhase::Playing,
+            MatchPhase::GoalCelebration {
+                timer,
+                conceding_team,
+            } => HandoffPhase::GoalCelebration {
+                timer,
+                conceding_team,
+            },
+            MatchPhase::Ended { timer, winner } => HandoffPhase::Ended { timer, winner },
+        }
+    }
+}
+
+impl From<HandoffPhase> for MatchPhase {
+    fn from(phase: HandoffPhase) -> Self {
+        match phase {
+            HandoffPhase::PreKickoff => MatchPhase::PreKickoff,
+            HandoffPhase::Kickoff { kicking_team } => MatchPhase::Kickoff { kicking_team },
+            HandoffPhase::Playing => MatchPhase::Playing,
+            HandoffPhase::GoalCelebration {
+                timer,
+                conceding_team,
+            } => MatchPhase::GoalCelebration {
+                timer,
+                conceding_team,
+            },
+            HandoffPhase::Ended { timer, winner } => MatchPhase::Ended { timer, winner },
+        }
+    }
+}
+
+fn handoff_body(transform: &Transform, velocity: &Velocity, angular_velocity: f32) -> HandoffBody {
+    HandoffBody {
+        position: (transform.translation.x, transform.translation.y),
+        velocity: (velocity.linvel.x, velocity.linvel.y),
+        angular_velocity,
+    }
+}
+
+fn apply_body(body: &HandoffBody, transform: &mut Transform, velocity: &mut Velocity) {
+    transform.translation.x = body.position.0;
+    transform.translation.y = body.position.1;
+    velocity.linvel = Vec2::new(body.velocity.0, body.velocity.1);
+}
+
+/// Configuración, tick y marcador que completan el estado replicado
+#[derive(SystemParam)]
+pub struct HandoffMatch<'w> {
+    pub config: Res<'w, GameConfig>,
+    pub tick: Res<'w, GameTick>,
+    pub score: Res<'w, MatchScore>,
+}
+
+/// Envía periódicamente el estado del partido al sucesor: un cliente conectado que
+/// puede ser host (admins primero, después el de menor ID)
+pub fn replicate_to_successor(
+    time: Res<Time>,
+    mut migration: ResMut<HostMigration>,
+    network_state: Res<SharedNetworkState>,
+    network_tx: Res<NetworkSender>,
+    match_data: HandoffMatch,
+    status: MatchStatus,
+    bodies: FieldBodies,
+) {
+    let HandoffMatch {
+        config,
+        tick,
+        score,
+    } = match_data;
+    let MatchStatus {
+        match_slots,
+        match_clock: clock,
+        match_phase: phase,
+        rules,
+    } = status;
+    let FieldBodies {
+        players,
+        sphere_query,
+        ball: ball_query,
+        discs: disc_query,
+    } = bodies;
+
+    migration.timer.tick(time.delta());
+    if !migration.timer.just_finished() {
+        return;
+    }
+
+    let (registered, next_player_id, sessions, candidates) = {
+        let state = network_state.0.lock().unwrap();
+        (
+            state.proxy_token.is_some(),
+            state.next_player_id,
+            state.sessions.export(),
+            state.host_candidates.clone(),
+        )
+    };
+    // Sin token la sala todavía no está registrada en el proxy
+    if !registered {
+        return;
+    }
+
+    let successor = players
+        .iter()
+        .filter(|p| p.is_ready && p.disconnected_at.is_none())
+        .filter(|p| candidates.contains(&p.peer_id))
+        .min_by_key(|p| (!match_slots.0.is_admin(p.id), p.id));
+    let successor_peer = successor.map(|p| p.peer_id);
+    if successor_peer != migration.successor {
+        match successor {
+            Some(player) => println!("🧭 Sucesor del host: {} ({})", player.name, player.id),
+            None => println!("🧭 Sin sucesor para el host"),
+        }
+        migration.successor = successor_peer;
+    }
+
+    // El hilo de red pide al proxy un token de toma nuevo para cada sucesor
+    let takeover_token = {
+        let mut state = network_state.0.lock().unwrap();
+        state.successor = successor_peer;
+        state.takeover_token.clone()
+    };
+    let Some(peer_id) = successor_peer else {
+        return;
+    };
+    // Hasta que llegue el token del sucesor actual no hay nada que mandarle
+    let Some(takeover_token) = takeover_token
+        .filter(|(issued_for, _)| *issued_for == peer_id)
+        .map(|(_, token)| token)
+    else {
+        return;
+    };
+
+    let Ok((ball_transform, ball_velocity, ball)) = ball_query.single() else {
+        return;
+    };
+
+    let handoff = HostHandoff {
+        room_id: migration.room_id.clone(),
+        room_name: migration.room_name.clone(),
+        max_players: migration.max_players,
+        map_name: migration.map_name.clone(),
+        takeover_token,
+        config: config.clone(),
+        rules: *rules,
+        tick: tick.0,
+        next_player_id,
+        sessions,
+        slots: match_slots.0.clone(),
+        score: score.goals,
+        clock_elapsed_secs: clock.elapsed_secs,
+        overtime: clock.overtime,
+        phase: (*phase).into(),
+        players: players
+            .iter()
+            .filter_map(|player| {
+                let (transform, velocity) = sphere_query.get(player.sphere).ok()?;
+                Some(HandoffPlayer {
+                    id: player.id,
+                    name: player.name.clone(),
+                    team_index: player.team_index,
+                    body: handoff_body(transform, velocity, velocity.angvel),
+                    stamina: player.stamin,
+                })
+            })
+            .collect(),
+        ball: handoff_body(ball_transform, ball_velocity, ball.angular_velocity),
+        discs: disc_query
+            .iter()
+            .map(|(transform, velocity, disc)| {
+                (
+                    disc.index,
+                    handoff_body(transform, velocity, velocity.angvel),
+                )
+            })
+            .collect(),
+    };
+
+    if let Ok(data) = bincode::serialize(&ControlMessage::HostHandoff(Box::new(handoff))) {
+        let _ = network_tx.0.send(OutgoingMessage::ToOne {
+            peer_id,
+            channel: Channel::Reliable,
+            data,
+        });
+    }
+}
+
+/// Recrea los jugadores, la pelota y los discos del host anterior. Solo los
+/// titulares vuelven a su posición en la cancha
+pub fn restore_handoff_bodies(
+    mut commands: Commands,
+    pending: Res<PendingHandoff>,
+    config: Res<GameConfig>,
+    loaded_map: Res<LoadedMap>,
+    mut match_slots: ResMut<HostMatchSlots>,
+    mut ball_query: Query<(&mut Transform, &mut Velocity, &mut Ball)>,
+    mut disc_query: Query<(&mut Transform, &mut Velocity, &DynamicDisc), Without<Ball>>,
+) {
+    let Some(handoff) = &pending.0 else {
+        return;
+    };
+
+    for saved in &handoff.players {
+        // Hasta que su cliente vuelva con el token, el jugador no tiene peer
+        let sphere = spawn_physics(
+            &mut commands,
+            saved.id,
+            saved.name.clone(),
+            PeerId(Default::default()),
+            &config,
+            loaded_map.0.as_ref(),
+            &mut match_slots.0,
+        );
+        if !match_slots.0.is_starter(saved.id) {
+            // Suplentes y espectadores quedan fuera del campo y sin colisiones,
+            // igual que al moverlos al banco
+            commands.entity(sphere).insert((
+                Transform::from_xyz(99999.0, 99999.0, 0.0),
+                CollisionGroups::new(Group::NONE, Group::NONE),
+            ));
+            continue;
+        }
+        let mut transform = Transform::default();
+        let mut velocity = Velocity::zero();
+        apply_body(&saved.body, &mut transform, &mut velocity);
+        commands.entity(sphere).insert((transform, velocity));
+    }
+
+    if let Ok((mut transform, mut velocity, mut ball)) = ball_query.single_mut() {
+        apply_body(&handoff.ball, &mut transform, &mut velocity);
+        ball.angular_velocity = handoff.ball.angular_velocity;
+    }
+    for (mut transform, mut velocity, disc) in disc_query.iter_mut() {
+        if let Some((_, body)) = handoff.discs.iter().find(|(index, _)| *index == disc.index) {
+            apply_body(body, &mut transform, &mut velocity);
+        }
+    }
+
+    println!(
+        "🧭 Estado del host anterior restaurado: {} jugadores, {} - {}",
+        handoff.players.len(),
+        handoff.score[0],
+        handoff.score[1]
+    );
+}
+
+/// Los jugadores restaurados esperan a su cliente como si se hubieran desconectado:
+/// vuelven con su token de reconexión dentro del período de gracia. Los bots
+/// siguen jugando en el host nuevo
+pub fn restore_handoff_players(
+    mut pending: ResMut<PendingHandoff>,
+    mut game_input: ResMut<GameInputManager>,
+    mut players: Query<&mut Player>,
+) {
+    let Some(handoff) = pending.0.take() else {
+        return;
+    };
+    for mut player in players.iter_mut() {
+        if let Some(saved) = handoff.players.iter().find(|p| p.id == player.id) {
+            player.team_index = saved.team_index;
+            player.stamin = saved.stamina;
+            match handoff.slots.bots.get(&player.id) {
+                Some(difficulty) => game_input.add_bot(player.id, *difficulty),
+                None => player.disconnected_at = Some(0.0),
+            }
+        }
+    }
+}
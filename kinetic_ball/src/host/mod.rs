@@ -1,7 +1,16 @@
+mod bots;
+mod chat;
 mod engine;
 mod host;
 mod input;
+mod latency;
 mod map;
+mod match_state;
+mod migration;
 mod network;
+mod reconnect;
 
-pub use host::host;
+#[cfg(test)]
+pub use host::host_loopback;
+pub use host::{host, host_lan, practice, resume_host};
+pub use network::RoomAccess;
@@ -1,31 +1,50 @@
 use crate::networking::hmac_auth;
+use crate::shared::snapshot::{
+    PlayerInfo, QuantizedBall, QuantizedDisc, QuantizedGameState, QuantizedPlayer,
+};
 use crate::shared::*;
+use crate::transport::{Channel, PeerEvent, PeerId, Transport, WebRtcTransport};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
-use matchbox_socket::{PeerId, PeerState, WebRtcSocket};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 
+use super::bots::MAX_BOTS;
+use super::chat::ChatRelay;
 use super::engine::spawn_physics;
 use super::host::{
-    Ball, BroadcastTimer, GameInputManager, GameTick, HostMatchSlots, LoadedMap, NetworkEvent,
-    NetworkReceiver, NetworkSender, NetworkState, OutgoingMessage, Player, Sphere,
+    Ball, BroadcastTimer, DynamicDisc, GameInputManager, GameTick, HostMatchSlots, HostSnapshots,
+    LoadedMap, MatchClock, MatchPhase, MatchScore, NetworkEvent, NetworkReceiver, NetworkSender,
+    NetworkState, OutgoingMessage, Player, SharedNetworkState, Sphere,
 };
+use super::latency::PeerLatency;
+use super::map::collision;
+use super::match_state::team_spawn_position;
+use super::reconnect::RECONNECT_GRACE_SECS;
 
 // ============================================================================
-// NETWORK SERVER - MATCHBOX WEBRTC
+// NETWORK SERVER
 // ============================================================================
 
+/// Cada cuánto el host mide el RTT de cada peer
+const PING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Intentos de tomar la sala del host anterior antes de rendirse
+const TAKEOVER_ATTEMPTS: u32 = 5;
+
+/// Cada cuánto el hilo de red revisa si cambió el sucesor del host
+const SUCCESSOR_POLL: std::time::Duration = std::time::Duration::from_millis(500);
+
 pub fn start_webrtc_server(
     event_tx: mpsc::Sender<NetworkEvent>,
     state: Arc<Mutex<NetworkState>>,
-    room: String,
     outgoing_rx: mpsc::Receiver<OutgoingMessage>,
-    server_host: String,
-    room_name: String,
-    max_players: u8,
-    map_name: Option<String>,
+    registration: RoomRegistration,
 ) {
+    let room = registration.room.clone();
+    let server_host = registration.server_host.clone();
+
     let rt = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
@@ -41,103 +60,174 @@ pub fn start_webrtc_server(
         let http_url = format!("{}://{}", http_scheme, server_host);
         // Obtener versión mínima del servidor para enviar al proxy
         let min_version_str = protocol::ProtocolVersion::current().to_string();
-        let room_url = match register_room_with_proxy(
-            &http_url,
-            &room,
-            &room_name,
-            max_players,
-            map_name.as_deref(),
-            Some(&min_version_str),
-        )
-        .await
-        {
+        let mut registered =
+            register_room_with_proxy(&http_url, &registration, &min_version_str).await;
+        // Al tomar una sala, el proxy puede no haber notado todavía que el host anterior se fue
+        if registration.takeover_token.is_some() {
+            for _ in 1..TAKEOVER_ATTEMPTS {
+                if registered.is_ok() {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                registered =
+                    register_room_with_proxy(&http_url, &registration, &min_version_str).await;
+            }
+        }
+        let token = match registered {
             Ok(token) => {
                 println!("✅ Room '{}' registrada en proxy", room);
-                let ws_url = format!("{}://{}", ws_scheme, server_host);
-                format!("{}/connect?token={}", ws_url, token)
+                state.lock().unwrap().proxy_token = Some(token.clone());
+                token
             }
             Err(e) => {
                 eprintln!("❌ Error registrando room en proxy: {}", e);
                 eprintln!("   Asegúrate de que el proxy está corriendo");
+                // Sin sala no hay a quién atender: cerrar el host
+                let _ = event_tx.send(NetworkEvent::Shutdown);
                 return;
             }
         };
+        let ws_url = format!("{}://{}", ws_scheme, server_host);
+        let room_url = format!("{}/connect?token={}", ws_url, token);
 
         println!("🔗 Connecting to: {}", room_url);
 
-        // Crear WebRtcSocket y conectar a la room
-        let (mut socket, loop_fut) = WebRtcSocket::builder(room_url)
-            .add_channel(matchbox_socket::ChannelConfig::reliable()) // Canal 0: Control (reliable)
-            .add_channel(matchbox_socket::ChannelConfig::unreliable()) // Canal 1: GameData (unreliable)
-            .build();
+        let transport = WebRtcTransport::connect(&room_url);
+        println!("✅ Server WebRTC socket ready, waiting for peers...");
+        tokio::select! {
+            _ = serve(transport, &event_tx, &state, &outgoing_rx) => {}
+            _ = rotate_takeover_token(&http_url, &room, &token, &state) => {}
+        }
+        eprintln!("❌ Se perdió la conexión con el proxy");
+    });
+}
 
-        // Spawn el loop de matchbox (maneja la señalización)
-        tokio::spawn(loop_fut);
+/// Pide al proxy un token de toma nuevo cada vez que cambia el sucesor del host.
+/// El anterior deja de servir, así un sucesor descartado ya no puede tomar la sala
+async fn rotate_takeover_token(
+    http_url: &str,
+    room: &str,
+    token: &str,
+    state: &Arc<Mutex<NetworkState>>,
+) {
+    let mut issued_for: Option<PeerId> = None;
+    loop {
+        tokio::time::sleep(SUCCESSOR_POLL).await;
+        let successor = state.lock().unwrap().successor;
+        if successor == issued_for {
+            continue;
+        }
+        match request_takeover_token(http_url, room, token).await {
+            Ok(takeover_token) => {
+                issued_for = successor;
+                state.lock().unwrap().takeover_token =
+                    successor.map(|peer_id| (peer_id, takeover_token));
+            }
+            // Se reintenta en la próxima vuelta
+            Err(e) => eprintln!("⚠️  No se pudo renovar el token de toma de la sala: {}", e),
+        }
+    }
+}
 
-        println!("✅ Server WebRTC socket ready, waiting for peers...");
+/// Servidor sin proxy (red local o práctica sin conexión). Cuando el transporte se
+/// cierra, avisa a Bevy para que el host termine
+pub fn start_direct_server(
+    event_tx: mpsc::Sender<NetworkEvent>,
+    state: Arc<Mutex<NetworkState>>,
+    outgoing_rx: mpsc::Receiver<OutgoingMessage>,
+    transport: impl Transport,
+) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("No se pudo crear el runtime de Tokio");
 
-        // Loop principal: manejar eventos de peers y mensajes
-        loop {
-            // Procesar eventos de conexión/desconexión de peers
-            for (peer_id, peer_state) in socket.update_peers() {
-                match peer_state {
-                    PeerState::Connected => {
-                        println!("🔗 Peer connected: {:?}", peer_id);
-                        // No asignamos player_id aquí, esperamos el mensaje JOIN
-                    }
-                    PeerState::Disconnected => {
-                        println!("🔌 Peer disconnected: {:?}", peer_id);
-                        let _ = event_tx.send(NetworkEvent::PlayerDisconnected { peer_id });
-                    }
+    println!("✅ Host listo, esperando clientes...");
+    rt.block_on(serve(transport, &event_tx, &state, &outgoing_rx));
+    println!("🔌 Conexión cerrada, cerrando el host");
+    let _ = event_tx.send(NetworkEvent::Shutdown);
+}
+
+/// Loop principal del servidor: eventos de peers, mensajes de los clientes y mensajes
+/// salientes de Bevy. Termina cuando el transporte se cierra
+async fn serve(
+    mut transport: impl Transport,
+    event_tx: &mpsc::Sender<NetworkEvent>,
+    state: &Arc<Mutex<NetworkState>>,
+    outgoing_rx: &mpsc::Receiver<OutgoingMessage>,
+) {
+    let mut last_ping = std::time::Instant::now();
+
+    while !transport.is_closed() {
+        // Procesar eventos de conexión/desconexión de peers
+        for event in transport.update_peers() {
+            match event {
+                PeerEvent::Connected(peer_id) => {
+                    println!("🔗 Peer connected: {:?}", peer_id);
+                    // No asignamos player_id aquí, esperamos el mensaje JOIN
+                }
+                PeerEvent::Disconnected(peer_id) => {
+                    println!("🔌 Peer disconnected: {:?}", peer_id);
+                    state.lock().unwrap().remove_peer(peer_id);
+                    let _ = event_tx.send(NetworkEvent::PlayerDisconnected { peer_id });
                 }
             }
+        }
 
-            // Recibir mensajes del canal 0 (reliable - control)
-            for (peer_id, packet) in socket.channel_mut(0).receive() {
-                if let Ok(msg) = bincode::deserialize::<ControlMessage>(&packet) {
-                    // Manejar mensaje y obtener posible respuesta
-                    if let Some(response) = handle_control_message_typed(&event_tx, &state, peer_id, msg) {
-                        // Enviar respuesta al cliente (ej: VersionMismatch)
-                        if let Ok(data) = bincode::serialize(&response) {
-                            socket.channel_mut(0).send(data.into(), peer_id);
-                        }
+        // Mensajes de control (reliable)
+        for (peer_id, packet) in transport.receive(Channel::Reliable) {
+            if let Ok(msg) = bincode::deserialize::<ControlMessage>(&packet) {
+                // Manejar mensaje y obtener posible respuesta
+                if let Some(response) = handle_control_message_typed(event_tx, state, peer_id, msg)
+                {
+                    // Enviar respuesta al cliente (ej: VersionMismatch)
+                    if let Ok(data) = bincode::serialize(&response) {
+                        transport.send(Channel::Reliable, peer_id, data);
                     }
                 }
             }
+        }
 
-            // Recibir mensajes del canal 1 (unreliable - game data)
-            for (peer_id, packet) in socket.channel_mut(1).receive() {
-                if let Ok(msg) = bincode::deserialize::<GameDataMessage>(&packet) {
-                    handle_game_data_message_typed(&event_tx, peer_id, msg);
+        // Datos de juego (unreliable)
+        for (peer_id, packet) in transport.receive(Channel::Unreliable) {
+            if let Ok(msg) = bincode::deserialize::<GameDataMessage>(&packet) {
+                // Responder pings en el momento para no sumar la latencia del loop de juego
+                if let Some(response) =
+                    handle_game_data_message_typed(event_tx, state, peer_id, msg)
+                {
+                    if let Ok(data) = bincode::serialize(&response) {
+                        transport.send(Channel::Unreliable, peer_id, data);
+                    }
                 }
             }
+        }
 
-            // Enviar mensajes salientes desde Bevy a los clientes
-            while let Ok(outgoing) = outgoing_rx.try_recv() {
-                match outgoing {
-                    OutgoingMessage::ToOne {
-                        peer_id,
-                        channel,
-                        data,
-                    } => {
-                        socket.channel_mut(channel).send(data.into(), peer_id);
-                    }
-                    OutgoingMessage::Broadcast { channel, data } => {
-                        // Colectar peer_ids primero para evitar borrow conflict
-                        let peers: Vec<_> = socket.connected_peers().collect();
-                        for peer_id in peers {
-                            socket
-                                .channel_mut(channel)
-                                .send(data.clone().into(), peer_id);
-                        }
-                    }
-                }
+        // Ping periódico a cada peer para medir su latencia
+        if last_ping.elapsed() >= PING_INTERVAL {
+            last_ping = std::time::Instant::now();
+            let ping = GameDataMessage::Ping {
+                timestamp: now_millis(),
+            };
+            if let Ok(data) = bincode::serialize(&ping) {
+                transport.broadcast(Channel::Unreliable, data);
             }
+        }
 
-            // Pequeña pausa para no saturar el CPU
-            tokio::time::sleep(tokio::time::Duration::from_millis(1)).await;
+        // Enviar mensajes salientes desde Bevy a los clientes
+        while let Ok(outgoing) = outgoing_rx.try_recv() {
+            match outgoing {
+                OutgoingMessage::ToOne {
+                    peer_id,
+                    channel,
+                    data,
+                } => transport.send(channel, peer_id, data),
+                OutgoingMessage::Broadcast { channel, data } => transport.broadcast(channel, data),
+            }
         }
-    });
+
+        // Pequeña pausa para no saturar el CPU
+        tokio::time::sleep(tokio::time::Duration::from_millis(1)).await;
+    }
 }
 
 /// Maneja un mensaje de control y devuelve una respuesta opcional para enviar al cliente
@@ -148,9 +238,13 @@ pub fn handle_control_message_typed(
     msg: ControlMessage,
 ) -> Option<ControlMessage> {
     match msg {
-        ControlMessage::Join { player_name, client_version } => {
+        ControlMessage::Join {
+            player_name,
+            client_version,
+            reconnect_token,
+        } => {
             // Verificar versión del cliente
-            let (min_version, id) = {
+            let (id, reconnect_token, resumed) = {
                 let mut s = state.lock().unwrap();
 
                 // Obtener versión mínima
@@ -175,17 +269,34 @@ pub fn handle_control_message_typed(
                     println!("⚠️  Cliente sin versión (legacy), aceptando...");
                 }
 
-                let id = s.next_player_id;
-                s.next_player_id += 1;
-                (min_version, id)
+                // Con un token vigente vuelve el mismo jugador; si no, uno nuevo
+                let now = std::time::Instant::now();
+                let (id, token, resumed) = match reconnect_token
+                    .and_then(|token| s.sessions.resume(token, now).map(|id| (id, token)))
+                {
+                    Some((id, token)) => (id, token, true),
+                    None => {
+                        let id = s.next_player_id;
+                        s.next_player_id += 1;
+                        (id, s.sessions.issue(id), false)
+                    }
+                };
+                s.player_owners.insert(id, peer_id);
+                (id, token, resumed)
             };
 
-            println!("🎮 Player {} joined: {}", id, player_name);
+            if resumed {
+                println!("🔁 Player {} reconnected: {}", id, player_name);
+            } else {
+                println!("🎮 Player {} joined: {}", id, player_name);
+            }
 
             let _ = event_tx.send(NetworkEvent::NewPlayer {
                 id,
                 name: player_name,
                 peer_id,
+                reconnect_token,
+                resumed,
             });
 
             None // El Welcome se envía desde process_network_messages
@@ -196,6 +307,14 @@ pub fn handle_control_message_typed(
             None
         }
         ControlMessage::Leave { player_id } => {
+            {
+                let mut s = state.lock().unwrap();
+                if !s.check_owner(peer_id, player_id, "Leave") {
+                    return None;
+                }
+                s.player_owners.remove(&player_id);
+                s.sessions.revoke(player_id);
+            }
             println!("👋 Player {} requested to leave", player_id);
             let _ = event_tx.send(NetworkEvent::PlayerLeave { player_id });
             None
@@ -237,6 +356,61 @@ pub fn handle_control_message_typed(
             });
             None
         }
+        ControlMessage::RequestReplay => {
+            println!("⏪ RequestReplay request from peer {:?}", peer_id);
+            let _ = event_tx.send(NetworkEvent::RequestReplay {
+                admin_peer_id: peer_id,
+            });
+            None
+        }
+        ControlMessage::AddBot {
+            team_index,
+            difficulty,
+        } => {
+            println!(
+                "🤖 AddBot request: team {} ({:?}) from peer {:?}",
+                team_index, difficulty, peer_id
+            );
+            let _ = event_tx.send(NetworkEvent::AddBot {
+                admin_peer_id: peer_id,
+                team_index,
+                difficulty,
+            });
+            None
+        }
+        ControlMessage::RemoveBot { player_id } => {
+            println!("🤖 RemoveBot request: player {}", player_id);
+            let _ = event_tx.send(NetworkEvent::RemoveBot {
+                admin_peer_id: peer_id,
+                player_id,
+            });
+            None
+        }
+        ControlMessage::CanHost => {
+            println!("🧭 Peer {:?} puede tomar el lugar del host", peer_id);
+            state.lock().unwrap().host_candidates.insert(peer_id);
+            None
+        }
+        ControlMessage::SendChat {
+            player_id,
+            kind,
+            text,
+        } => {
+            if !state
+                .lock()
+                .unwrap()
+                .check_owner(peer_id, player_id, "Chat")
+            {
+                return None;
+            }
+            let _ = event_tx.send(NetworkEvent::Chat {
+                peer_id,
+                player_id,
+                kind,
+                text,
+            });
+            None
+        }
         _ => {
             // Otros mensajes de control del servidor no deberían venir del cliente
             None
@@ -244,45 +418,141 @@ pub fn handle_control_message_typed(
     }
 }
 
+/// Maneja un mensaje de datos de juego y devuelve una respuesta opcional (Pong)
 pub fn handle_game_data_message_typed(
     event_tx: &mpsc::Sender<NetworkEvent>,
-    _peer_id: PeerId,
+    state: &Arc<Mutex<NetworkState>>,
+    peer_id: PeerId,
     msg: GameDataMessage,
-) {
+) -> Option<GameDataMessage> {
     match msg {
-        GameDataMessage::Input { player_id, input } => {
-            // Ahora usamos el player_id del mensaje directamente
-            let _ = event_tx.send(NetworkEvent::PlayerInputById { player_id, input });
+        GameDataMessage::Input {
+            player_id,
+            sequence,
+            input,
+        } => {
+            // Solo se aceptan inputs de jugadores propios del peer
+            if !state
+                .lock()
+                .unwrap()
+                .check_owner(peer_id, player_id, "Input")
+            {
+                return None;
+            }
+            let _ = event_tx.send(NetworkEvent::PlayerInputById {
+                player_id,
+                sequence,
+                input,
+            });
+            None
         }
-        GameDataMessage::Ping { timestamp } => {
-            println!("Debería responder con Pong {}", timestamp);
+        GameDataMessage::Ping { timestamp } => Some(GameDataMessage::Pong {
+            client_timestamp: timestamp,
+            server_timestamp: now_millis(),
+        }),
+        GameDataMessage::Pong {
+            client_timestamp, ..
+        } => {
+            // Respuesta a un ping nuestro: client_timestamp es la hora a la que lo enviamos
+            let rtt_ms = now_millis().saturating_sub(client_timestamp) as f32;
+            let _ = event_tx.send(NetworkEvent::LatencySample { peer_id, rtt_ms });
+            None
+        }
+        GameDataMessage::SnapshotAck { tick } => {
+            let _ = event_tx.send(NetworkEvent::SnapshotAck { peer_id, tick });
+            None
         }
         _ => {
             // Otros mensajes del servidor no deberían venir del cliente
+            None
         }
     }
 }
 
+/// Hora actual en ms desde UNIX_EPOCH (timestamps de GameState y pings)
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
 pub fn update_input_manager(mut game_input: ResMut<GameInputManager>) {
     game_input.tick();
 }
 
+/// Canales con el hilo de red y el estado que el host lleva por peer
+#[derive(SystemParam)]
+pub struct PeerLinks<'w> {
+    pub network_rx: ResMut<'w, NetworkReceiver>,
+    pub network_tx: Res<'w, NetworkSender>,
+    pub snapshots: ResMut<'w, HostSnapshots>,
+    pub latency: ResMut<'w, PeerLatency>,
+    pub shared_state: Res<'w, SharedNetworkState>,
+}
+
+/// Sala que ven los clientes al unirse: configuración, mapa, slots, marcador y fase
+#[derive(SystemParam)]
+pub struct HostRoom<'w> {
+    pub config: Res<'w, GameConfig>,
+    pub loaded_map: Res<'w, LoadedMap>,
+    pub match_slots: ResMut<'w, HostMatchSlots>,
+    pub score: Res<'w, MatchScore>,
+    pub match_phase: Res<'w, MatchPhase>,
+}
+
+type SphereBodies<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut Transform, &'static mut Velocity, &'static mut CollisionGroups),
+    With<Sphere>,
+>;
+
+/// Jugadores y sus cuerpos físicos
+#[derive(SystemParam)]
+pub struct PlayerBodies<'w, 's> {
+    pub players: Query<'w, 's, (&'static mut Player, Entity)>,
+    pub sphere_query: SphereBodies<'w, 's>,
+}
+
 pub fn process_network_messages(
     mut commands: Commands,
-    network_rx: ResMut<NetworkReceiver>,
-    network_tx: Res<NetworkSender>,
-    config: Res<GameConfig>,
-    loaded_map: Res<LoadedMap>,
+    time: Res<Time>,
     mut game_input: ResMut<GameInputManager>,
-    mut players: Query<(&mut Player, Entity)>,
-    mut match_slots: ResMut<HostMatchSlots>,
-    mut sphere_query: Query<(&mut Transform, &mut Velocity, &mut CollisionGroups), With<Sphere>>,
+    links: PeerLinks,
+    room: HostRoom,
+    bodies: PlayerBodies,
+    mut chat: ChatRelay,
 ) {
+    let PeerLinks {
+        network_rx,
+        network_tx,
+        mut snapshots,
+        mut latency,
+        shared_state,
+    } = links;
+    let HostRoom {
+        config,
+        loaded_map,
+        mut match_slots,
+        score,
+        match_phase,
+    } = room;
+    let PlayerBodies {
+        mut players,
+        mut sphere_query,
+    } = bodies;
     let mut slots_changed = false;
 
     while let Ok(event) = network_rx.0.lock().unwrap().try_recv() {
         match event {
-            NetworkEvent::NewPlayer { id, name, peer_id } => {
+            NetworkEvent::NewPlayer {
+                id,
+                name,
+                peer_id,
+                reconnect_token,
+                resumed,
+            } => {
                 // Agregar jugador al GameInputManager
                 game_input.add_player(id);
 
@@ -290,75 +560,113 @@ pub fn process_network_messages(
                 let welcome_msg = ControlMessage::Welcome {
                     player_id: id,
                     map: loaded_map.0.clone(),
+                    reconnect_token,
                 };
 
                 if let Ok(data) = bincode::serialize(&welcome_msg) {
                     println!("📤 Enviando WELCOME a jugador {}", id);
                     let _ = network_tx.0.send(OutgoingMessage::ToOne {
                         peer_id,
-                        channel: 0, // Canal reliable
+                        channel: Channel::Reliable,
                         data,
                     });
                 }
 
-                spawn_physics(&mut commands, id, name, peer_id, &config, &mut match_slots.0);
-                slots_changed = true;
+                // Reconexión: el jugador sigue en el juego con su equipo, lugar y admin;
+                // solo cambia el peer. Si ya venció el período de gracia entra como nuevo
+                let reattached = resumed
+                    && players
+                        .iter_mut()
+                        .find(|(p, _)| p.id == id)
+                        .map(|(mut player, _)| {
+                            player.peer_id = peer_id;
+                            player.is_ready = false;
+                            player.disconnected_at = None;
+                        })
+                        .is_some();
+
+                if reattached {
+                    println!("🔁 Jugador {} ({}) recuperó su lugar", name, id);
+                } else {
+                    spawn_physics(
+                        &mut commands,
+                        id,
+                        name,
+                        peer_id,
+                        &config,
+                        loaded_map.0.as_ref(),
+                        &mut match_slots.0,
+                    );
+                    slots_changed = true;
+                }
 
                 // Send current slots state to the new player
                 let slots_msg = ControlMessage::SlotsUpdated(match_slots.0.clone());
                 if let Ok(data) = bincode::serialize(&slots_msg) {
                     let _ = network_tx.0.send(OutgoingMessage::ToOne {
                         peer_id,
-                        channel: 0,
+                        channel: Channel::Reliable,
+                        data,
+                    });
+                }
+
+                // Enviar el marcador actual al nuevo jugador
+                let score_msg = ControlMessage::ScoreUpdated {
+                    score: score.goals,
+                    scoring_team: None,
+                };
+                if let Ok(data) = bincode::serialize(&score_msg) {
+                    let _ = network_tx.0.send(OutgoingMessage::ToOne {
+                        peer_id,
+                        channel: Channel::Reliable,
                         data,
                     });
                 }
             }
 
             NetworkEvent::PlayerInput { peer_id, input } => {
-                // Buscar el player_id real usando el peer_id (legacy, un jugador por peer)
+                // Buscar el player_id real usando el peer_id (legacy, un jugador por peer, sin secuencia)
                 for (player, _) in players.iter() {
                     if player.peer_id == peer_id {
-                        game_input.update_input(player.id, input.clone());
+                        let sequence = game_input.last_sequence(player.id);
+                        game_input.update_input(player.id, sequence, input);
                         break;
                     }
                 }
             }
 
-            NetworkEvent::PlayerInputById { player_id, input } => {
+            NetworkEvent::PlayerInputById {
+                player_id,
+                sequence,
+                input,
+            } => {
                 // Input identificado directamente por player_id (multijugador local)
-                game_input.update_input(player_id, input);
+                game_input.update_input(player_id, sequence, input);
             }
 
-            NetworkEvent::PlayerDisconnected { peer_id } => {
-                for (player, entity) in players.iter() {
-                    if player.peer_id == peer_id {
-                        // Remove from slots
-                        match_slots.0.remove_player(player.id);
-                        match_slots.0.admins.remove(&player.id);
-                        slots_changed = true;
+            NetworkEvent::SnapshotAck { peer_id, tick } => {
+                snapshots.encoder.ack(peer_id, tick);
+            }
 
-                        // Notificar a todos los clientes que este jugador se desconectó
-                        let disconnect_msg = ControlMessage::PlayerDisconnected {
-                            player_id: player.id,
-                        };
-                        if let Ok(data) = bincode::serialize(&disconnect_msg) {
-                            let _ = network_tx.0.send(OutgoingMessage::Broadcast {
-                                channel: 0, // Canal reliable
-                                data,
-                            });
-                        }
+            NetworkEvent::LatencySample { peer_id, rtt_ms } => {
+                latency.record(peer_id, rtt_ms);
+            }
 
-                        // Despawnear tanto Player como Sphere con todos sus hijos
-                        commands.entity(player.sphere).despawn();
-                        commands.entity(entity).despawn();
-                        // Remover del GameInputManager
-                        game_input.remove_player(player.id);
+            NetworkEvent::PlayerDisconnected { peer_id } => {
+                snapshots.forget(&peer_id);
+                latency.forget(&peer_id);
+                // Un peer puede tener varios jugadores locales: se guarda el lugar de todos.
+                // Si no se reconectan a tiempo los quita expire_disconnected_players
+                for (mut player, _) in players.iter_mut() {
+                    if player.peer_id == peer_id && player.disconnected_at.is_none() {
+                        player.disconnected_at = Some(time.elapsed_secs_f64());
+                        player.is_ready = false;
+                        // Reiniciar su input para que no quede con teclas apretadas
+                        game_input.add_player(player.id);
                         println!(
-                            "❌ Jugador {} ({}) desconectado y removido",
-                            player.name, player.id
+                            "⏳ Jugador {} ({}) desconectado, se guarda su lugar {:.0}s",
+                            player.name, player.id, RECONNECT_GRACE_SECS
                         );
-                        break;
                     }
                 }
             }
@@ -388,7 +696,7 @@ pub fn process_network_messages(
                         let disconnect_msg = ControlMessage::PlayerDisconnected { player_id };
                         if let Ok(data) = bincode::serialize(&disconnect_msg) {
                             let _ = network_tx.0.send(OutgoingMessage::Broadcast {
-                                channel: 0, // Canal reliable
+                                channel: Channel::Reliable,
                                 data,
                             });
                         }
@@ -398,6 +706,7 @@ pub fn process_network_messages(
                         commands.entity(entity).despawn();
                         // Remover del GameInputManager
                         game_input.remove_player(player.id);
+                        chat.limiter.forget(player.id);
                         println!(
                             "👋 Jugador {} ({}) salió voluntariamente y fue removido",
                             player.name, player.id
@@ -422,8 +731,9 @@ pub fn process_network_messages(
                 // Verify admin has permission
                 if let Some(admin_id) = admin_player_id {
                     if match_slots.0.is_admin(admin_id) {
-                        // Check if player was a starter before
+                        // Check if player was a starter before (and on which team)
                         let was_starter = match_slots.0.is_starter(player_id);
+                        let previous_team = match_slots.0.get_team_index(player_id);
 
                         // Move the player in slots
                         match_slots.0.move_player(player_id, team_index, is_starter);
@@ -461,21 +771,29 @@ pub fn process_network_messages(
                                             "🚫 Jugador {} física desactivada (fuera del campo)",
                                             player_id
                                         );
-                                    } else if !was_starter && now_starter {
-                                        // Entering field: spawn at team position
-                                        let spawn_x = if player.team_index == 0 {
-                                            -500.0 - (player_id as f32 * 100.0)
-                                        } else {
-                                            500.0 + (player_id as f32 * 100.0)
-                                        };
-                                        transform.translation.x = spawn_x;
-                                        transform.translation.y = 0.0;
-                                        // Re-enable collisions (GROUP_4 = players)
-                                        collision_groups.memberships = Group::GROUP_4;
-                                        collision_groups.filters = Group::ALL ^ Group::GROUP_5;
+                                    } else if now_starter
+                                        && (!was_starter || previous_team != team_index)
+                                    {
+                                        // Entering field or switching sides: spawn on the
+                                        // team's half, at the team's spawn point
+                                        let slot =
+                                            match_slots.0.starter_slot(player_id).unwrap_or(0);
+                                        let spawn = team_spawn_position(
+                                            player.team_index,
+                                            slot,
+                                            &config,
+                                            loaded_map.0.as_ref(),
+                                        );
+                                        transform.translation.x = spawn.x;
+                                        transform.translation.y = spawn.y;
+                                        velocity.linvel = Vec2::ZERO;
+                                        velocity.angvel = 0.0;
+                                        // Re-enable collisions with the team group
+                                        *collision_groups =
+                                            collision::player_groups(player.team_index, None);
                                         println!(
-                                            "✅ Jugador {} física activada (en el campo)",
-                                            player_id
+                                            "✅ Jugador {} en el campo: equipo {} en ({:.0}, {:.0})",
+                                            player_id, player.team_index, spawn.x, spawn.y
                                         );
                                     }
                                 }
@@ -508,7 +826,23 @@ pub fn process_network_messages(
 
                 // Verify admin has permission
                 if let Some(admin_id) = admin_player_id {
-                    if match_slots.0.is_admin(admin_id) {
+                    if match_slots.0.is_admin(admin_id) && match_slots.0.is_bot(player_id) {
+                        // Un bot no tiene peer al que avisar: se quita como con RemoveBot
+                        if let Some((player, entity)) =
+                            players.iter().find(|(p, _)| p.id == player_id)
+                        {
+                            remove_bot(
+                                &mut commands,
+                                &mut match_slots,
+                                &mut game_input,
+                                &network_tx,
+                                player,
+                                entity,
+                            );
+                            slots_changed = true;
+                            println!("🤖 Bot {} expulsado por admin {}", player_id, admin_id);
+                        }
+                    } else if match_slots.0.is_admin(admin_id) {
                         // Find and kick the player
                         for (player, entity) in players.iter() {
                             if player.id == player_id {
@@ -517,12 +851,29 @@ pub fn process_network_messages(
                                 match_slots.0.admins.remove(&player.id);
                                 slots_changed = true;
 
+                                // El peer deja de controlarlo y su token ya no lo recupera
+                                {
+                                    let mut s = shared_state.0.lock().unwrap();
+                                    s.player_owners.remove(&player_id);
+                                    s.sessions.revoke(player_id);
+                                }
+
+                                // Avisar al expulsado (su cliente cierra la conexión)
+                                let kicked_msg = ControlMessage::Kicked { player_id };
+                                if let Ok(data) = bincode::serialize(&kicked_msg) {
+                                    let _ = network_tx.0.send(OutgoingMessage::ToOne {
+                                        peer_id: player.peer_id,
+                                        channel: Channel::Reliable,
+                                        data,
+                                    });
+                                }
+
                                 // Notify all clients
                                 let disconnect_msg =
                                     ControlMessage::PlayerDisconnected { player_id };
                                 if let Ok(data) = bincode::serialize(&disconnect_msg) {
                                     let _ = network_tx.0.send(OutgoingMessage::Broadcast {
-                                        channel: 0,
+                                        channel: Channel::Reliable,
                                         data,
                                     });
                                 }
@@ -531,6 +882,7 @@ pub fn process_network_messages(
                                 commands.entity(player.sphere).despawn();
                                 commands.entity(entity).despawn();
                                 game_input.remove_player(player.id);
+                                chat.limiter.forget(player.id);
                                 println!("👢 Jugador {} expulsado por admin {}", player_id, admin_id);
                                 break;
                             }
@@ -557,7 +909,9 @@ pub fn process_network_messages(
 
                 // Verify sender is admin
                 if let Some(admin_id) = admin_player_id {
-                    if match_slots.0.is_admin(admin_id) {
+                    if match_slots.0.is_bot(player_id) {
+                        println!("⚠️ Admin rechazado: el jugador {} es un bot", player_id);
+                    } else if match_slots.0.is_admin(admin_id) {
                         if is_admin {
                             match_slots.0.add_admin(player_id);
                             println!("👑 Jugador {} ahora es admin (otorgado por {})", player_id, admin_id);
@@ -574,6 +928,140 @@ pub fn process_network_messages(
                     }
                 }
             }
+
+            NetworkEvent::Chat {
+                peer_id,
+                player_id,
+                kind,
+                text,
+            } => {
+                let all_players: Vec<&Player> = players.iter().map(|(p, _)| p).collect();
+                chat.relay(&match_slots.0, &all_players, peer_id, player_id, kind, text);
+            }
+
+            NetworkEvent::RequestReplay { admin_peer_id } => {
+                // Find admin's player_id from peer_id
+                let admin_player_id = players
+                    .iter()
+                    .find(|(p, _)| p.peer_id == admin_peer_id)
+                    .map(|(p, _)| p.id);
+
+                // Verify sender is admin
+                if let Some(admin_id) = admin_player_id {
+                    if !match_slots.0.is_admin(admin_id) {
+                        println!(
+                            "⚠️ Player {} tried to request a replay but is not admin",
+                            admin_id
+                        );
+                    } else if !matches!(
+                        *match_phase,
+                        MatchPhase::GoalCelebration { .. } | MatchPhase::Ended { .. }
+                    ) {
+                        // Los clientes dejan de jugar durante la repetición y los bots
+                        // no: solo mientras nadie puede tocar la pelota (festejo o fin)
+                        println!("⚠️ Repetición rechazada: el partido está en juego");
+                    } else {
+                        println!("⏪ Repetición pedida por admin {}", admin_id);
+                        if let Ok(data) = bincode::serialize(&ControlMessage::InstantReplay) {
+                            let _ = network_tx.0.send(OutgoingMessage::Broadcast {
+                                channel: Channel::Reliable,
+                                data,
+                            });
+                        }
+                    }
+                }
+            }
+
+            NetworkEvent::AddBot {
+                admin_peer_id,
+                team_index,
+                difficulty,
+            } => {
+                // Find admin's player_id from peer_id
+                let admin_player_id = players
+                    .iter()
+                    .find(|(p, _)| p.peer_id == admin_peer_id)
+                    .map(|(p, _)| p.id);
+
+                // Verify sender is admin
+                if let Some(admin_id) = admin_player_id {
+                    if !match_slots.0.is_admin(admin_id) {
+                        println!("⚠️ Player {} tried to add a bot but is not admin", admin_id);
+                    } else if team_index > 1 {
+                        println!("⚠️ Bot rechazado: equipo {} inválido", team_index);
+                    } else if match_slots.0.bots.len() >= MAX_BOTS {
+                        println!("⚠️ Bot rechazado: máximo {} bots", MAX_BOTS);
+                    } else {
+                        // Los bots comparten la numeración de los jugadores
+                        let id = {
+                            let mut s = shared_state.0.lock().unwrap();
+                            let id = s.next_player_id;
+                            s.next_player_id += 1;
+                            id
+                        };
+                        let name = format!("Bot {} {}", difficulty.label(), id);
+                        match_slots.0.add_bot(id, difficulty);
+                        match_slots.0.add_starter(id, team_index);
+                        game_input.add_bot(id, difficulty);
+                        // Sin peer: no recibe snapshots ni cuenta como desconectado
+                        spawn_physics(
+                            &mut commands,
+                            id,
+                            name,
+                            PeerId(Default::default()),
+                            &config,
+                            loaded_map.0.as_ref(),
+                            &mut match_slots.0,
+                        );
+                        slots_changed = true;
+                        println!(
+                            "🤖 Bot {} agregado al equipo {} por admin {}",
+                            id, team_index, admin_id
+                        );
+                    }
+                }
+            }
+
+            NetworkEvent::RemoveBot {
+                admin_peer_id,
+                player_id,
+            } => {
+                // Find admin's player_id from peer_id
+                let admin_player_id = players
+                    .iter()
+                    .find(|(p, _)| p.peer_id == admin_peer_id)
+                    .map(|(p, _)| p.id);
+
+                // Verify sender is admin (and that the target really is a bot)
+                if let Some(admin_id) = admin_player_id {
+                    if !match_slots.0.is_admin(admin_id) {
+                        println!(
+                            "⚠️ Player {} tried to remove a bot but is not admin",
+                            admin_id
+                        );
+                    } else if match_slots.0.is_bot(player_id) {
+                        if let Some((player, entity)) =
+                            players.iter().find(|(p, _)| p.id == player_id)
+                        {
+                            remove_bot(
+                                &mut commands,
+                                &mut match_slots,
+                                &mut game_input,
+                                &network_tx,
+                                player,
+                                entity,
+                            );
+                            slots_changed = true;
+                            println!("🤖 Bot {} quitado por admin {}", player_id, admin_id);
+                        }
+                    }
+                }
+            }
+
+            NetworkEvent::Shutdown => {
+                println!("🛑 Host detenido");
+                commands.write_message(AppExit::Success);
+            }
         }
     }
 
@@ -582,23 +1070,96 @@ pub fn process_network_messages(
         let slots_msg = ControlMessage::SlotsUpdated(match_slots.0.clone());
         if let Ok(data) = bincode::serialize(&slots_msg) {
             let _ = network_tx.0.send(OutgoingMessage::Broadcast {
-                channel: 0,
+                channel: Channel::Reliable,
                 data,
             });
         }
     }
 }
 
+/// Quita un bot de la sala: sale de los slots y de la lista de bots, y los clientes
+/// lo ven irse como a un jugador desconectado
+fn remove_bot(
+    commands: &mut Commands,
+    match_slots: &mut HostMatchSlots,
+    game_input: &mut GameInputManager,
+    network_tx: &NetworkSender,
+    player: &Player,
+    entity: Entity,
+) {
+    match_slots.0.remove_player(player.id);
+    match_slots.0.bots.remove(&player.id);
+
+    let disconnect_msg = ControlMessage::PlayerDisconnected {
+        player_id: player.id,
+    };
+    if let Ok(data) = bincode::serialize(&disconnect_msg) {
+        let _ = network_tx.0.send(OutgoingMessage::Broadcast {
+            channel: Channel::Reliable,
+            data,
+        });
+    }
+
+    commands.entity(player.sphere).despawn();
+    commands.entity(entity).despawn();
+    game_input.remove_player(player.id);
+}
+
+/// Ritmo de envío del estado: timer, tick y snapshots ya enviados
+#[derive(SystemParam)]
+pub struct BroadcastClock<'w> {
+    pub broadcast_timer: ResMut<'w, BroadcastTimer>,
+    pub tick: ResMut<'w, GameTick>,
+    pub snapshots: ResMut<'w, HostSnapshots>,
+}
+
+/// Slots, reloj, fase y reglas que viajan en cada GameState
+#[derive(SystemParam)]
+pub struct MatchStatus<'w> {
+    pub match_slots: Res<'w, HostMatchSlots>,
+    pub match_clock: Res<'w, MatchClock>,
+    pub match_phase: Res<'w, MatchPhase>,
+    pub rules: Res<'w, MatchRules>,
+}
+
+type BallBodies<'w, 's> =
+    Query<'w, 's, (&'static Transform, &'static Velocity, &'static Ball), Without<Sphere>>;
+
+/// Todo lo que se mueve en la cancha
+#[derive(SystemParam)]
+pub struct FieldBodies<'w, 's> {
+    pub players: Query<'w, 's, &'static Player>,
+    pub sphere_query: Query<'w, 's, (&'static Transform, &'static Velocity), With<Sphere>>,
+    pub ball: BallBodies<'w, 's>,
+    pub discs: Query<'w, 's, (&'static Transform, &'static Velocity, &'static DynamicDisc)>,
+}
+
 pub fn broadcast_game_state(
     time: Res<Time>,
-    mut broadcast_timer: ResMut<BroadcastTimer>,
-    mut tick: ResMut<GameTick>,
-    players: Query<&Player>,
-    sphere_query: Query<(&Transform, &Velocity), With<Sphere>>,
-    ball: Query<(&Transform, &Velocity, &Ball), Without<Sphere>>,
     network_tx: Res<NetworkSender>,
-    match_slots: Res<HostMatchSlots>,
+    game_input: Res<GameInputManager>,
+    clock: BroadcastClock,
+    status: MatchStatus,
+    bodies: FieldBodies,
 ) {
+    let BroadcastClock {
+        mut broadcast_timer,
+        mut tick,
+        mut snapshots,
+    } = clock;
+    let MatchStatus {
+        match_slots,
+        match_clock,
+        match_phase,
+        rules,
+    } = status;
+    let FieldBodies {
+        players,
+        sphere_query,
+        ball,
+        discs,
+    } = bodies;
+
     // Actualizar timer
     broadcast_timer.0.tick(time.delta());
 
@@ -637,6 +1198,7 @@ pub fn broadcast_game_state(
                     active_movement: player.active_movement.clone(),
                     team_index: player.team_index,
                     mode_cube_active: player.mode_cube_active,
+                    last_input_sequence: game_input.last_sequence(player.id),
                 })
             } else {
                 println!(
@@ -672,47 +1234,83 @@ pub fn broadcast_game_state(
         }
     };
 
-    let game_state = ServerMessage::GameState {
-        tick: tick.0,
-        timestamp: std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64,
-        players: player_states,
-        ball: ball_state,
-    };
+    let disc_states: Vec<DiscState> = discs
+        .iter()
+        .map(|(transform, velocity, disc)| DiscState {
+            index: disc.index,
+            position: (transform.translation.x, transform.translation.y),
+            velocity: (velocity.linvel.x, velocity.linvel.y),
+        })
+        .collect();
 
-    // Extraer datos de game_state (ServerMessage) para GameDataMessage
-    let (tick_num, timestamp_num, players_vec, ball_state_data) = match game_state {
-        ServerMessage::GameState {
-            tick,
-            timestamp,
-            players,
-            ball,
-        } => (tick, timestamp, players, ball),
-        _ => return, // No debería pasar
+    let clock_state = MatchClockState {
+        elapsed_secs: match_clock.elapsed_secs,
+        time_limit_secs: rules.time_limit_secs,
+        overtime: match_clock.overtime,
+        winner: match *match_phase {
+            MatchPhase::Ended { winner, .. } => Some(winner),
+            _ => None,
+        },
     };
 
-    // Crear GameDataMessage para el canal unreliable
-    let game_data_msg = GameDataMessage::GameState {
-        tick: tick_num,
-        timestamp: timestamp_num,
-        players: players_vec,
-        ball: ball_state_data,
-    };
+    // Nombre y equipo van por el canal reliable, solo cuando cambian
+    let mut roster: Vec<PlayerInfo> = player_states
+        .iter()
+        .map(|ps| PlayerInfo {
+            id: ps.id,
+            name: ps.name.clone(),
+            team_index: ps.team_index,
+        })
+        .collect();
+    roster.sort_by_key(|info| info.id);
+    if roster != snapshots.roster {
+        snapshots.roster = roster;
+        snapshots.roster_sent.clear();
+    }
+
+    snapshots.encoder.push(QuantizedGameState {
+        tick: tick.0,
+        timestamp: now_millis(),
+        players: player_states
+            .iter()
+            .map(QuantizedPlayer::from_state)
+            .collect(),
+        ball: QuantizedBall::from_state(&ball_state),
+        discs: disc_states.iter().map(QuantizedDisc::from_state).collect(),
+        clock: clock_state,
+    });
 
-    // Serializar y broadcast a todos los jugadores READY
-    if let Ok(data) = bincode::serialize(&game_data_msg) {
-        // Enviar solo a jugadores que están ready
-        for player in players.iter() {
-            if player.is_ready {
+    // Un envío por peer (puede tener varios jugadores locales), solo a los READY
+    let ready_peers: std::collections::HashSet<PeerId> = players
+        .iter()
+        .filter(|player| player.is_ready)
+        .map(|player| player.peer_id)
+        .collect();
+
+    for peer_id in ready_peers {
+        if !snapshots.roster_sent.contains(&peer_id) {
+            let roster_msg = ControlMessage::PlayerRoster(snapshots.roster.clone());
+            if let Ok(data) = bincode::serialize(&roster_msg) {
                 let _ = network_tx.0.send(OutgoingMessage::ToOne {
-                    peer_id: player.peer_id,
-                    channel: 1, // Canal unreliable para GameState
-                    data: data.clone(),
+                    peer_id,
+                    channel: Channel::Reliable,
+                    data,
                 });
+                snapshots.roster_sent.insert(peer_id);
             }
         }
+
+        // Delta contra el último snapshot que confirmó este peer
+        let Some(delta) = snapshots.encoder.encode_for(&peer_id) else {
+            continue;
+        };
+        if let Ok(data) = bincode::serialize(&GameDataMessage::Snapshot(delta)) {
+            let _ = network_tx.0.send(OutgoingMessage::ToOne {
+                peer_id,
+                channel: Channel::Unreliable,
+                data,
+            });
+        }
     }
 }
 
@@ -720,6 +1318,28 @@ pub fn broadcast_game_state(
 // PROXY REGISTRATION
 // ============================================================================
 
+/// Control de acceso de la sala: contraseña opcional y visibilidad en la lista
+#[derive(Debug, Clone, Default)]
+pub struct RoomAccess {
+    /// Contraseña para entrar (None o vacía = sala abierta)
+    pub password: Option<String>,
+    /// Sala privada: no aparece en la lista, se entra solo por ID
+    pub private: bool,
+}
+
+/// Datos con los que el host registra la sala en el proxy
+pub struct RoomRegistration {
+    pub room: String,
+    pub server_host: String,
+    pub room_name: String,
+    pub max_players: u8,
+    pub map_name: Option<String>,
+    pub access: RoomAccess,
+    /// Token de toma que recibió del host anterior: toma su sala en vez de crear
+    /// una nueva (migración de host)
+    pub takeover_token: Option<String>,
+}
+
 #[derive(serde::Serialize)]
 struct CreateRoomRequest {
     room_id: String,
@@ -727,6 +1347,9 @@ struct CreateRoomRequest {
     max_players: u8,
     map_name: Option<String>,
     min_version: Option<String>,
+    password: Option<String>,
+    private: bool,
+    takeover_token: Option<String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -734,28 +1357,34 @@ struct CreateRoomResponse {
     token: String,
 }
 
+#[derive(serde::Deserialize)]
+struct TakeoverTokenResponse {
+    takeover_token: String,
+}
+
 async fn register_room_with_proxy(
     http_url: &str,
-    room_id: &str,
-    room_name: &str,
-    max_players: u8,
-    map_name: Option<&str>,
-    min_version: Option<&str>,
+    registration: &RoomRegistration,
+    min_version: &str,
 ) -> Result<String, String> {
     let client = reqwest::Client::new();
     let url = format!("{}/api/rooms", http_url);
 
+    let access = &registration.access;
     let request = CreateRoomRequest {
-        room_id: room_id.to_string(),
-        name: room_name.to_string(),
-        max_players,
-        map_name: map_name.map(|s| s.to_string()),
-        min_version: min_version.map(|s| s.to_string()),
+        room_id: registration.room.clone(),
+        name: registration.room_name.clone(),
+        max_players: registration.max_players,
+        map_name: registration.map_name.clone(),
+        min_version: Some(min_version.to_string()),
+        password: access.password.clone().filter(|p| !p.is_empty()),
+        private: access.private,
+        takeover_token: registration.takeover_token.clone(),
     };
 
     println!(
         "📡 Registering room '{}' with proxy at {}",
-        room_id, http_url
+        registration.room, http_url
     );
 
     let mut req = client.post(&url).json(&request);
@@ -779,3 +1408,30 @@ async fn register_room_with_proxy(
         Err(format!("Proxy returned error {}: {}", status, body))
     }
 }
+
+/// Pide al proxy un token de toma para el sucesor (invalida el anterior)
+async fn request_takeover_token(http_url: &str, room: &str, token: &str) -> Result<String, String> {
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/rooms/{}/takeover", http_url, room);
+
+    let mut req = client.post(&url).query(&[("token", token)]);
+    for (key, value) in hmac_auth::auth_headers() {
+        req = req.header(key, value);
+    }
+    let response = req
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+
+    if response.status().is_success() {
+        let body: TakeoverTokenResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        Ok(body.takeover_token)
+    } else {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        Err(format!("Proxy returned error {}: {}", status, body))
+    }
+}
@@ -0,0 +1,201 @@
+// ============================================================================
+// RECONEXIÓN - TOKENS Y PERÍODO DE GRACIA
+// ============================================================================
+
+use crate::shared::protocol::ControlMessage;
+use crate::transport::Channel;
+use bevy::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Instant;
+
+use super::chat::ChatRateLimiter;
+use super::host::{GameInputManager, HostMatchSlots, NetworkSender, OutgoingMessage, Player};
+
+/// Segundos que se guarda el lugar de un jugador cuya conexión se cortó
+pub const RECONNECT_GRACE_SECS: f64 = 30.0;
+
+struct Session {
+    player_id: u32,
+    /// Momento en que se cortó la conexión (None = conectado)
+    dropped_at: Option<Instant>,
+}
+
+/// Hash de un token de reconexión. El host guarda solo el hash (y es lo que
+/// replica a su sucesor): reconoce al cliente que vuelve sin poder hacerse pasar por él
+fn token_hash(token: u64) -> u64 {
+    let digest = Sha256::digest(token.to_le_bytes());
+    u64::from_le_bytes(digest[..8].try_into().expect("SHA-256 tiene 32 bytes"))
+}
+
+/// Tokens de reconexión entregados en los WELCOME (hash del token -> jugador)
+#[derive(Default)]
+pub struct ReconnectSessions {
+    sessions: HashMap<u64, Session>,
+}
+
+impl ReconnectSessions {
+    /// Genera el token de un jugador recién unido
+    pub fn issue(&mut self, player_id: u32) -> u64 {
+        loop {
+            let token = rand::random::<u64>();
+            let hash = token_hash(token);
+            if token != 0 && !self.sessions.contains_key(&hash) {
+                self.sessions.insert(
+                    hash,
+                    Session {
+                        player_id,
+                        dropped_at: None,
+                    },
+                );
+                return token;
+            }
+        }
+    }
+
+    /// Empieza el período de gracia de un jugador cuyo peer se desconectó
+    pub fn mark_dropped(&mut self, player_id: u32, now: Instant) {
+        for session in self.sessions.values_mut() {
+            if session.player_id == player_id && session.dropped_at.is_none() {
+                session.dropped_at = Some(now);
+            }
+        }
+    }
+
+    /// Devuelve el jugador del token si se desconectó hace menos del período de gracia.
+    /// Un token de un jugador todavía conectado no sirve (evita robar el lugar)
+    pub fn resume(&mut self, token: u64, now: Instant) -> Option<u32> {
+        self.sessions.retain(|_, session| {
+            session
+                .dropped_at
+                .is_none_or(|t| now.duration_since(t).as_secs_f64() < RECONNECT_GRACE_SECS)
+        });
+        let session = self.sessions.get_mut(&token_hash(token))?;
+        session.dropped_at.take()?;
+        Some(session.player_id)
+    }
+
+    /// Sesiones vigentes (hash del token, player_id), para replicarlas al sucesor
+    /// del host. Los tokens en sí nunca salen del host
+    pub fn export(&self) -> Vec<(u64, u32)> {
+        self.sessions
+            .iter()
+            .map(|(hash, session)| (*hash, session.player_id))
+            .collect()
+    }
+
+    /// Sesiones recibidas del host anterior: todas empiezan desconectadas, hasta que
+    /// cada cliente vuelve con su token
+    pub fn restore(entries: &[(u64, u32)], now: Instant) -> Self {
+        let sessions = entries
+            .iter()
+            .map(|&(hash, player_id)| {
+                let session = Session {
+                    player_id,
+                    dropped_at: Some(now),
+                };
+                (hash, session)
+            })
+            .collect();
+        Self { sessions }
+    }
+
+    /// Invalida el token de un jugador que salió voluntariamente
+    pub fn revoke(&mut self, player_id: u32) {
+        self.sessions
+            .retain(|_, session| session.player_id != player_id);
+    }
+}
+
+/// Quita a los jugadores desconectados que no volvieron dentro del período de gracia
+pub fn expire_disconnected_players(
+    mut commands: Commands,
+    time: Res<Time>,
+    players: Query<(&Player, Entity)>,
+    mut match_slots: ResMut<HostMatchSlots>,
+    mut game_input: ResMut<GameInputManager>,
+    mut chat_limiter: ResMut<ChatRateLimiter>,
+    network_tx: Res<NetworkSender>,
+) {
+    let now = time.elapsed_secs_f64();
+    let mut slots_changed = false;
+
+    for (player, entity) in players.iter() {
+        let Some(disconnected_at) = player.disconnected_at else {
+            continue;
+        };
+        if now - disconnected_at < RECONNECT_GRACE_SECS {
+            continue;
+        }
+
+        // Remove from slots
+        match_slots.0.remove_player(player.id);
+        match_slots.0.admins.remove(&player.id);
+        slots_changed = true;
+
+        // Notificar a todos los clientes que este jugador se fue
+        let disconnect_msg = ControlMessage::PlayerDisconnected {
+            player_id: player.id,
+        };
+        if let Ok(data) = bincode::serialize(&disconnect_msg) {
+            let _ = network_tx.0.send(OutgoingMessage::Broadcast {
+                channel: Channel::Reliable,
+                data,
+            });
+        }
+
+        // Despawnear tanto Player como Sphere
+        commands.entity(player.sphere).despawn();
+        commands.entity(entity).despawn();
+        game_input.remove_player(player.id);
+        chat_limiter.forget(player.id);
+        println!(
+            "❌ Jugador {} ({}) no volvió a tiempo y fue removido",
+            player.name, player.id
+        );
+    }
+
+    if slots_changed {
+        let slots_msg = ControlMessage::SlotsUpdated(match_slots.0.clone());
+        if let Ok(data) = bincode::serialize(&slots_msg) {
+            let _ = network_tx.0.send(OutgoingMessage::Broadcast {
+                channel: Channel::Reliable,
+                data,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exported_sessions_hide_tokens_but_resume_after_restore() {
+        let now = Instant::now();
+        let mut sessions = ReconnectSessions::default();
+        let token = sessions.issue(4);
+
+        let exported = sessions.export();
+        assert_eq!(exported.len(), 1);
+        assert_ne!(exported[0].0, token);
+        assert_eq!(exported[0].1, 4);
+
+        // El sucesor reconoce al cliente que vuelve con su token
+        let mut restored = ReconnectSessions::restore(&exported, now);
+        assert_eq!(restored.resume(exported[0].0, now), None);
+        assert_eq!(restored.resume(token, now), Some(4));
+    }
+
+    #[test]
+    fn revoked_or_connected_tokens_do_not_resume() {
+        let now = Instant::now();
+        let mut sessions = ReconnectSessions::default();
+        let token = sessions.issue(1);
+        assert_eq!(sessions.resume(token, now), None);
+
+        sessions.mark_dropped(1, now);
+        sessions.revoke(1);
+        assert_eq!(sessions.resume(token, now), None);
+    }
+}
@@ -389,12 +389,21 @@ pub fn save_keybindings(config: &KeyBindingsConfig) -> Result<(), String> {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub server: String,
+    /// Retardo con el que se dibujan las entidades remotas (ms). Más alto = más suave
+    /// ante jitter, pero más atrasado respecto del host
+    #[serde(default = "default_interpolation_delay_ms")]
+    pub interpolation_delay_ms: u32,
+}
+
+fn default_interpolation_delay_ms() -> u32 {
+    100
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
             server: "kinetic-ball.fly.dev".to_string(),
+            interpolation_delay_ms: default_interpolation_delay_ms(),
         }
     }
 }
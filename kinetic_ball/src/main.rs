@@ -14,15 +14,19 @@ mod color_utils;
 mod components;
 mod events;
 mod game;
+#[cfg(test)]
+mod harness;
 mod host;
 mod keybindings;
 mod local_players;
 mod networking;
 mod rendering;
+mod replay;
 mod resources;
 mod shared;
 mod spawning;
 mod states;
+mod transport;
 mod ui;
 
 // ============================================================================
@@ -34,15 +38,15 @@ use camera::{
     camera_follow_player_and_ball, camera_zoom_control, update_camera_viewports,
     update_split_compositor, update_split_screen_state,
 };
-use events::{SpawnBallEvent, SpawnPlayerEvent};
+use events::{SpawnBallEvent, SpawnDiscEvent, SpawnPlayerEvent};
 use game::{
-    animate_keys, cleanup_game, handle_multi_player_input, interpolate_entities, process_movements,
-    setup,
+    animate_keys, cleanup_game, handle_multi_player_input, interpolate_entities,
+    predict_local_players, process_movements, setup,
 };
 use keybindings::{
-    load_app_config, load_gamepad_bindings_map, load_keybindings, DetectedGamepadEvent,
-    GamepadBindingsMap, GamepadConfigUIState, GilrsWrapper, KeyBindingsConfig, RawGamepadInput,
-    SettingsUIState,
+    load_app_config, load_gamepad_bindings_map, load_keybindings, AppConfig,
+    DetectedGamepadEvent, GamepadBindingsMap, GamepadConfigUIState, GilrsWrapper,
+    KeyBindingsConfig, RawGamepadInput, SettingsUIState,
 };
 use local_players::{detect_gamepads, AvailableInputDevices, LocalPlayers, LocalPlayersUIState};
 use networking::{check_connection, process_network_messages, start_connection};
@@ -51,18 +55,23 @@ use rendering::{
     sync_minimap_dots, sync_minimap_names, update_charge_bar, update_dash_cooldown,
     update_mode_visuals, update_player_sprite,
 };
+use replay::{
+    advance_replay, instant_replay_idle, play_instant_replay, replay_camera, save_finished_replay,
+    start_replay, stop_replay, InstantReplay, ReplayPlayback, ReplayRecorder,
+};
 use resources::{
     AdminPanelState, ConnectionConfig, CreateRoomConfig, DynamicSplitState, GameTick, LoadedMap,
     MyPlayerId, NetworkChannels, PlayerColors, PreviousInput, RoomFetchChannel, RoomList,
     SelectedRoom, SplitScreenMaterial, SplitScreenTextures,
 };
-use shared::protocol::GameConfig;
-use spawning::{handle_spawn_ball, handle_spawn_player};
-use states::AppState;
+use shared::protocol::{GameConfig, MatchRules};
+use spawning::{handle_spawn_ball, handle_spawn_disc, handle_spawn_player};
+use states::{match_on_screen, AppState};
 use ui::{
-    admin_panel_ui, check_rooms_fetch, cleanup_menu_camera, create_room_ui, fetch_rooms,
-    gamepad_config_ui, hosting_ui, local_players_setup_ui, menu_ui, room_selection_ui, settings_ui,
-    setup_menu_camera_if_needed, start_hosting, toggle_admin_panel,
+    admin_panel_ui, chat_ui, check_rooms_fetch, cleanup_menu_camera, create_room_ui, fetch_rooms,
+    gamepad_config_ui, hosting_ui, instant_replay_ui, local_players_setup_ui, menu_ui, replay_ui,
+    room_selection_ui, scoreboard_ui, settings_ui, setup_menu_camera_if_needed, start_hosting,
+    toggle_admin_panel, toggle_chat,
 };
 
 // ============================================================================
@@ -84,6 +93,121 @@ pub struct Args {
     /// Nombre del jugador
     #[arg(long, default_value = "Player")]
     pub name: String,
+
+    /// Host de la red local (ip:puerto) para unirse sin proxy desde el menú
+    #[arg(long)]
+    pub lan: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Ejecuta solo el host de una sala, sin ventana (servidor dedicado)
+    Host(HostArgs),
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct HostArgs {
+    /// Ruta al mapa (.hbs/.json5). Si no se indica, se usa el mapa embebido
+    #[arg(long)]
+    pub map: Option<String>,
+
+    /// Escala del mapa (entre 0.5 y 2.0)
+    #[arg(long, default_value_t = 1.0)]
+    pub scale: f32,
+
+    /// Nombre visible de la sala
+    #[arg(long, default_value = "Sala dedicada")]
+    pub room_name: String,
+
+    /// ID de la sala (por defecto se genera uno)
+    #[arg(long)]
+    pub room_id: Option<String>,
+
+    /// Cantidad máxima de jugadores
+    #[arg(long, default_value_t = 16)]
+    pub max_players: u8,
+
+    /// Host del proxy (sin protocolo). Por defecto el de la configuración
+    #[arg(long)]
+    pub proxy: Option<String>,
+
+    /// Duración del partido en minutos (0 = sin límite)
+    #[arg(long, default_value_t = 3)]
+    pub time_limit_minutes: u32,
+
+    /// Goles para ganar (0 = sin límite)
+    #[arg(long, default_value_t = 3)]
+    pub score_limit: u32,
+
+    /// Contraseña para entrar a la sala
+    #[arg(long)]
+    pub password: Option<String>,
+
+    /// Sala privada: no aparece en la lista, se entra por ID
+    #[arg(long)]
+    pub private: bool,
+
+    /// Hostear en la red local por UDP en esta dirección (ej. 0.0.0.0:7777), sin proxy
+    #[arg(long)]
+    pub lan: Option<String>,
+}
+
+/// Ejecuta el host sin Bevy de ventana (bloquea hasta que el proceso termina)
+fn run_headless_host(host_args: HostArgs, app_config: &AppConfig) {
+    let rules = MatchRules {
+        time_limit_secs: host_args.time_limit_minutes * 60,
+        score_limit: host_args.score_limit,
+    };
+
+    // Red local: sin proxy, los clientes se conectan directo por UDP
+    if let Some(bind_addr) = host_args.lan {
+        println!("🖥️  Modo host dedicado en red local (sin ventana)");
+        host::host_lan(
+            host_args.map,
+            assets::DEFAULT_MAP,
+            host_args.scale,
+            bind_addr,
+            rules,
+        );
+        return;
+    }
+
+    let server_host = host_args
+        .proxy
+        .unwrap_or_else(|| app_config.server.clone());
+    let room_id = host_args.room_id.unwrap_or_else(|| {
+        format!(
+            "room_{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        )
+    });
+    let access = host::RoomAccess {
+        password: host_args.password,
+        private: host_args.private,
+    };
+
+    println!("🖥️  Modo host dedicado (sin ventana)");
+    println!("   Sala: {}", host_args.room_name);
+    println!("   Room ID: {}", room_id);
+    println!("   Max jugadores: {}", host_args.max_players);
+
+    host::host(
+        host_args.map,
+        assets::DEFAULT_MAP,
+        host_args.scale,
+        room_id,
+        server_host,
+        host_args.room_name,
+        host_args.max_players,
+        rules,
+        access,
+    );
 }
 
 // ============================================================================
@@ -99,6 +223,12 @@ fn main() {
 
     let args = Args::parse();
     let app_config = load_app_config();
+
+    if let Some(Command::Host(host_args)) = args.command.clone() {
+        run_headless_host(host_args, &app_config);
+        return;
+    }
+
     println!("🎮 Haxball Client - Iniciando...");
 
     // Bevy
@@ -159,9 +289,28 @@ fn main() {
         .insert_resource(AdminPanelState::default())
         // Client match slots (synced from server)
         .insert_resource(resources::ClientMatchSlots::default())
+        // Marcador del partido (sincronizado desde el servidor)
+        .insert_resource(resources::ClientMatchScore::default())
+        // Chat de la sala
+        .insert_resource(resources::ChatState::default())
+        // Predicción de los jugadores locales
+        .insert_resource(resources::ClientPrediction::default())
+        // Latencia de los jugadores (medida por el host)
+        .insert_resource(resources::PlayerLatencies::default())
+        // Grabación del partido en curso (se guarda al terminar)
+        .insert_resource(ReplayRecorder::default())
+        // Últimos segundos del partido para la repetición instantánea
+        .insert_resource(InstantReplay::default())
+        // Replay abierto desde el menú
+        .insert_resource(ReplayPlayback::default())
+        // Retardo de interpolación de las entidades remotas (config.ron)
+        .insert_resource(resources::SnapshotClock::new(
+            app_config.interpolation_delay_ms,
+        ))
         // Eventos de spawning
-        .add_event::<SpawnBallEvent>()
-        .add_event::<SpawnPlayerEvent>()
+        .add_message::<SpawnBallEvent>()
+        .add_message::<SpawnDiscEvent>()
+        .add_message::<SpawnPlayerEvent>()
         // Cargar assets embebidos al inicio (antes de todo)
         .add_systems(Startup, load_embedded_assets)
         // Sistemas de input y detección
@@ -237,18 +386,49 @@ fn main() {
         .add_systems(OnEnter(AppState::InGame), setup)
         // Cleanup al salir de InGame
         .add_systems(OnExit(AppState::InGame), cleanup_game)
-        // Lógica de red y entrada (frecuencia fija, solo en InGame)
+        // Replay: el mismo juego, alimentado por el archivo en vez de la red
+        .add_systems(
+            OnEnter(AppState::Replay),
+            (cleanup_menu_camera, start_replay, setup).chain(),
+        )
+        .add_systems(OnExit(AppState::Replay), (cleanup_game, stop_replay))
+        // Lógica de red y entrada (frecuencia fija; en un replay no hay input local)
         .add_systems(
             FixedUpdate,
-            (handle_multi_player_input, process_network_messages)
-                .run_if(in_state(AppState::InGame)),
+            (
+                handle_multi_player_input.run_if(in_state(AppState::InGame)),
+                process_network_messages,
+                predict_local_players.run_if(in_state(AppState::InGame)),
+            )
+                .chain()
+                .run_if(match_on_screen),
         )
         // Sistemas de spawning (procesan eventos emitidos por network)
         .add_systems(
             Update,
-            (handle_spawn_ball, handle_spawn_player).run_if(in_state(AppState::InGame)),
+            (handle_spawn_ball, handle_spawn_disc, handle_spawn_player).run_if(match_on_screen),
+        )
+        // Cámaras: siguen a los jugadores locales en vivo, o lo elegido en el replay
+        .add_systems(
+            Update,
+            camera_follow_player_and_ball
+                .run_if(in_state(AppState::InGame))
+                .run_if(instant_replay_idle),
         )
-        // Lógica visual y renderizado (solo en InGame)
+        // Repetición instantánea: pisa lo que dejó la interpolación en vivo
+        .add_systems(
+            Update,
+            play_instant_replay
+                .after(interpolate_entities)
+                .after(camera_zoom_control)
+                .run_if(in_state(AppState::InGame)),
+        )
+        .add_systems(
+            Update,
+            (advance_replay.before(interpolate_entities), replay_camera)
+                .run_if(in_state(AppState::Replay)),
+        )
+        // Lógica visual y renderizado (en vivo o en un replay)
         .add_systems(
             Update,
             (
@@ -256,7 +436,6 @@ fn main() {
                 interpolate_entities,
                 keep_name_horizontal,
                 update_split_screen_state,
-                camera_follow_player_and_ball,
                 camera_zoom_control,
                 update_camera_viewports,
                 update_split_compositor,
@@ -271,14 +450,25 @@ fn main() {
                 cleanup_minimap_dots,
                 animate_keys,
             )
-                .run_if(in_state(AppState::InGame)),
+                .run_if(match_on_screen),
         )
         // Panel de administración - sin run_if para debug
         .add_systems(
             Update,
-            toggle_admin_panel.run_if(in_state(AppState::InGame)),
+            (toggle_admin_panel, toggle_chat).run_if(in_state(AppState::InGame)),
         )
         .add_systems(EguiPrimaryContextPass, admin_panel_ui)
+        // Guardar replays aunque ya se haya salido de la sala
+        .add_systems(Update, save_finished_replay)
+        .add_systems(
+            EguiPrimaryContextPass,
+            (
+                scoreboard_ui.run_if(match_on_screen),
+                chat_ui.run_if(in_state(AppState::InGame)),
+                instant_replay_ui.run_if(in_state(AppState::InGame)),
+                replay_ui.run_if(in_state(AppState::Replay)),
+            ),
+        )
         .run();
 
     println!("✅ [Bevy] App::run() ha finalizado normalmente");
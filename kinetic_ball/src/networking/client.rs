@@ -1,19 +1,26 @@
 use bevy::prelude::*;
-use matchbox_socket::WebRtcSocket;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 
+use crate::assets::DEFAULT_MAP;
+use crate::host;
 use crate::local_players::LocalPlayers;
-use crate::resources::{ConnectionConfig, NetworkChannels};
+use crate::networking::hmac_auth;
+use crate::resources::{ConnectionConfig, CreateRoomConfig, NetworkChannels};
+use crate::shared::handoff::{HostHandoff, HANDOFF_STALE_SECS};
+use crate::shared::map::Map;
 use crate::shared::protocol::{
     ControlMessage, GameDataMessage, PlayerInput, ProtocolVersion, ServerMessage,
 };
-use crate::states::AppState;
+use crate::shared::snapshot::{PlayerInfo, SnapshotDecoder, SnapshotDelta};
+use crate::states::{AppState, RoomInfo};
+use crate::transport::{Channel, PeerEvent, PeerId, Transport, UdpTransport, WebRtcTransport};
 
 pub fn start_connection(
     config: Res<ConnectionConfig>,
     mut channels: ResMut<NetworkChannels>,
     local_players: Res<LocalPlayers>,
+    create_config: Res<CreateRoomConfig>,
 ) {
     let (network_tx, network_rx) = mpsc::channel();
     let (input_tx, input_rx) = mpsc::channel();
@@ -24,8 +31,15 @@ pub fn start_connection(
     channels.sender = Some(input_tx);
     channels.control_sender = Some(control_tx);
 
-    let ws_url = config.ws_url();
-    let room = config.room.clone();
+    let proxy_room = ProxyRoom {
+        server_url: config.ws_url(),
+        http_url: config.http_url(),
+        server_host: config.server_host.clone(),
+        room: config.room.clone(),
+        room_password: config.room_password.clone(),
+        // El que creó la sala ya tiene su host en este proceso: no se ofrece como sucesor
+        can_host: !create_config.created_room_ids.contains(&config.room),
+    };
 
     // Recoger los nombres de los jugadores locales
     // Si no hay jugadores locales configurados, usar el nombre del config (modo legacy)
@@ -44,6 +58,42 @@ pub fn start_connection(
         player_names.len()
     );
 
+    // Práctica sin conexión: el host corre en este proceso
+    if config.offline {
+        let transport = host::practice(
+            create_config.map(),
+            DEFAULT_MAP,
+            create_config.scale,
+            create_config.rules(),
+        );
+        std::thread::spawn(move || {
+            run_direct_client(transport, player_names, network_tx, input_rx, control_rx);
+            println!("🌐 [Red] El hilo de red HA TERMINADO");
+        });
+        return;
+    }
+
+    // Red local: UDP directo al host, sin proxy
+    if config.lan {
+        let lan_host = config.lan_host.clone();
+        std::thread::spawn(move || {
+            println!("🌐 [Red] Conectando por red local a {}", lan_host);
+            match UdpTransport::connect(lan_host.as_str()) {
+                Ok(transport) => {
+                    run_direct_client(transport, player_names, network_tx, input_rx, control_rx)
+                }
+                Err(e) => {
+                    println!("❌ [Red] No se pudo conectar a {}: {}", lan_host, e);
+                    let _ = network_tx.send(ServerMessage::Error {
+                        message: format!("No se pudo conectar a {}: {}", lan_host, e),
+                    });
+                }
+            }
+            println!("🌐 [Red] El hilo de red HA TERMINADO");
+        });
+        return;
+    }
+
     // Iniciar hilo de red
     std::thread::spawn(move || {
         println!("🌐 [Red] Iniciando cliente WebRTC");
@@ -53,7 +103,7 @@ pub fn start_connection(
             .expect("Fallo al crear Runtime de Tokio");
 
         rt.block_on(async {
-            start_webrtc_client(ws_url, room, player_names, network_tx, input_rx, control_rx).await;
+            start_webrtc_client(proxy_room, player_names, network_tx, input_rx, control_rx).await;
         });
         println!("🌐 [Red] El hilo de red HA TERMINADO");
     });
@@ -72,56 +122,366 @@ pub fn check_connection(channels: Res<NetworkChannels>, mut next_state: ResMut<N
     }
 }
 
+/// URL de la sala en el proxy; la contraseña va como query param (`?password=`)
+fn room_url(server_url: &str, room: &str, password: Option<&str>) -> String {
+    let base = format!("{}/{}", server_url, room);
+    let Some(password) = password.filter(|p| !p.is_empty()) else {
+        return base;
+    };
+    match reqwest::Url::parse(&base) {
+        Ok(mut url) => {
+            url.query_pairs_mut().append_pair("password", password);
+            url.to_string()
+        }
+        Err(_) => base,
+    }
+}
+
+/// Lo que informa el proxy sobre la sala antes de conectar
+enum RoomLookup {
+    /// Peer del host (None si el proxy todavía no lo sabe)
+    Host(Option<String>),
+    /// La sala pide contraseña y la ingresada no es la correcta
+    WrongPassword,
+}
+
+/// Pregunta al proxy qué peer es el host de la sala y verifica la contraseña
+/// (el WebSocket la rechazaría con un 401 que matchbox no distingue de un corte)
+async fn fetch_room(http_url: &str, room: &str, password: Option<&str>) -> RoomLookup {
+    let url = format!("{}/api/rooms/{}", http_url, room);
+    let password = password.filter(|p| !p.is_empty());
+    let mut req = reqwest::Client::new()
+        .get(&url)
+        .header("ngrok-skip-browser-warning", "true");
+    if let Some(password) = password {
+        req = req.query(&[("password", password)]);
+    }
+    for (key, value) in hmac_auth::auth_headers() {
+        req = req.header(key, value);
+    }
+    match req.send().await {
+        Ok(response) if response.status() == reqwest::StatusCode::FORBIDDEN => {
+            RoomLookup::WrongPassword
+        }
+        Ok(response) if response.status().is_success() => match response.json::<RoomInfo>().await {
+            Ok(info) if info.has_password && password.is_none() => RoomLookup::WrongPassword,
+            Ok(info) => RoomLookup::Host(info.host_peer_id),
+            Err(_) => RoomLookup::Host(None),
+        },
+        Ok(response) => {
+            println!(
+                "⚠️ [Red] No se pudo consultar la sala: {}",
+                response.status()
+            );
+            RoomLookup::Host(None)
+        }
+        Err(e) => {
+            println!("⚠️ [Red] No se pudo consultar la sala: {}", e);
+            RoomLookup::Host(None)
+        }
+    }
+}
+
+/// Quién es el host antes de recibir el primer WELCOME
+#[derive(Clone, Copy)]
+enum HostPeer<'a> {
+    /// El peer que el proxy anunció como host de la sala
+    Announced(&'a str),
+    /// Conexión directa (red local, práctica): el único peer es el host
+    Direct,
+}
+
+/// Solo se habla con el host de la sala: una vez recibido el WELCOME, el peer que lo
+/// mandó; antes, el que anunció el proxy (o el único peer en una conexión directa)
+fn is_host_peer(peer_id: PeerId, server_peer_id: Option<PeerId>, host: HostPeer) -> bool {
+    match (server_peer_id, host) {
+        (Some(server_id), _) => peer_id == server_id,
+        (None, HostPeer::Announced(host)) => peer_id.0.to_string() == host,
+        (None, HostPeer::Direct) => true,
+    }
+}
+
+/// Reintentos de conexión antes de rendirse (cubren el período de gracia del host)
+const RECONNECT_ATTEMPTS: u32 = 6;
+
+/// Si el host no responde con WELCOME en este tiempo, el intento se da por fallido
+const CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Lo que se conserva entre conexiones con el host
+#[derive(Default)]
+struct SessionMemory {
+    /// Tokens de reconexión de cada jugador local, en el orden de los WELCOME
+    reconnect_tokens: Vec<u64>,
+    /// Mapa de la sala (del primer WELCOME), para levantar un host si hace falta
+    map: Option<Map>,
+    /// Último estado que replicó el host a este cliente (si es su sucesor) y cuándo llegó
+    handoff: Option<(HostHandoff, std::time::Instant)>,
+    /// Este cliente ya levantó un host para la sala
+    hosting: bool,
+}
+
+impl SessionMemory {
+    /// Si este cliente es el sucesor del host que se fue, levanta un host con el
+    /// último estado replicado; el resto de los clientes vuelve a él con sus tokens
+    fn take_over(&mut self, server_host: &str) {
+        let Some((handoff, received_at)) = self.handoff.take() else {
+            return;
+        };
+        if self.hosting || received_at.elapsed().as_secs_f64() > HANDOFF_STALE_SECS {
+            return;
+        }
+        println!("🧭 [Red] El host se fue: tomando la sala como sucesor");
+        self.hosting = true;
+        let map = self.map.clone();
+        let server_host = server_host.to_string();
+        std::thread::spawn(move || {
+            crate::host::resume_host(handoff, map, server_host);
+        });
+    }
+}
+
+/// Canales con Bevy que usa cada conexión con el host
+#[derive(Clone, Copy)]
+struct SessionChannels<'a> {
+    network_tx: &'a mpsc::Sender<ServerMessage>,
+    input_rx: &'a mpsc::Receiver<(u32, u32, PlayerInput)>,
+    control_rx: &'a mpsc::Receiver<ControlMessage>,
+}
+
+/// Cómo terminó una conexión con el host
+enum SessionEnd {
+    /// El jugador salió de la sala
+    Left,
+    /// Un admin expulsó a todos los jugadores locales
+    Kicked,
+    /// Se cortó la conexión o no se pudo establecer (`welcomed` = se llegó a unir)
+    Lost { welcomed: bool },
+}
+
+/// Sala del proxy a la que se conecta el cliente
+pub struct ProxyRoom {
+    /// URL WebSocket del proxy (señalización)
+    pub server_url: String,
+    /// URL HTTP del proxy (API de salas)
+    pub http_url: String,
+    /// Host del proxy, para registrar la sala si este cliente pasa a ser el host
+    pub server_host: String,
+    pub room: String,
+    pub room_password: Option<String>,
+    /// Puede ofrecerse como sucesor del host
+    pub can_host: bool,
+}
+
 pub async fn start_webrtc_client(
-    server_url: String,
-    room: String,
+    proxy_room: ProxyRoom,
     player_names: Vec<String>,
     network_tx: mpsc::Sender<ServerMessage>,
-    input_rx: mpsc::Receiver<(u32, PlayerInput)>,
+    input_rx: mpsc::Receiver<(u32, u32, PlayerInput)>,
     control_rx: mpsc::Receiver<ControlMessage>,
 ) {
-    // Conectar al proxy
-    let room_url = format!("{}/{}", server_url, room);
-    println!("🔌 [Red] Conectando a {}", room_url);
+    let ProxyRoom {
+        server_url,
+        http_url,
+        server_host,
+        room,
+        room_password,
+        can_host,
+    } = proxy_room;
+    let room_url = room_url(&server_url, &room, room_password.as_deref());
 
-    // Crear WebRtcSocket y conectar a la room
-    let (mut socket, loop_fut) = WebRtcSocket::builder(room_url)
-        .add_channel(matchbox_socket::ChannelConfig::reliable()) // Canal 0: Control
-        .add_channel(matchbox_socket::ChannelConfig::unreliable()) // Canal 1: GameData
-        .build();
+    let mut memory = SessionMemory::default();
+    let mut attempt: u32 = 0;
 
-    // Spawn el loop de matchbox
-    tokio::spawn(loop_fut);
+    loop {
+        println!("🔌 [Red] Conectando a {}/{}", server_url, room);
 
-    println!(
-        "✅ [Red] WebRTC socket creado, esperando conexión con peers... ({} jugadores locales)",
-        player_names.len()
-    );
+        // Averiguar el peer del host antes de conectar, para no hablar con otros clientes
+        let announced_host = match fetch_room(&http_url, &room, room_password.as_deref()).await {
+            RoomLookup::Host(host) => host,
+            RoomLookup::WrongPassword => {
+                // Reintentar no sirve: el jugador tiene que volver a entrar
+                println!("❌ [Red] Contraseña incorrecta para la sala {}", room);
+                let _ = network_tx.send(ServerMessage::Error {
+                    message: "Contraseña incorrecta".to_string(),
+                });
+                return;
+            }
+        };
+        // Sin host conocido (no se conectó todavía o la sala está migrando) no se sabe
+        // con quién hablar: se reintenta como una conexión fallida
+        let end = match &announced_host {
+            Some(host) => {
+                println!("🎯 [Red] Host de la sala: peer {}", host);
+                let mut transport = WebRtcTransport::connect(&room_url);
+                println!(
+                    "✅ [Red] WebRTC socket creado, esperando conexión con peers... ({} jugadores locales)",
+                    player_names.len()
+                );
+                let end = run_session(
+                    &mut transport,
+                    HostPeer::Announced(host),
+                    &player_names,
+                    can_host,
+                    &mut memory,
+                    SessionChannels {
+                        network_tx: &network_tx,
+                        input_rx: &input_rx,
+                        control_rx: &control_rx,
+                    },
+                )
+                .await;
+                // Cerrar el socket antes de esperar para reintentar
+                drop(transport);
+                end
+            }
+            None => {
+                println!("⚠️ [Red] El proxy no informó el host de la sala");
+                SessionEnd::Lost { welcomed: false }
+            }
+        };
+
+        let SessionEnd::Lost { welcomed } = end else {
+            return;
+        };
+        if welcomed {
+            memory.take_over(&server_host);
+        }
+        attempt = if welcomed { 1 } else { attempt + 1 };
+        if attempt > RECONNECT_ATTEMPTS {
+            println!("❌ [Red] No se pudo reconectar con el host");
+            let _ = network_tx.send(ServerMessage::Error {
+                message: "Se perdió la conexión con el host".to_string(),
+            });
+            return;
+        }
+
+        // Si Bevy ya no escucha (salió del juego) no tiene sentido reintentar
+        if network_tx
+            .send(ServerMessage::Reconnecting { attempt })
+            .is_err()
+        {
+            return;
+        }
+        let delay = std::time::Duration::from_secs(1 << (attempt - 1).min(3));
+        println!(
+            "🔄 [Red] Conexión perdida, reintento {}/{} en {}s",
+            attempt,
+            RECONNECT_ATTEMPTS,
+            delay.as_secs()
+        );
+        tokio::time::sleep(delay).await;
+
+        // Un LEAVE pedido durante la espera termina el hilo
+        if control_rx
+            .try_iter()
+            .any(|msg| matches!(msg, ControlMessage::Leave { .. }))
+        {
+            return;
+        }
+    }
+}
+
+/// Red local o práctica sin conexión: una única conexión directa con el host, sin
+/// proxy (no hay reconexión ni migración)
+pub fn run_direct_client(
+    mut transport: impl Transport,
+    player_names: Vec<String>,
+    network_tx: mpsc::Sender<ServerMessage>,
+    input_rx: mpsc::Receiver<(u32, u32, PlayerInput)>,
+    control_rx: mpsc::Receiver<ControlMessage>,
+) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Fallo al crear Runtime de Tokio");
+
+    let mut memory = SessionMemory::default();
+    let end = rt.block_on(run_session(
+        &mut transport,
+        HostPeer::Direct,
+        &player_names,
+        false,
+        &mut memory,
+        SessionChannels {
+            network_tx: &network_tx,
+            input_rx: &input_rx,
+            control_rx: &control_rx,
+        },
+    ));
+    if let SessionEnd::Lost { .. } = end {
+        println!("❌ [Red] Se perdió la conexión con el host");
+        let _ = network_tx.send(ServerMessage::Error {
+            message: "Se perdió la conexión con el host".to_string(),
+        });
+    }
+}
+
+/// Una conexión con el host: JOIN de los jugadores locales (con sus tokens de
+/// reconexión, si ya los tienen) y loop de mensajes hasta que se corta o el jugador sale.
+/// `host` dice con qué peer hablar hasta el primer WELCOME
+async fn run_session(
+    transport: &mut impl Transport,
+    host: HostPeer<'_>,
+    player_names: &[String],
+    can_host: bool,
+    memory: &mut SessionMemory,
+    channels: SessionChannels<'_>,
+) -> SessionEnd {
+    let SessionChannels {
+        network_tx,
+        input_rx,
+        control_rx,
+    } = channels;
+    let started = std::time::Instant::now();
 
     // El server_peer_id real se determina cuando recibimos WELCOME
-    let mut server_peer_id: Option<matchbox_socket::PeerId> = None;
+    let mut server_peer_id: Option<PeerId> = None;
 
-    // Rastrear a qué peers ya enviamos JOINs
-    let mut peers_joined: std::collections::HashSet<matchbox_socket::PeerId> =
-        std::collections::HashSet::new();
+    // Peers ya vistos (al host se le enviaron los JOINs, el resto se ignora)
+    let mut peers_seen: std::collections::HashSet<PeerId> = std::collections::HashSet::new();
 
     // Contador de WELCOMEs recibidos para asociar con local_index
     let mut welcomes_received: usize = 0;
 
+    // Jugadores locales que siguen en la sala (los expulsados se quitan)
+    let mut joined_players: Vec<u32> = Vec::new();
+
+    // Snapshots decodificados (baselines de los deltas) y nombres/equipos de los jugadores
+    let mut snapshot_decoder = SnapshotDecoder::default();
+    let mut roster: std::collections::HashMap<u32, PlayerInfo> = std::collections::HashMap::new();
+
     // Loop principal: recibir mensajes y enviar inputs
     loop {
-        // Procesar nuevos peers y enviar JOINs para todos los jugadores locales
-        socket.update_peers();
-        let current_peers: Vec<_> = socket.connected_peers().collect();
+        // Detectar la caída del host (o de la conexión con el proxy)
+        let host_left = transport
+            .update_peers()
+            .into_iter()
+            .any(|event| server_peer_id.is_some_and(|id| event == PeerEvent::Disconnected(id)));
+        let timed_out = server_peer_id.is_none() && started.elapsed() >= CONNECT_TIMEOUT;
+        if host_left || timed_out || transport.is_closed() {
+            println!("🔌 [Red] Conexión con el host perdida");
+            return SessionEnd::Lost {
+                welcomed: server_peer_id.is_some(),
+            };
+        }
 
-        for peer_id in current_peers {
-            if !peers_joined.contains(&peer_id) {
+        // Procesar nuevos peers y enviar JOINs para todos los jugadores locales al host
+        for peer_id in transport.connected_peers() {
+            if peers_seen.insert(peer_id) {
+                if !is_host_peer(peer_id, server_peer_id, host) {
+                    println!(
+                        "🚫 [Red] Ignorando peer {:?}: no es el host de la sala",
+                        peer_id
+                    );
+                    continue;
+                }
                 // Nuevo peer, enviar JOIN para cada jugador local
                 for (idx, name) in player_names.iter().enumerate() {
                     let client_version = ProtocolVersion::current();
                     let join_msg = ControlMessage::Join {
                         player_name: name.clone(),
                         client_version: Some(client_version),
+                        reconnect_token: memory.reconnect_tokens.get(idx).copied(),
                     };
                     if let Ok(data) = bincode::serialize(&join_msg) {
                         println!(
@@ -131,19 +491,25 @@ pub async fn start_webrtc_client(
                             client_version,
                             peer_id
                         );
-                        socket.channel_mut(0).send(data.into(), peer_id);
+                        transport.send(Channel::Reliable, peer_id, data);
                     }
                 }
-                peers_joined.insert(peer_id);
             }
         }
 
         // Recibir mensajes del servidor
-        // Canal 0: Control messages (reliable)
-        for (peer_id, packet) in socket.channel_mut(0).receive() {
+        // Control messages (reliable)
+        for (peer_id, packet) in transport.receive(Channel::Reliable) {
+            if !is_host_peer(peer_id, server_peer_id, host) {
+                continue;
+            }
             if let Ok(msg) = bincode::deserialize::<ControlMessage>(&packet) {
                 match msg {
-                    ControlMessage::Welcome { player_id, map } => {
+                    ControlMessage::Welcome {
+                        player_id,
+                        map,
+                        reconnect_token,
+                    } => {
                         println!(
                             "🎉 [Red] WELCOME #{} recibido de peer {:?}! Player ID: {}",
                             welcomes_received + 1,
@@ -151,9 +517,23 @@ pub async fn start_webrtc_client(
                             player_id
                         );
 
-                        // Guardar el peer_id del servidor real (del primer WELCOME)
+                        // Guardar el peer_id del host (del primer WELCOME)
                         if server_peer_id.is_none() {
                             server_peer_id = Some(peer_id);
+                            memory.map = map.clone();
+
+                            // Ofrecerse como sucesor por si el host se va
+                            if can_host && !memory.hosting {
+                                if let Ok(data) = bincode::serialize(&ControlMessage::CanHost) {
+                                    transport.send(Channel::Reliable, peer_id, data);
+                                }
+                            }
+                        }
+
+                        // Token para recuperar a este jugador local si se corta la conexión
+                        match memory.reconnect_tokens.get_mut(welcomes_received) {
+                            Some(token) => *token = reconnect_token,
+                            None => memory.reconnect_tokens.push(reconnect_token),
                         }
 
                         // Convertir a ServerMessage para compatibilidad con el código existente
@@ -167,70 +547,63 @@ pub async fn start_webrtc_client(
                                 "📤 [Red -> Servidor] Enviando READY para jugador {}...",
                                 player_id
                             );
-                            socket.channel_mut(0).send(data.into(), peer_id);
+                            transport.send(Channel::Reliable, peer_id, data);
                         }
 
                         welcomes_received += 1;
+                        joined_players.push(player_id);
                     }
-                    ControlMessage::PlayerDisconnected { player_id } => {
-                        println!("👋 [Red] Jugador {} se desconectó", player_id);
-                        let _ = network_tx.send(ServerMessage::PlayerDisconnected { player_id });
-                    }
-                    ControlMessage::VersionMismatch {
-                        client_version,
-                        min_required,
-                        message,
-                    } => {
-                        println!(
-                            "❌ [Red] VERSION INCOMPATIBLE: Tu versión {} es menor que la mínima requerida {}",
-                            client_version, min_required
-                        );
-                        println!("   {}", message);
+                    ControlMessage::Kicked { player_id } => {
+                        println!("👢 [Red] Jugador {} expulsado de la sala", player_id);
                         let _ = network_tx.send(ServerMessage::Error {
-                            message: format!(
-                                "Versión incompatible: tienes v{}, se requiere v{} o superior. {}",
-                                client_version, min_required, message
-                            ),
+                            message: "Fuiste expulsado de la sala".to_string(),
                         });
+                        // Sin jugadores en la sala se cierra la conexión, sin reintentar
+                        joined_players.retain(|id| *id != player_id);
+                        if joined_players.is_empty() {
+                            println!("🚪 [Red] Cerrando conexión...");
+                            return SessionEnd::Kicked;
+                        }
                     }
-                    ControlMessage::Error { message } => {
-                        println!("❌ [Red] Error del servidor: {}", message);
-                        let _ = network_tx.send(ServerMessage::Error { message });
-                    }
-                    ControlMessage::SlotsUpdated(slots) => {
-                        println!(
-                            "📊 [Red] SlotsUpdated recibido - Admins: {:?}, Starters T0: {}, T1: {}",
-                            slots.admins,
-                            slots.teams[0].starters.len(),
-                            slots.teams[1].starters.len()
-                        );
-                        // Enviar como un mensaje especial que el cliente puede procesar
-                        // Usamos un canal interno para esto
-                        let _ = network_tx.send(ServerMessage::SlotsUpdated(slots));
+                    ControlMessage::HostHandoff(handoff) => {
+                        // Este cliente es el sucesor elegido por el host
+                        memory.handoff = Some((*handoff, std::time::Instant::now()));
                     }
-                    _ => {}
+                    other => forward_control(other, &mut roster, network_tx),
                 }
             }
         }
 
-        // Canal 1: GameData messages (unreliable)
-        for (_peer_id, packet) in socket.channel_mut(1).receive() {
+        // GameData messages (unreliable)
+        let mut snapshot_ack: Option<(PeerId, u32)> = None;
+        for (peer_id, packet) in transport.receive(Channel::Unreliable) {
+            if !is_host_peer(peer_id, server_peer_id, host) {
+                continue;
+            }
             if let Ok(msg) = bincode::deserialize::<GameDataMessage>(&packet) {
                 match msg {
-                    GameDataMessage::GameState {
-                        tick,
-                        timestamp,
-                        players,
-                        ball,
-                    } => {
-                        // Convertir a ServerMessage
-                        let server_msg = ServerMessage::GameState {
-                            tick,
-                            timestamp,
-                            players,
-                            ball,
+                    GameDataMessage::Snapshot(delta) => {
+                        let Some(tick) =
+                            forward_snapshot(delta, &mut snapshot_decoder, &roster, network_tx)
+                        else {
+                            continue;
                         };
-                        let _ = network_tx.send(server_msg);
+                        if snapshot_ack.is_none_or(|(_, acked)| tick > acked) {
+                            snapshot_ack = Some((peer_id, tick));
+                        }
+                    }
+                    GameDataMessage::Ping { timestamp } => {
+                        // El host mide nuestra latencia: responder enseguida
+                        let pong = GameDataMessage::Pong {
+                            client_timestamp: timestamp,
+                            server_timestamp: std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .unwrap()
+                                .as_millis() as u64,
+                        };
+                        if let Ok(data) = bincode::serialize(&pong) {
+                            transport.send(Channel::Unreliable, peer_id, data);
+                        }
                     }
                     GameDataMessage::Pong {
                         client_timestamp,
@@ -247,12 +620,23 @@ pub async fn start_webrtc_client(
             }
         }
 
+        // Confirmar el snapshot más nuevo decodificado (baseline del próximo delta)
+        if let Some((peer_id, tick)) = snapshot_ack {
+            if let Ok(data) = bincode::serialize(&GameDataMessage::SnapshotAck { tick }) {
+                transport.send(Channel::Unreliable, peer_id, data);
+            }
+        }
+
         // Enviar inputs desde Bevy (solo si ya identificamos al servidor)
         if let Some(server_id) = server_peer_id {
-            while let Ok((player_id, input)) = input_rx.try_recv() {
-                let input_msg = GameDataMessage::Input { player_id, input };
+            while let Ok((player_id, sequence, input)) = input_rx.try_recv() {
+                let input_msg = GameDataMessage::Input {
+                    player_id,
+                    sequence,
+                    input,
+                };
                 if let Ok(data) = bincode::serialize(&input_msg) {
-                    socket.channel_mut(1).send(data.into(), server_id); // Canal 1 = unreliable
+                    transport.send(Channel::Unreliable, server_id, data);
                 }
             }
         } else {
@@ -267,29 +651,58 @@ pub async fn start_webrtc_client(
                     ControlMessage::Leave { player_id } => {
                         println!("📤 [Red] Enviando LEAVE para jugador {}...", player_id);
                         if let Ok(data) = bincode::serialize(&control_msg) {
-                            socket.channel_mut(0).send(data.into(), server_id);
+                            transport.send(Channel::Reliable, server_id, data);
                         }
                         // Dar tiempo para que el mensaje se envíe
                         tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
                         println!("🚪 [Red] Cerrando conexión...");
-                        return; // Terminar el loop de red
+                        return SessionEnd::Left; // Terminar el loop de red
                     }
                     ControlMessage::MovePlayer { player_id, team_index, is_starter } => {
                         println!("📤 [Red] Enviando MovePlayer: {} -> team {:?}, starter {:?}", player_id, team_index, is_starter);
                         if let Ok(data) = bincode::serialize(&control_msg) {
-                            socket.channel_mut(0).send(data.into(), server_id);
+                            transport.send(Channel::Reliable, server_id, data);
                         }
                     }
                     ControlMessage::KickPlayer { player_id } => {
                         println!("📤 [Red] Enviando KickPlayer: {}", player_id);
                         if let Ok(data) = bincode::serialize(&control_msg) {
-                            socket.channel_mut(0).send(data.into(), server_id);
+                            transport.send(Channel::Reliable, server_id, data);
                         }
                     }
                     ControlMessage::ToggleAdmin { player_id, is_admin } => {
                         println!("📤 [Red] Enviando ToggleAdmin: {} -> {}", player_id, is_admin);
                         if let Ok(data) = bincode::serialize(&control_msg) {
-                            socket.channel_mut(0).send(data.into(), server_id);
+                            transport.send(Channel::Reliable, server_id, data);
+                        }
+                    }
+                    ControlMessage::SendChat { .. } => {
+                        if let Ok(data) = bincode::serialize(&control_msg) {
+                            transport.send(Channel::Reliable, server_id, data);
+                        }
+                    }
+                    ControlMessage::RequestReplay => {
+                        println!("📤 [Red] Enviando RequestReplay");
+                        if let Ok(data) = bincode::serialize(&control_msg) {
+                            transport.send(Channel::Reliable, server_id, data);
+                        }
+                    }
+                    ControlMessage::AddBot {
+                        team_index,
+                        difficulty,
+                    } => {
+                        println!(
+                            "📤 [Red] Enviando AddBot: team {} ({:?})",
+                            team_index, difficulty
+                        );
+                        if let Ok(data) = bincode::serialize(&control_msg) {
+                            transport.send(Channel::Reliable, server_id, data);
+                        }
+                    }
+                    ControlMessage::RemoveBot { player_id } => {
+                        println!("📤 [Red] Enviando RemoveBot: {}", player_id);
+                        if let Ok(data) = bincode::serialize(&control_msg) {
+                            transport.send(Channel::Reliable, server_id, data);
                         }
                     }
                     _ => {}
@@ -301,3 +714,103 @@ pub async fn start_webrtc_client(
         tokio::time::sleep(tokio::time::Duration::from_millis(1)).await;
     }
 }
+
+/// Pasa a Bevy un mensaje de control del host que no necesita respuesta
+fn forward_control(
+    msg: ControlMessage,
+    roster: &mut std::collections::HashMap<u32, PlayerInfo>,
+    network_tx: &mpsc::Sender<ServerMessage>,
+) {
+    match msg {
+        ControlMessage::PlayerDisconnected { player_id } => {
+            println!("👋 [Red] Jugador {} se desconectó", player_id);
+            let _ = network_tx.send(ServerMessage::PlayerDisconnected { player_id });
+        }
+        ControlMessage::VersionMismatch {
+            client_version,
+            min_required,
+            message,
+        } => {
+            println!(
+                "❌ [Red] VERSION INCOMPATIBLE: Tu versión {} es menor que la mínima requerida {}",
+                client_version, min_required
+            );
+            println!("   {}", message);
+            let _ = network_tx.send(ServerMessage::Error {
+                message: format!(
+                    "Versión incompatible: tienes v{}, se requiere v{} o superior. {}",
+                    client_version, min_required, message
+                ),
+            });
+        }
+        ControlMessage::Error { message } => {
+            println!("❌ [Red] Error del servidor: {}", message);
+            let _ = network_tx.send(ServerMessage::Error { message });
+        }
+        ControlMessage::SlotsUpdated(slots) => {
+            println!(
+                "📊 [Red] SlotsUpdated recibido - Admins: {:?}, Starters T0: {}, T1: {}",
+                slots.admins,
+                slots.teams[0].starters.len(),
+                slots.teams[1].starters.len()
+            );
+            // Enviar como un mensaje especial que el cliente puede procesar
+            // Usamos un canal interno para esto
+            let _ = network_tx.send(ServerMessage::SlotsUpdated(slots));
+        }
+        ControlMessage::ScoreUpdated {
+            score,
+            scoring_team,
+        } => {
+            println!(
+                "⚽ [Red] Marcador recibido: {} - {} (gol de {:?})",
+                score[0], score[1], scoring_team
+            );
+            let _ = network_tx.send(ServerMessage::ScoreUpdated {
+                score,
+                scoring_team,
+            });
+        }
+        ControlMessage::Chat(chat) => {
+            let _ = network_tx.send(ServerMessage::Chat(chat));
+        }
+        ControlMessage::PlayerRoster(players) => {
+            *roster = players.into_iter().map(|p| (p.id, p)).collect();
+        }
+        ControlMessage::LatencyUpdated(latencies) => {
+            let _ = network_tx.send(ServerMessage::LatencyUpdated(latencies));
+        }
+        ControlMessage::InstantReplay => {
+            let _ = network_tx.send(ServerMessage::InstantReplay);
+        }
+        _ => {}
+    }
+}
+
+/// Decodifica un snapshot y lo pasa a Bevy; devuelve su tick para confirmarlo
+fn forward_snapshot(
+    delta: SnapshotDelta,
+    decoder: &mut SnapshotDecoder,
+    roster: &std::collections::HashMap<u32, PlayerInfo>,
+    network_tx: &mpsc::Sender<ServerMessage>,
+) -> Option<u32> {
+    // Sin el baseline no se puede decodificar: el host reenvía completo
+    // cuando deja de recibir confirmaciones
+    let state = decoder.decode(delta)?;
+
+    // Convertir a ServerMessage (los jugadores sin roster todavía se omiten)
+    let server_msg = ServerMessage::GameState {
+        tick: state.tick,
+        timestamp: state.timestamp,
+        players: state
+            .players
+            .iter()
+            .filter_map(|p| roster.get(&p.id).map(|info| p.to_state(info)))
+            .collect(),
+        ball: state.ball.to_state(),
+        discs: state.discs.iter().map(|d| d.to_state()).collect(),
+        clock: state.clock,
+    };
+    let _ = network_tx.send(server_msg);
+    Some(state.tick)
+}
@@ -5,12 +5,17 @@ use bevy::sprite_render::ColorMaterial;
 use crate::color_utils::get_team_colors;
 use crate::components::{
     Interpolated, KickChargeBar, KickChargeBarCurveLeft, KickChargeBarCurveRight, PlayerCamera,
-    PlayerSprite, RemoteBall, RemotePlayer,
+    PlayerSprite, RemoteBall, RemoteDisc, RemotePlayer, Snapshot, SnapshotBuffer,
 };
-use crate::events::{SpawnBallEvent, SpawnPlayerEvent};
+use crate::events::{SpawnBallEvent, SpawnDiscEvent, SpawnPlayerEvent};
 use crate::local_players::LocalPlayers;
-use crate::resources::{AdminPanelState, ClientMatchSlots, GameTick, LoadedMap, NetworkChannels};
-use crate::shared::protocol::{GameConfig, ServerMessage};
+use crate::replay::{InstantReplay, ReplayRecorder};
+use crate::resources::{
+    AdminPanelState, ChatState, ClientMatchScore, ClientMatchSlots, GameTick, LoadedMap,
+    NetworkChannels, PlayerLatencies, SnapshotClock,
+};
+use crate::shared::protocol::{ChatKind, ChatMessage, GameConfig, ServerMessage};
+use crate::states::AppState;
 
 #[derive(SystemParam)]
 pub struct NetworkParams<'w, 's> {
@@ -23,11 +28,63 @@ pub struct NetworkParams<'w, 's> {
     pub local_players: ResMut<'w, LocalPlayers>,
     pub game_tick: ResMut<'w, GameTick>,
     pub spawn_ball_events: MessageWriter<'w, SpawnBallEvent>,
+    pub spawn_disc_events: MessageWriter<'w, SpawnDiscEvent>,
     pub spawn_player_events: MessageWriter<'w, SpawnPlayerEvent>,
     pub match_slots: ResMut<'w, ClientMatchSlots>,
     pub admin_state: ResMut<'w, AdminPanelState>,
+    pub match_score: ResMut<'w, ClientMatchScore>,
+    pub chat: ResMut<'w, ChatState>,
+    pub clocks: ClockParams<'w>,
+}
+
+/// Relojes y latencias del cliente (agrupados para no pasar el límite de parámetros)
+#[derive(SystemParam)]
+pub struct ClockParams<'w> {
+    pub time: Res<'w, Time>,
+    pub real_time: Res<'w, Time<Real>>,
+    pub snapshot_clock: ResMut<'w, SnapshotClock>,
+    pub latencies: ResMut<'w, PlayerLatencies>,
+}
+
+/// Grabación del partido y buffer de la repetición instantánea
+#[derive(SystemParam)]
+pub struct ReplayParams<'w> {
+    pub state: Res<'w, State<AppState>>,
+    pub recorder: ResMut<'w, ReplayRecorder>,
+    pub instant_replay: ResMut<'w, InstantReplay>,
 }
 
+/// Aviso del hilo de red que se muestra en el chat
+fn network_notice(text: String) -> ChatMessage {
+    ChatMessage {
+        sender_id: None,
+        sender_name: "Red".to_string(),
+        team_index: None,
+        kind: ChatKind::System,
+        text,
+    }
+}
+
+/// Posiciones de un GameState para los buffers de snapshots
+struct SnapshotFrame {
+    ball: Snapshot,
+    discs: Vec<(u32, Snapshot)>,
+    players: Vec<(u32, Snapshot)>,
+}
+
+/// Discos móviles remotos (disjunta de jugadores y pelota)
+type RemoteDiscQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Interpolated,
+        &'static mut Transform,
+        &'static RemoteDisc,
+        Option<&'static mut SnapshotBuffer>,
+    ),
+    (Without<RemotePlayer>, Without<RemoteBall>),
+>;
+
 #[derive(SystemParam)]
 pub struct NetworkQueries<'w, 's> {
     pub ball_q: Query<
@@ -37,9 +94,11 @@ pub struct NetworkQueries<'w, 's> {
             &'static mut Interpolated,
             &'static mut Transform,
             &'static RemoteBall,
+            Option<&'static mut SnapshotBuffer>,
         ),
         Without<RemotePlayer>,
     >,
+    pub disc_q: RemoteDiscQuery<'w, 's>,
     pub players_q: Query<
         'w,
         's,
@@ -49,6 +108,7 @@ pub struct NetworkQueries<'w, 's> {
             &'static mut Transform,
             &'static mut RemotePlayer,
             &'static Children,
+            Option<&'static mut SnapshotBuffer>,
         ),
         (Without<RemoteBall>, Without<PlayerCamera>),
     >,
@@ -70,7 +130,11 @@ pub struct NetworkQueries<'w, 's> {
     pub text_color_query: Query<'w, 's, &'static mut TextColor>,
 }
 
-pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQueries) {
+pub fn process_network_messages(
+    mut params: NetworkParams,
+    mut queries: NetworkQueries,
+    mut replays: ReplayParams,
+) {
     let commands = &mut params.commands;
     let config = &mut params.config;
     let channels = &params.channels;
@@ -80,28 +144,44 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
     let local_players = &mut params.local_players;
     let game_tick = &mut params.game_tick;
     let spawn_ball_events = &mut params.spawn_ball_events;
+    let spawn_disc_events = &mut params.spawn_disc_events;
     let spawn_player_events = &mut params.spawn_player_events;
     let match_slots = &mut params.match_slots;
     let admin_state = &mut params.admin_state;
+    let match_score = &mut params.match_score;
+    let chat = &mut params.chat;
+    let now = params.clocks.time.elapsed_secs_f64();
+    let local_ms = params.clocks.real_time.elapsed_secs_f64() * 1000.0;
+    let snapshot_clock = &mut params.clocks.snapshot_clock;
+    let latencies = &mut params.clocks.latencies;
 
     let ball_q = &mut queries.ball_q;
+    let disc_q = &mut queries.disc_q;
     let players_q = &mut queries.players_q;
     let bar_sprites = &mut queries.bar_sprites;
     let player_materials = &queries.player_materials;
     let children_query = &queries.children_query;
     let text_color_query = &mut queries.text_color_query;
 
+    // Un replay que se está mirando no se vuelve a grabar ni se repite
+    let live = *replays.state.get() == AppState::InGame;
+    let recorder = &mut replays.recorder;
+    let instant_replay = &mut replays.instant_replay;
+
     let Some(ref receiver) = channels.receiver else {
         return;
     };
     let rx = receiver.lock().unwrap();
     let mut spawned_this_frame = std::collections::HashSet::new();
 
-    // Procesar solo el ultimo GameState si hay multiples (incluye tick)
+    // Todos los GameState van a los buffers de snapshots; el resto del estado
+    // (spawns, equipos, carga de patada) se toma solo del más nuevo
+    let mut snapshot_frames: Vec<SnapshotFrame> = Vec::new();
     let mut last_game_state: Option<(
         u32, // tick
         Vec<crate::shared::protocol::PlayerState>,
         crate::shared::protocol::BallState,
+        Vec<crate::shared::protocol::DiscState>,
     )> = None;
     let mut messages = Vec::new();
 
@@ -110,6 +190,9 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
     }
 
     for msg in messages {
+        if live {
+            recorder.record(now, &msg);
+        }
         match msg {
             ServerMessage::Welcome { player_id, map } => {
                 println!("🎉 [Bevy] Welcome recibido. PlayerID: {}", player_id);
@@ -153,6 +236,8 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
                             received_map.segments.len(),
                             received_map.discs.len()
                         );
+                        // Radios de jugador/pelota definidos por el mapa
+                        received_map.apply_physics(config);
                         loaded_map.0 = Some(received_map);
                     } else {
                         println!("🏟️  [Bevy] Usando arena por defecto");
@@ -162,15 +247,72 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
             ServerMessage::GameState {
                 players,
                 ball,
+                discs,
                 tick,
-                ..
+                timestamp,
+                clock,
             } => {
+                match_score.clock = clock;
                 // Log solo el primer GameState recibido
                 if tick == 1 {
                     println!("📥 [Bevy] Primer GameState recibido: {} jugadores, pelota en ({:.0}, {:.0})",
                         players.len(), ball.position.0, ball.position.1);
                 }
-                last_game_state = Some((tick, players, ball));
+                let server_time = timestamp as f64;
+                snapshot_clock.observe(server_time, local_ms);
+                if live {
+                    instant_replay.push(
+                        server_time,
+                        Vec2::new(ball.position.0, ball.position.1),
+                        players
+                            .iter()
+                            .map(|ps| (ps.id, ps.position, ps.rotation))
+                            .collect(),
+                    );
+                }
+                snapshot_frames.push(SnapshotFrame {
+                    ball: Snapshot {
+                        tick,
+                        server_time,
+                        position: Vec2::new(ball.position.0, ball.position.1),
+                        velocity: Vec2::new(ball.velocity.0, ball.velocity.1),
+                        rotation: None,
+                    },
+                    discs: discs
+                        .iter()
+                        .map(|d| {
+                            let snapshot = Snapshot {
+                                tick,
+                                server_time,
+                                position: Vec2::new(d.position.0, d.position.1),
+                                velocity: Vec2::new(d.velocity.0, d.velocity.1),
+                                rotation: None,
+                            };
+                            (d.index, snapshot)
+                        })
+                        .collect(),
+                    players: players
+                        .iter()
+                        .map(|ps| {
+                            let snapshot = Snapshot {
+                                tick,
+                                server_time,
+                                position: ps.position,
+                                velocity: Vec2::new(ps.velocity.0, ps.velocity.1),
+                                rotation: Some(ps.rotation),
+                            };
+                            (ps.id, snapshot)
+                        })
+                        .collect(),
+                });
+
+                // Con paquetes desordenados puede llegar uno viejo después del nuevo
+                if last_game_state
+                    .as_ref()
+                    .is_none_or(|(last_tick, ..)| tick > *last_tick)
+                {
+                    last_game_state = Some((tick, players, ball, discs));
+                }
             }
             ServerMessage::ChangeTeamColor { team_index, color } => {
                 println!(
@@ -189,7 +331,7 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
                     get_team_colors(team_index, &config.team_colors);
 
                 // 3. Actualizar jugadores de ese equipo
-                for (_, _, _, player, children) in players_q.iter() {
+                for (_, _, _, player, children, _) in players_q.iter() {
                     if player.team_index != team_index {
                         continue;
                     }
@@ -222,7 +364,7 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
             }
             ServerMessage::PlayerDisconnected { player_id } => {
                 // Buscar y eliminar el jugador desconectado
-                for (entity, _, _, rp, _) in players_q.iter() {
+                for (entity, _, _, rp, _, _) in players_q.iter() {
                     if rp.id == player_id {
                         commands.entity(entity).despawn();
                         println!("👋 [Bevy] Jugador {} eliminado del juego", player_id);
@@ -251,22 +393,92 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
                     }
                 }
             }
+            ServerMessage::ScoreUpdated {
+                score,
+                scoring_team,
+            } => {
+                match_score.score = score;
+                if let Some(team) = scoring_team {
+                    println!(
+                        "⚽ [Bevy] ¡Gol del equipo {}! {} - {}",
+                        team, score[0], score[1]
+                    );
+                    match_score.last_goal = Some((team, now));
+                    if live {
+                        instant_replay.request_goal();
+                    }
+                }
+            }
+            ServerMessage::InstantReplay if live => {
+                println!("⏪ [Bevy] Un admin pidió una repetición");
+                instant_replay.request_recent();
+            }
+            ServerMessage::Chat(message) => {
+                chat.push(message, now);
+            }
+            ServerMessage::LatencyUpdated(entries) => {
+                latencies.0 = entries.into_iter().map(|l| (l.player_id, l)).collect();
+            }
+            ServerMessage::Reconnecting { attempt } => {
+                // Si el host recupera a los jugadores, los WELCOME devuelven los mismos IDs
+                println!("🔄 [Bevy] Reconectando con el host (intento {})", attempt);
+                for local_player in local_players.players.iter_mut() {
+                    local_player.server_player_id = None;
+                }
+                my_id.0 = None;
+                snapshot_clock.reset();
+                chat.push(
+                    network_notice(format!(
+                        "Conexión perdida, reconectando (intento {})...",
+                        attempt
+                    )),
+                    now,
+                );
+            }
+            ServerMessage::Error { message } => {
+                println!("❌ [Bevy] {}", message);
+                chat.push(network_notice(message), now);
+            }
             _ => {}
         }
     }
 
+    // Cargar los snapshots en los buffers de las entidades remotas
+    for frame in &snapshot_frames {
+        for (_, _, _, buffer) in ball_q.iter_mut() {
+            if let Some(mut buffer) = buffer {
+                buffer.push(frame.ball);
+            }
+        }
+        for (_, _, remote_disc, buffer) in disc_q.iter_mut() {
+            let Some(mut buffer) = buffer else { continue };
+            if let Some((_, snapshot)) = frame.discs.iter().find(|(i, _)| *i == remote_disc.index) {
+                buffer.push(*snapshot);
+            }
+        }
+        for (_, _, _, rp, _, buffer) in players_q.iter_mut() {
+            let Some(mut buffer) = buffer else { continue };
+            if let Some((_, snapshot)) = frame.players.iter().find(|(id, _)| *id == rp.id) {
+                buffer.push(*snapshot);
+            }
+        }
+    }
+
     // Procesar solo el ultimo GameState si existe
-    if let Some((tick, players, ball)) = last_game_state {
+    if let Some((tick, players, ball, discs)) = last_game_state {
         game_tick.0 = tick;
 
         // Actualizar Pelota
         let ball_exists = !ball_q.is_empty();
         if ball_exists {
-            for (mut interp, mut transform, _) in ball_q.iter_mut() {
+            for (mut interp, mut transform, _, buffer) in ball_q.iter_mut() {
                 interp.target_position = Vec2::new(ball.position.0, ball.position.1);
                 interp.target_velocity = Vec2::new(ball.velocity.0, ball.velocity.1);
-                transform.translation.x = ball.position.0;
-                transform.translation.y = ball.position.1;
+                // Con buffer la posición la decide interpolate_entities
+                if buffer.is_none() {
+                    transform.translation.x = ball.position.0;
+                    transform.translation.y = ball.position.1;
+                }
             }
         } else {
             // Emitir evento para spawn de pelota
@@ -276,13 +488,37 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
             });
         }
 
+        // Actualizar discos móviles del mapa (igual que la pelota)
+        for disc in &discs {
+            let mut found = false;
+            for (mut interp, mut transform, remote_disc, buffer) in disc_q.iter_mut() {
+                if remote_disc.index == disc.index {
+                    interp.target_position = Vec2::new(disc.position.0, disc.position.1);
+                    interp.target_velocity = Vec2::new(disc.velocity.0, disc.velocity.1);
+                    if buffer.is_none() {
+                        transform.translation.x = disc.position.0;
+                        transform.translation.y = disc.position.1;
+                    }
+                    found = true;
+                    break;
+                }
+            }
+            if !found {
+                spawn_disc_events.write(SpawnDiscEvent {
+                    index: disc.index,
+                    position: disc.position,
+                    velocity: disc.velocity,
+                });
+            }
+        }
+
         // Primero: detectar jugadores que cambiaron de equipo y despawnearlos
         // Esto fuerza un respawn con los colores correctos
         let mut team_changed_ids: std::collections::HashSet<u32> = std::collections::HashSet::new();
         let mut entities_to_despawn: Vec<Entity> = Vec::new();
 
         for ps in &players {
-            for (entity, _, _, rp, _) in players_q.iter() {
+            for (entity, _, _, rp, _, _) in players_q.iter() {
                 if rp.id == ps.id && rp.team_index != ps.team_index {
                     println!(
                         "🔄 [Client] Jugador {} cambió de equipo {} -> {}, despawneando para respawn",
@@ -326,13 +562,15 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
             }
 
             let mut found = false;
-            for (_entity, mut interp, mut transform, mut rp, _) in players_q.iter_mut() {
+            for (_entity, mut interp, mut transform, mut rp, _, buffer) in players_q.iter_mut() {
                 if rp.id == ps.id {
                     interp.target_position = ps.position;
                     interp.target_velocity = Vec2::new(ps.velocity.0, ps.velocity.1);
                     interp.target_rotation = ps.rotation;
-                    transform.translation.x = ps.position.x;
-                    transform.translation.y = ps.position.y;
+                    if buffer.is_none() {
+                        transform.translation.x = ps.position.x;
+                        transform.translation.y = ps.position.y;
+                    }
                     rp.kick_charge = ps.kick_charge;
                     rp.is_sliding = ps.is_sliding;
                     rp.ball_target_position = ps.ball_target_position;
@@ -340,6 +578,8 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
                     rp.active_movement = ps.active_movement.clone();
                     rp.mode_cube_active = ps.mode_cube_active;
                     rp.team_index = ps.team_index;
+                    rp.server_position = ps.position;
+                    rp.last_input_sequence = ps.last_input_sequence;
 
                     found = true;
                     break;
@@ -382,7 +622,7 @@ pub fn process_network_messages(mut params: NetworkParams, mut queries: NetworkQ
         // Collect entities to despawn (can't despawn while iterating)
         let mut to_despawn: Vec<Entity> = Vec::new();
 
-        for (entity, _, _, rp, _) in players_q.iter() {
+        for (entity, _, _, rp, _, _) in players_q.iter() {
             if !all_starters.contains(&rp.id) {
                 to_despawn.push(entity);
                 println!("🚫 [Bevy] Despawneando jugador {} (no es starter)", rp.id);
@@ -3,4 +3,6 @@ pub mod hmac_auth;
 mod messages;
 
 pub use client::{start_connection, check_connection, start_webrtc_client};
-pub use messages::{NetworkParams, NetworkQueries, process_network_messages};
+#[cfg(test)]
+pub use client::run_direct_client;
+pub use messages::process_network_messages;
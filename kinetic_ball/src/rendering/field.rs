@@ -180,8 +180,8 @@ pub fn spawn_map_lines(
         }
     }
 
-    // Dibujar discos (círculos)
-    for disc in &map.discs {
+    // Dibujar discos fijos (los móviles se replican desde el host como la pelota)
+    for disc in map.discs.iter().filter(|d| !d.is_dynamic()) {
         let pos = Vec2::new(disc.pos[0], disc.pos[1]);
         spawn_circle_outline(commands, meshes, materials, pos, disc.radius, disc_color);
     }
@@ -309,12 +309,11 @@ pub fn approximate_curve_for_rendering(
     // Ángulo inicial (de center a p0)
     let start_angle = (p0.y - center.y).atan2(p0.x - center.x);
 
-    // Determinar dirección de barrido
-    let angle_step = if curve > 0.0 {
-        -total_angle / num_segments as f32
-    } else {
-        total_angle / num_segments as f32
-    };
+    // Barrer de p0 a p1 por el lado opuesto al centro (el arco "panza" hacia -perp
+    // con curva positiva), así el último punto cae en p1
+    let bulge = if curve > 0.0 { -perp } else { perp };
+    let direction = (p0 - center).perp_dot(bulge).signum();
+    let angle_step = direction * total_angle / num_segments as f32;
 
     // Generar puntos
     for i in 0..=num_segments {
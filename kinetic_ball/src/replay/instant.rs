@@ -0,0 +1,328 @@
+// ============================================================================
+// REPETICIÓN INSTANTÁNEA
+// ============================================================================
+//
+// El cliente guarda los últimos segundos de snapshots. Tras un gol (o cuando un
+// admin la pide) las entidades remotas se dibujan desde ese buffer en cámara
+// lenta y con una cámara de transmisión; los snapshots en vivo se siguen
+// recibiendo, así que al terminar el juego continúa sin saltos. Mientras dura,
+// los jugadores locales no se mueven, y el host la deja terminar antes de
+// poner la pelota en juego (ver `GOAL_REPLAY_SECS`).
+
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::components::{PlayerCamera, RemoteBall, RemotePlayer};
+use crate::shared::replay::{
+    GOAL_REPLAY_AFTER_SECS, GOAL_REPLAY_BEFORE_SECS, INSTANT_REPLAY_SLOW_MOTION,
+};
+
+/// Segundos de snapshots recientes que se guardan
+const BUFFER_SECS: f64 = 10.0;
+
+/// Tramo repetido cuando lo pide un admin (hasta el momento del pedido)
+const REQUESTED_SECS: f64 = 8.0;
+
+/// Zoom de la cámara de transmisión (ve buena parte de la cancha)
+const BROADCAST_ZOOM: f32 = 2.0;
+
+/// Posiciones de un tick del host
+#[derive(Clone)]
+struct Frame {
+    /// Timestamp del host en ms
+    server_time: f64,
+    ball: Vec2,
+    /// (id, posición, rotación)
+    players: Vec<(u32, Vec2, f32)>,
+}
+
+impl Frame {
+    fn lerp(&self, next: &Frame, t: f32) -> Frame {
+        Frame {
+            server_time: self.server_time + (next.server_time - self.server_time) * t as f64,
+            ball: self.ball.lerp(next.ball, t),
+            players: self
+                .players
+                .iter()
+                .map(|&(id, position, rotation)| {
+                    match next.players.iter().find(|(next_id, ..)| *next_id == id) {
+                        Some(&(_, next_position, _)) => {
+                            (id, position.lerp(next_position, t), rotation)
+                        }
+                        None => (id, position, rotation),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Tramo que se está repitiendo (ms del host). Tiene su propia copia de los
+/// frames: el buffer sigue descartando los viejos durante la cámara lenta
+struct Playback {
+    frames: Vec<Frame>,
+    end: f64,
+    cursor: f64,
+}
+
+impl Playback {
+    /// Posiciones en `time` (ms del host), interpoladas entre los frames vecinos
+    fn sample(&self, time: f64) -> Option<Frame> {
+        let index = self.frames.partition_point(|f| f.server_time <= time);
+        let prev = self.frames.get(index.checked_sub(1)?)?;
+        let Some(next) = self.frames.get(index) else {
+            return Some(prev.lerp(prev, 0.0));
+        };
+        let span = next.server_time - prev.server_time;
+        let t = if span > 0.0 {
+            ((time - prev.server_time) / span) as f32
+        } else {
+            1.0
+        };
+        Some(prev.lerp(next, t))
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct InstantReplay {
+    frames: VecDeque<Frame>,
+    /// Repetición pedida (inicio, fin): empieza cuando el buffer llega al fin
+    pending: Option<(f64, f64)>,
+    playing: Option<Playback>,
+    /// Zoom de las cámaras antes de la repetición
+    saved_zoom: Option<f32>,
+}
+
+impl InstantReplay {
+    /// Guarda las posiciones de un GameState recibido
+    pub fn push(&mut self, server_time: f64, ball: Vec2, players: Vec<(u32, Vec2, f32)>) {
+        if self
+            .frames
+            .back()
+            .is_some_and(|last| server_time <= last.server_time)
+        {
+            return;
+        }
+        self.frames.push_back(Frame {
+            server_time,
+            ball,
+            players,
+        });
+        while self
+            .frames
+            .front()
+            .is_some_and(|first| server_time - first.server_time > BUFFER_SECS * 1000.0)
+        {
+            self.frames.pop_front();
+        }
+    }
+
+    fn latest_time(&self) -> Option<f64> {
+        self.frames.back().map(|f| f.server_time)
+    }
+
+    /// Repetición del gol que acaba de entrar: arranca cuando llegan los snapshots
+    /// de después del gol
+    pub fn request_goal(&mut self) {
+        if let Some(now) = self.latest_time() {
+            self.pending = Some((
+                now - GOAL_REPLAY_BEFORE_SECS * 1000.0,
+                now + GOAL_REPLAY_AFTER_SECS * 1000.0,
+            ));
+        }
+    }
+
+    /// Repetición de los últimos segundos pedida por un admin
+    pub fn request_recent(&mut self) {
+        if let Some(now) = self.latest_time() {
+            self.pending = Some((now - REQUESTED_SECS * 1000.0, now));
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing.is_some()
+    }
+
+    /// Vuelve al juego en vivo
+    pub fn skip(&mut self) {
+        self.pending = None;
+        if let Some(playback) = &mut self.playing {
+            playback.cursor = playback.end;
+        }
+    }
+
+    /// Arranca la repetición pedida cuando el buffer ya tiene el tramo completo,
+    /// copiando sus frames (más los vecinos de cada punta para interpolar)
+    fn start_pending(&mut self) -> bool {
+        let (Some((start, end)), Some(latest)) = (self.pending, self.latest_time()) else {
+            return false;
+        };
+        if latest < end {
+            return false;
+        }
+        self.pending = None;
+        let first = self
+            .frames
+            .partition_point(|f| f.server_time <= start)
+            .saturating_sub(1);
+        let last = self.frames.partition_point(|f| f.server_time < end);
+        let frames: Vec<Frame> = self
+            .frames
+            .range(first..=last.min(self.frames.len() - 1))
+            .cloned()
+            .collect();
+        let cursor = frames.first().map_or(start, |f| start.max(f.server_time));
+        self.playing = Some(Playback {
+            frames,
+            end,
+            cursor,
+        });
+        true
+    }
+}
+
+/// Condición de los sistemas que mueven la cámara en vivo
+pub fn instant_replay_idle(instant_replay: Res<InstantReplay>) -> bool {
+    !instant_replay.is_playing()
+}
+
+/// Jugadores remotos (disjunta de la pelota y las cámaras)
+type ReplayPlayerQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static RemotePlayer, &'static mut Transform),
+    (Without<RemoteBall>, Without<PlayerCamera>),
+>;
+
+/// Reproduce la repetición: pisa las posiciones que dejó la interpolación en vivo y
+/// maneja las cámaras de jugador como una transmisión (sigue la pelota desde lejos)
+pub fn play_instant_replay(
+    mut instant_replay: ResMut<InstantReplay>,
+    time: Res<Time>,
+    mut players: ReplayPlayerQuery,
+    mut ball: Query<&mut Transform, (With<RemoteBall>, Without<PlayerCamera>)>,
+    mut cameras: Query<(&mut Transform, &mut Projection), With<PlayerCamera>>,
+) {
+    let replay = instant_replay.as_mut();
+
+    // Empezar cuando el buffer ya tiene el tramo completo
+    if replay.start_pending() {
+        println!("⏪ [Bevy] Repetición");
+    }
+
+    let Some(playback) = &mut replay.playing else {
+        return;
+    };
+    playback.cursor += time.delta_secs_f64() * 1000.0 * INSTANT_REPLAY_SLOW_MOTION;
+    let cursor = playback.cursor;
+    let finished = cursor >= playback.end;
+
+    if finished {
+        replay.playing = None;
+        // De vuelta al juego en vivo con el zoom que tenía el jugador
+        if let Some(zoom) = replay.saved_zoom.take() {
+            for (_, mut projection) in cameras.iter_mut() {
+                if let Projection::Orthographic(ref mut ortho) = projection.as_mut() {
+                    ortho.scale = zoom;
+                }
+            }
+        }
+        return;
+    }
+
+    let Some(frame) = replay.playing.as_ref().and_then(|p| p.sample(cursor)) else {
+        return;
+    };
+    for (player, mut transform) in players.iter_mut() {
+        if let Some(&(_, position, rotation)) =
+            frame.players.iter().find(|(id, ..)| *id == player.id)
+        {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+            transform.rotation = Quat::from_rotation_z(rotation);
+        }
+    }
+    for mut transform in ball.iter_mut() {
+        transform.translation.x = frame.ball.x;
+        transform.translation.y = frame.ball.y;
+    }
+
+    // Cámara de transmisión: acompaña la pelota a lo largo de la cancha y se
+    // mueve poco en vertical
+    let delta = time.delta_secs();
+    let target = Vec2::new(frame.ball.x, frame.ball.y * 0.4);
+    for (mut cam_transform, mut projection) in cameras.iter_mut() {
+        if let Projection::Orthographic(ref mut ortho) = projection.as_mut() {
+            if replay.saved_zoom.is_none() {
+                replay.saved_zoom = Some(ortho.scale);
+            }
+            ortho.scale = BROADCAST_ZOOM;
+        }
+        cam_transform.translation.x += (target.x - cam_transform.translation.x) * 4.0 * delta;
+        cam_transform.translation.y += (target.y - cam_transform.translation.y) * 4.0 * delta;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::replay::GOAL_REPLAY_SECS;
+
+    /// Un frame cada 100 ms con la pelota en x = ms
+    fn push_frames(replay: &mut InstantReplay, from_ms: u32, to_ms: u32) {
+        for ms in (from_ms..=to_ms).step_by(100) {
+            replay.push(
+                ms as f64,
+                Vec2::new(ms as f32, 0.0),
+                vec![(1, Vec2::ZERO, 0.0)],
+            );
+        }
+    }
+
+    #[test]
+    fn goal_replay_waits_for_the_frames_after_the_goal() {
+        let mut replay = InstantReplay::default();
+        push_frames(&mut replay, 0, 5_000);
+        replay.request_goal();
+        assert!(!replay.start_pending());
+        push_frames(&mut replay, 5_100, 6_500);
+        assert!(replay.start_pending());
+        assert!(replay.is_playing());
+    }
+
+    #[test]
+    fn goal_replay_ends_within_goal_replay_secs() {
+        let mut replay = InstantReplay::default();
+        push_frames(&mut replay, 0, 10_000);
+        replay.request_goal();
+        let after_ms = (GOAL_REPLAY_AFTER_SECS * 1000.0) as u32;
+        push_frames(&mut replay, 10_100, 10_000 + after_ms);
+        assert!(replay.start_pending());
+
+        // Espera de los snapshots de después del gol + el tramo en cámara lenta
+        let playback = replay.playing.as_ref().unwrap();
+        let slow_ms = (playback.end - playback.cursor) / INSTANT_REPLAY_SLOW_MOTION;
+        let total_secs = (after_ms as f64 + slow_ms) / 1000.0;
+        assert!(total_secs <= GOAL_REPLAY_SECS as f64 + 1e-6);
+    }
+
+    #[test]
+    fn playback_keeps_its_frames_while_live_snapshots_arrive() {
+        let mut replay = InstantReplay::default();
+        push_frames(&mut replay, 0, 10_000);
+        replay.request_recent();
+        assert!(replay.start_pending());
+
+        // En cámara lenta el juego en vivo sigue llegando y el buffer descarta
+        // todo el tramo que se está repitiendo
+        push_frames(&mut replay, 10_100, 30_000);
+        assert!(replay.frames.front().unwrap().server_time > 10_000.0);
+
+        let playback = replay.playing.as_ref().unwrap();
+        assert_eq!(playback.cursor, 2_000.0);
+        let frame = playback.sample(2_050.0).unwrap();
+        assert!((frame.ball.x - 2_050.0).abs() < 1e-3);
+        let frame = playback.sample(playback.end).unwrap();
+        assert!((frame.ball.x - 10_000.0).abs() < 1e-3);
+    }
+}
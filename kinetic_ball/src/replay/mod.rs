@@ -0,0 +1,10 @@
+mod instant;
+mod player;
+mod recorder;
+
+pub use instant::{instant_replay_idle, play_instant_replay, InstantReplay};
+pub use player::{
+    advance_replay, replay_camera, start_replay, stop_replay, ReplayCamera, ReplayPlayback,
+    REPLAY_SPEEDS,
+};
+pub use recorder::{get_replays_dir, save_finished_replay, ReplayRecorder};
@@ -0,0 +1,556 @@
+// ============================================================================
+// REPRODUCCIÓN DE REPLAYS
+// ============================================================================
+//
+// El replay reemplaza al hilo de red: envía los mensajes grabados por el mismo
+// canal que lee `process_network_messages`, así jugadores, pelota, minimapa y
+// cámaras se dibujan igual que en un partido en vivo. El reloj de snapshots se
+// ata al tiempo de reproducción, lo que da pausa, velocidad y saltos exactos.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+use crate::components::{PlayerCamera, RemoteBall, RemoteDisc, RemotePlayer, SnapshotBuffer};
+use crate::resources::{LoadedMap, NetworkChannels, SnapshotClock};
+use crate::shared::protocol::{GameConfig, ServerMessage};
+use crate::shared::replay::{Replay, ReplayFrame};
+
+/// Velocidades de reproducción disponibles
+pub const REPLAY_SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+
+/// Salto de las flechas izquierda/derecha (segundos)
+const SEEK_STEP_SECS: f32 = 5.0;
+
+/// Velocidad de la cámara libre con zoom 1x (unidades/s)
+const FREE_CAMERA_SPEED: f32 = 900.0;
+
+/// A qué sigue la cámara durante el replay
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum ReplayCamera {
+    #[default]
+    Ball,
+    Player(u32),
+    /// WASD para mover, 1-9 para el zoom
+    Free,
+}
+
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Option<Replay>,
+    sender: Option<mpsc::Sender<ServerMessage>>,
+    /// Segundos del replay que se están mostrando
+    time: f32,
+    /// Próximo frame a enviar
+    cursor: usize,
+    /// Hay que reconstruir el estado en `time` (inicio o salto)
+    resync: bool,
+    pub paused: bool,
+    pub speed: f32,
+    pub camera: ReplayCamera,
+    /// Error al abrir el último archivo (se muestra en el menú)
+    pub load_error: Option<String>,
+}
+
+impl Default for ReplayPlayback {
+    fn default() -> Self {
+        Self {
+            replay: None,
+            sender: None,
+            time: 0.0,
+            cursor: 0,
+            resync: true,
+            paused: false,
+            speed: 1.0,
+            camera: ReplayCamera::default(),
+            load_error: None,
+        }
+    }
+}
+
+impl ReplayPlayback {
+    /// Abre un archivo de replay; queda listo para reproducirse desde el inicio
+    pub fn load_file(&mut self, path: &Path) -> Result<(), String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Error leyendo replay: {}", e))?;
+        let replay = Replay::decode(&bytes)?;
+        println!(
+            "🎬 [Replay] Abierto {:?}: {} frames, {:.0}s",
+            path,
+            replay.frames.len(),
+            replay.duration_secs()
+        );
+        *self = Self {
+            replay: Some(replay),
+            ..Default::default()
+        };
+        Ok(())
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.replay.as_ref().map_or(0.0, Replay::duration_secs)
+    }
+
+    pub fn seek(&mut self, time: f32) {
+        self.time = time.clamp(0.0, self.duration());
+        self.resync = true;
+    }
+
+    /// Pasa a la velocidad siguiente (`step` = 1) o anterior (`step` = -1)
+    pub fn change_speed(&mut self, step: i32) {
+        let current = REPLAY_SPEEDS
+            .iter()
+            .position(|s| *s >= self.speed)
+            .unwrap_or(REPLAY_SPEEDS.len() - 1) as i32;
+        let next = (current + step).clamp(0, REPLAY_SPEEDS.len() as i32 - 1);
+        self.speed = REPLAY_SPEEDS[next as usize];
+    }
+
+    pub fn toggle_pause(&mut self) {
+        // Al final, reproducir de nuevo vuelve al inicio
+        if self.paused && self.time >= self.duration() {
+            self.seek(0.0);
+        }
+        self.paused = !self.paused;
+    }
+
+    /// Frames que ya tocan según el tiempo de reproducción
+    fn due_messages(&mut self) -> Vec<ServerMessage> {
+        let Some(replay) = &self.replay else {
+            return Vec::new();
+        };
+        let end = replay.frames.partition_point(|f| f.time_secs <= self.time);
+        let messages = replay.frames[self.cursor.min(end)..end]
+            .iter()
+            .map(stamped)
+            .collect();
+        self.cursor = end;
+        messages
+    }
+
+    /// Estado completo en `time` tras un salto: colores, slots y marcador vigentes
+    /// más el último GameState
+    fn resync_messages(&mut self) -> Vec<ServerMessage> {
+        let Some(replay) = &self.replay else {
+            return Vec::new();
+        };
+        let end = replay.frames.partition_point(|f| f.time_secs <= self.time);
+
+        let mut colors: Vec<&ReplayFrame> = Vec::new();
+        let mut slots = None;
+        let mut score = None;
+        let mut state = None;
+        for frame in &replay.frames[..end] {
+            match &frame.message {
+                ServerMessage::ChangeTeamColor { team_index, .. } => {
+                    colors.retain(|f| {
+                        !matches!(f.message, ServerMessage::ChangeTeamColor { team_index: t, .. } if t == *team_index)
+                    });
+                    colors.push(frame);
+                }
+                ServerMessage::SlotsUpdated(_) => slots = Some(frame),
+                ServerMessage::ScoreUpdated { .. } => score = Some(frame),
+                ServerMessage::GameState { .. } => state = Some(frame),
+                _ => {}
+            }
+        }
+
+        let mut messages: Vec<ServerMessage> = colors.into_iter().map(stamped).collect();
+        messages.extend(slots.map(stamped));
+        // Sin `scoring_team`: un salto no festeja goles
+        if let Some(ServerMessage::ScoreUpdated { score, .. }) = score.map(|f| &f.message) {
+            messages.push(ServerMessage::ScoreUpdated {
+                score: *score,
+                scoring_team: None,
+            });
+        }
+        messages.extend(state.map(stamped));
+        self.cursor = end;
+        messages
+    }
+}
+
+/// Copia del mensaje grabado con el timestamp en ms del replay (el reloj de snapshots
+/// de la reproducción corre en esa escala)
+fn stamped(frame: &ReplayFrame) -> ServerMessage {
+    let mut message = frame.message.clone();
+    if let ServerMessage::GameState { timestamp, .. } = &mut message {
+        *timestamp = (frame.time_secs * 1000.0) as u64;
+    }
+    message
+}
+
+/// Tras un salto quita los jugadores y discos que no están en el GameState al que
+/// se saltó: los mensajes del replay solo crean y mueven entidades, así que
+/// quedarían congelados donde estaban
+fn despawn_missing(
+    commands: &mut Commands,
+    messages: &[ServerMessage],
+    players: &Query<(Entity, &RemotePlayer)>,
+    discs: &Query<(Entity, &RemoteDisc)>,
+) {
+    let state = messages.iter().find_map(|message| match message {
+        ServerMessage::GameState { players, discs, .. } => Some((players, discs)),
+        _ => None,
+    });
+    let (player_ids, disc_indices): (HashSet<u32>, HashSet<u32>) = match state {
+        Some((players, discs)) => (
+            players.iter().map(|p| p.id).collect(),
+            discs.iter().map(|d| d.index).collect(),
+        ),
+        None => Default::default(),
+    };
+
+    for (entity, player) in players.iter() {
+        if !player_ids.contains(&player.id) {
+            commands.entity(entity).despawn();
+        }
+    }
+    for (entity, disc) in discs.iter() {
+        if !disc_indices.contains(&disc.index) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Conecta el replay al canal de mensajes del juego y carga su mapa y colores
+pub fn start_replay(
+    mut playback: ResMut<ReplayPlayback>,
+    mut channels: ResMut<NetworkChannels>,
+    mut loaded_map: ResMut<LoadedMap>,
+    mut config: ResMut<GameConfig>,
+) {
+    let (replay_tx, replay_rx) = mpsc::channel();
+    channels.receiver = Some(Arc::new(Mutex::new(replay_rx)));
+    playback.sender = Some(replay_tx);
+    playback.seek(0.0);
+
+    let Some(replay) = &playback.replay else {
+        return;
+    };
+    if let Some(map) = &replay.map {
+        // Radios de jugador/pelota definidos por el mapa
+        map.apply_physics(&mut config);
+        loaded_map.0 = Some(map.clone());
+    }
+    config.team_colors = replay.team_colors.clone();
+}
+
+/// Olvida el replay al volver al menú
+pub fn stop_replay(mut playback: ResMut<ReplayPlayback>) {
+    *playback = ReplayPlayback::default();
+}
+
+/// Entidades remotas que un salto tiene que reiniciar o quitar
+#[derive(SystemParam)]
+pub struct ReplayEntities<'w, 's> {
+    pub buffers: Query<'w, 's, &'static mut SnapshotBuffer>,
+    pub players: Query<'w, 's, (Entity, &'static RemotePlayer)>,
+    pub discs: Query<'w, 's, (Entity, &'static RemoteDisc)>,
+}
+
+/// Avanza la reproducción y envía los frames que tocan. Atajos: Espacio pausa,
+/// flechas izquierda/derecha saltan, arriba/abajo cambian la velocidad
+pub fn advance_replay(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    real_time: Res<Time<Real>>,
+    mut snapshot_clock: ResMut<SnapshotClock>,
+    mut entities: ReplayEntities,
+) {
+    if keyboard.just_pressed(KeyCode::Space) {
+        playback.toggle_pause();
+    }
+    if keyboard.just_pressed(KeyCode::ArrowLeft) {
+        let target = playback.time - SEEK_STEP_SECS;
+        playback.seek(target);
+    }
+    if keyboard.just_pressed(KeyCode::ArrowRight) {
+        let target = playback.time + SEEK_STEP_SECS;
+        playback.seek(target);
+    }
+    if keyboard.just_pressed(KeyCode::ArrowUp) {
+        playback.change_speed(1);
+    }
+    if keyboard.just_pressed(KeyCode::ArrowDown) {
+        playback.change_speed(-1);
+    }
+
+    if !playback.paused {
+        let duration = playback.duration();
+        playback.time = (playback.time + time.delta_secs() * playback.speed).min(duration);
+        if playback.time >= duration {
+            playback.paused = true;
+        }
+    }
+
+    let messages = if playback.resync {
+        playback.resync = false;
+        // Los ticks pueden ir hacia atrás: se descartan los snapshots de antes del salto
+        for mut buffer in entities.buffers.iter_mut() {
+            buffer.clear();
+        }
+        let messages = playback.resync_messages();
+        despawn_missing(&mut commands, &messages, &entities.players, &entities.discs);
+        messages
+    } else {
+        playback.due_messages()
+    };
+    if let Some(sender) = &playback.sender {
+        for message in messages {
+            let _ = sender.send(message);
+        }
+    }
+
+    snapshot_clock.sync_to(
+        playback.time as f64 * 1000.0,
+        real_time.elapsed_secs_f64() * 1000.0,
+    );
+}
+
+/// Mueve las cámaras de jugador según el modo elegido para el replay
+pub fn replay_camera(
+    playback: Res<ReplayPlayback>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    players: Query<(&RemotePlayer, &Transform), Without<PlayerCamera>>,
+    ball: Query<&Transform, (With<RemoteBall>, Without<PlayerCamera>)>,
+    mut cameras: Query<(&mut Transform, &Projection), With<PlayerCamera>>,
+) {
+    let delta = time.delta_secs();
+    let smoothing = 10.0;
+
+    let target = match playback.camera {
+        ReplayCamera::Ball => ball.iter().next().map(|t| t.translation),
+        ReplayCamera::Player(id) => players
+            .iter()
+            .find(|(p, _)| p.id == id)
+            .map(|(_, t)| t.translation),
+        ReplayCamera::Free => None,
+    };
+
+    let mut pan = Vec2::ZERO;
+    if keyboard.pressed(KeyCode::KeyW) {
+        pan.y += 1.0;
+    }
+    if keyboard.pressed(KeyCode::KeyS) {
+        pan.y -= 1.0;
+    }
+    if keyboard.pressed(KeyCode::KeyA) {
+        pan.x -= 1.0;
+    }
+    if keyboard.pressed(KeyCode::KeyD) {
+        pan.x += 1.0;
+    }
+
+    for (mut cam_transform, projection) in cameras.iter_mut() {
+        if let Some(target) = target {
+            cam_transform.translation.x +=
+                (target.x - cam_transform.translation.x) * smoothing * delta;
+            cam_transform.translation.y +=
+                (target.y - cam_transform.translation.y) * smoothing * delta;
+        } else if playback.camera == ReplayCamera::Free {
+            // Con más zoom afuera la cámara se mueve más rápido
+            let scale = match projection {
+                Projection::Orthographic(ortho) => ortho.scale,
+                _ => 1.0,
+            };
+            let step = pan.normalize_or_zero() * FREE_CAMERA_SPEED * scale * delta;
+            cam_transform.translation.x += step.x;
+            cam_transform.translation.y += step.y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::protocol::{DiscState, PlayerState, ProtocolVersion};
+    use crate::shared::replay::game_state;
+    use crate::shared::MatchSlots;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn player_state(id: u32) -> PlayerState {
+        PlayerState {
+            id,
+            name: format!("P{}", id),
+            position: Vec2::ZERO,
+            velocity: (0.0, 0.0),
+            rotation: 0.0,
+            kick_charge: Vec2::ZERO,
+            kick_charging: false,
+            is_sliding: false,
+            not_interacting: false,
+            ball_target_position: None,
+            stamin_charge: 1.0,
+            active_movement: None,
+            team_index: 0,
+            mode_cube_active: false,
+            last_input_sequence: 0,
+        }
+    }
+
+    /// El jugador 2 y el disco 0 solo están en los primeros 5 segundos
+    fn frame_state(tick: u32) -> ServerMessage {
+        let early = tick <= 300;
+        let mut players = vec![player_state(1)];
+        let mut discs = Vec::new();
+        if early {
+            players.push(player_state(2));
+            discs.push(DiscState {
+                index: 0,
+                position: (0.0, 0.0),
+                velocity: (0.0, 0.0),
+            });
+        }
+        game_state(tick, players, discs)
+    }
+
+    /// 10 segundos a 60 Hz con un gol a los 3 segundos
+    fn playback() -> ReplayPlayback {
+        let mut frames = vec![ReplayFrame {
+            time_secs: 0.0,
+            message: ServerMessage::SlotsUpdated(MatchSlots::default()),
+        }];
+        for tick in 1..=600 {
+            let time_secs = tick as f32 / 60.0;
+            frames.push(ReplayFrame {
+                time_secs,
+                message: frame_state(tick),
+            });
+            if tick == 180 {
+                frames.push(ReplayFrame {
+                    time_secs,
+                    message: ServerMessage::ScoreUpdated {
+                        score: [1, 0],
+                        scoring_team: Some(0),
+                    },
+                });
+            }
+        }
+        ReplayPlayback {
+            replay: Some(Replay {
+                game_version: ProtocolVersion::current(),
+                recorded_at: 1_700_000_000,
+                map: None,
+                team_colors: vec![(1.0, 0.0, 0.0), (0.0, 0.0, 1.0)],
+                frames,
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn state_tick(messages: &[ServerMessage]) -> Option<u32> {
+        messages.iter().find_map(|message| match message {
+            ServerMessage::GameState { tick, .. } => Some(*tick),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn seeking_rebuilds_the_state_at_the_target_time() {
+        let mut playback = playback();
+
+        playback.seek(8.0);
+        let messages = playback.resync_messages();
+        assert!(matches!(messages[0], ServerMessage::SlotsUpdated(_)));
+        assert!(matches!(
+            messages[1],
+            ServerMessage::ScoreUpdated {
+                score: [1, 0],
+                scoring_team: None
+            }
+        ));
+        assert_eq!(state_tick(&messages), Some(480));
+
+        // Hacia atrás, antes del gol
+        playback.seek(2.0);
+        let messages = playback.resync_messages();
+        assert!(!messages
+            .iter()
+            .any(|m| matches!(m, ServerMessage::ScoreUpdated { .. })));
+        assert_eq!(state_tick(&messages), Some(120));
+
+        // Y sigue desde ahí
+        playback.time += 1.0 / 60.0;
+        assert_eq!(state_tick(&playback.due_messages()), Some(121));
+        assert!(playback.due_messages().is_empty());
+    }
+
+    #[test]
+    fn seeking_is_clamped_to_the_replay() {
+        let mut playback = playback();
+        playback.seek(-5.0);
+        assert_eq!(playback.time(), 0.0);
+        playback.seek(60.0);
+        assert_eq!(playback.time(), 10.0);
+
+        // Reproducir al final vuelve al inicio
+        playback.paused = true;
+        playback.toggle_pause();
+        assert!(!playback.paused);
+        assert_eq!(playback.time(), 0.0);
+    }
+
+    #[test]
+    fn speed_steps_through_the_available_speeds() {
+        let mut playback = playback();
+        playback.change_speed(1);
+        assert_eq!(playback.speed, 2.0);
+        playback.change_speed(1);
+        playback.change_speed(1);
+        assert_eq!(playback.speed, 4.0);
+        for _ in 0..5 {
+            playback.change_speed(-1);
+        }
+        assert_eq!(playback.speed, 0.25);
+    }
+
+    #[test]
+    fn seeking_despawns_entities_missing_from_the_target_state() {
+        let mut world = World::new();
+        let remote_player = |id| RemotePlayer {
+            id,
+            name: format!("P{}", id),
+            team_index: 0,
+            kick_charge: Vec2::ZERO,
+            is_sliding: false,
+            not_interacting: false,
+            base_color: Color::WHITE,
+            ball_target_position: None,
+            stamin_charge: 1.0,
+            active_movement: None,
+            mode_cube_active: false,
+            server_position: Vec2::ZERO,
+            last_input_sequence: 0,
+        };
+        let kept = world.spawn(remote_player(1)).id();
+        let gone = world.spawn(remote_player(2)).id();
+        let disc = world.spawn(RemoteDisc { index: 0 }).id();
+
+        let mut playback = playback();
+        playback.seek(8.0);
+        let messages = playback.resync_messages();
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      players: Query<(Entity, &RemotePlayer)>,
+                      discs: Query<(Entity, &RemoteDisc)>| {
+                    despawn_missing(&mut commands, &messages, &players, &discs);
+                },
+            )
+            .unwrap();
+
+        assert!(world.get_entity(kept).is_ok());
+        assert!(world.get_entity(gone).is_err());
+        assert!(world.get_entity(disc).is_err());
+    }
+}
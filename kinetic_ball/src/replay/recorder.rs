@@ -0,0 +1,249 @@
+// ============================================================================
+// GRABACIÓN DE REPLAYS
+// ============================================================================
+
+use bevy::prelude::*;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use crate::keybindings::get_config_dir;
+use crate::shared::map::Map;
+use crate::shared::protocol::{GameConfig, ProtocolVersion, ServerMessage};
+use crate::shared::replay::{Replay, ReplayFrame, REPLAY_EXTENSION};
+use crate::shared::MatchSlots;
+
+/// Duración máxima de cada archivo: un partido sin límites se guarda en tramos
+const SEGMENT_SECS: f64 = 10.0 * 60.0;
+
+/// Graba los mensajes del host durante cada partido. La grabación queda lista
+/// para escribirse cuando el partido termina (el reloj informa un ganador), cada
+/// `SEGMENT_SECS` y al salir de la sala
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    /// Mapa de la sala (del primer WELCOME)
+    map: Option<Map>,
+    /// Colores de equipo vigentes
+    team_colors: Vec<(f32, f32, f32)>,
+    /// Últimos slots y marcador, para abrir cada grabación con el estado actual
+    slots: Option<MatchSlots>,
+    score: [u32; 2],
+    /// Jugadores del último GameState grabado
+    on_field: HashSet<u32>,
+    /// Inicio de la grabación en curso (None = no se está grabando)
+    started_at: Option<f64>,
+    replay: Option<Replay>,
+    /// Grabaciones cerradas, pendientes de escribir a disco
+    finished: Vec<Replay>,
+}
+
+impl Default for ReplayRecorder {
+    fn default() -> Self {
+        Self {
+            map: None,
+            team_colors: GameConfig::default().team_colors,
+            slots: None,
+            score: [0, 0],
+            on_field: HashSet::new(),
+            started_at: None,
+            replay: None,
+            finished: Vec::new(),
+        }
+    }
+}
+
+impl ReplayRecorder {
+    /// Registra un mensaje del host (`now` en segundos del reloj de Bevy)
+    pub fn record(&mut self, now: f64, message: &ServerMessage) {
+        match message {
+            ServerMessage::Welcome { map, .. } => {
+                if self.map.is_none() {
+                    self.map = map.clone();
+                }
+                return;
+            }
+            ServerMessage::ChangeTeamColor { team_index, color } => {
+                let index = *team_index as usize;
+                if self.team_colors.len() <= index {
+                    self.team_colors.resize(index + 1, (0.5, 0.5, 0.5));
+                }
+                self.team_colors[index] = *color;
+            }
+            ServerMessage::SlotsUpdated(slots) => self.slots = Some(slots.clone()),
+            ServerMessage::ScoreUpdated { score, .. } => self.score = *score,
+            ServerMessage::PlayerDisconnected { player_id } => {
+                self.on_field.remove(player_id);
+            }
+            ServerMessage::GameState { clock, .. } => match (clock.winner, self.started_at) {
+                // El partido terminó antes de que empezáramos a grabarlo
+                (Some(_), None) => return,
+                (None, None) => self.start(now),
+                _ => {}
+            },
+            _ => {}
+        }
+
+        if !Replay::is_recorded(message) {
+            return;
+        }
+        let (Some(started_at), Some(replay)) = (self.started_at, self.replay.as_mut()) else {
+            return;
+        };
+        let time_secs = (now - started_at) as f32;
+
+        // Jugadores que dejaron de venir en el GameState sin un PlayerDisconnected
+        // (ej. quitados del roster): el replay también los saca de la cancha
+        if let ServerMessage::GameState { players, .. } = message {
+            let on_field: HashSet<u32> = players.iter().map(|p| p.id).collect();
+            let mut removed: Vec<u32> = self.on_field.difference(&on_field).copied().collect();
+            removed.sort_unstable();
+            for player_id in removed {
+                replay.frames.push(ReplayFrame {
+                    time_secs,
+                    message: ServerMessage::PlayerDisconnected { player_id },
+                });
+            }
+            self.on_field = on_field;
+        }
+
+        replay.frames.push(ReplayFrame {
+            time_secs,
+            message: message.clone(),
+        });
+
+        if let ServerMessage::GameState { clock, .. } = message {
+            if clock.winner.is_some() {
+                self.finish();
+            } else if now - started_at >= SEGMENT_SECS {
+                // El tramo siguiente arranca con los slots y el marcador actuales
+                self.finish();
+                self.start(now);
+            }
+        }
+    }
+
+    /// Cierra la grabación en curso para escribirla a disco
+    fn finish(&mut self) {
+        self.started_at = None;
+        if let Some(replay) = self.replay.take() {
+            // Sin ningún GameState no hay nada que ver
+            if replay.duration_secs() > 0.0 {
+                self.finished.push(replay);
+            }
+        }
+    }
+
+    /// Al salir de la sala (o si el host la cierra) se guarda lo grabado hasta ahora;
+    /// el mapa y los colores son de esta sala
+    pub fn reset(&mut self) {
+        self.finish();
+        *self = Self {
+            finished: std::mem::take(&mut self.finished),
+            ..Default::default()
+        };
+    }
+
+    /// Empieza a grabar un partido, con los slots y el marcador actuales como primeros frames
+    fn start(&mut self, now: f64) {
+        let mut frames = Vec::new();
+        if let Some(slots) = &self.slots {
+            frames.push(ReplayFrame {
+                time_secs: 0.0,
+                message: ServerMessage::SlotsUpdated(slots.clone()),
+            });
+        }
+        frames.push(ReplayFrame {
+            time_secs: 0.0,
+            message: ServerMessage::ScoreUpdated {
+                score: self.score,
+                scoring_team: None,
+            },
+        });
+
+        self.started_at = Some(now);
+        self.on_field.clear();
+        self.replay = Some(Replay {
+            game_version: ProtocolVersion::current(),
+            recorded_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            map: self.map.clone(),
+            team_colors: self.team_colors.clone(),
+            frames,
+        });
+        println!("🎬 [Replay] Grabando partido");
+    }
+}
+
+/// Directorio donde se guardan los replays
+pub fn get_replays_dir() -> Option<PathBuf> {
+    get_config_dir().map(|p| p.join("replays"))
+}
+
+fn save_replay(replay: &Replay) -> Result<PathBuf, String> {
+    let dir = get_replays_dir().ok_or("No se pudo determinar directorio de config")?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Error creando directorio de replays: {}", e))?;
+
+    let path = dir.join(format!(
+        "replay_{}.{}",
+        replay.recorded_at, REPLAY_EXTENSION
+    ));
+    let bytes = replay.encode()?;
+    std::fs::write(&path, bytes).map_err(|e| format!("Error escribiendo replay: {}", e))?;
+    Ok(path)
+}
+
+/// Escribe a disco las grabaciones cerradas (en un hilo aparte, comprimir lleva tiempo)
+pub fn save_finished_replay(mut recorder: ResMut<ReplayRecorder>) {
+    for replay in recorder.finished.drain(..) {
+        std::thread::spawn(move || match save_replay(&replay) {
+            Ok(path) => println!(
+                "🎬 [Replay] Partido guardado en {:?} ({} frames, {:.0}s)",
+                path,
+                replay.frames.len(),
+                replay.duration_secs()
+            ),
+            Err(e) => eprintln!("❌ [Replay] {}", e),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::replay::game_state;
+
+    #[test]
+    fn leaving_the_room_keeps_the_match_so_far() {
+        let mut recorder = ReplayRecorder::default();
+        for tick in 0..=60 {
+            recorder.record(tick as f64 / 60.0, &game_state(tick, Vec::new(), Vec::new()));
+        }
+        assert!(recorder.finished.is_empty());
+
+        recorder.reset();
+        assert_eq!(recorder.finished.len(), 1);
+        assert!(recorder.replay.is_none());
+        assert!((recorder.finished[0].duration_secs() - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn long_matches_are_saved_in_segments() {
+        let mut recorder = ReplayRecorder::default();
+        let ticks = (SEGMENT_SECS * 2.5) as u32;
+        for tick in 0..=ticks {
+            recorder.record(tick as f64, &game_state(tick, Vec::new(), Vec::new()));
+        }
+        assert_eq!(recorder.finished.len(), 2);
+        for segment in &recorder.finished {
+            assert!(segment.duration_secs() as f64 <= SEGMENT_SECS);
+        }
+
+        // Cada tramo abre con el marcador vigente
+        let current = recorder.replay.as_ref().unwrap();
+        assert!(matches!(
+            current.frames[0].message,
+            ServerMessage::ScoreUpdated { .. }
+        ));
+    }
+}
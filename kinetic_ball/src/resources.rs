@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use bevy::render::render_resource::AsBindGroup;
 use bevy::shader::ShaderRef;
 use bevy::sprite_render::Material2d;
+use std::collections::{HashMap, VecDeque};
 use std::f32::consts::FRAC_PI_2;
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
@@ -9,7 +10,10 @@ use std::sync::{Arc, Mutex};
 use crate::assets::SPLIT_SCREEN_SHADER_HANDLE;
 use crate::keybindings::AppConfig;
 use crate::shared::match_slots::MatchSlots;
-use crate::shared::protocol::{ControlMessage, PlayerInput, ServerMessage};
+use crate::shared::protocol::{
+    BotDifficulty, ChatKind, ChatMessage, ControlMessage, MatchClockState, MatchRules, PlayerInput,
+    PlayerLatency, ServerMessage,
+};
 use crate::states::RoomInfo;
 
 // ============================================================================
@@ -25,12 +29,68 @@ pub struct AdminPanelState {
     pub is_open: bool,
     /// Whether the local player is an admin (can move players)
     pub is_admin: bool,
+    /// Difficulty of the bots added from the panel
+    pub bot_difficulty: BotDifficulty,
 }
 
 /// Client-side copy of match slots, synchronized from server
 #[derive(Resource, Default)]
 pub struct ClientMatchSlots(pub MatchSlots);
 
+/// Marcador del partido recibido del servidor (índice 0 = red, 1 = blue)
+#[derive(Resource, Default)]
+pub struct ClientMatchScore {
+    pub score: [u32; 2],
+    /// Último gol: (equipo que anotó, tiempo en que se recibió)
+    pub last_goal: Option<(u8, f64)>,
+    /// Reloj y resultado del partido (del último GameState)
+    pub clock: MatchClockState,
+}
+
+/// Mensajes de chat que se guardan en el historial
+const CHAT_HISTORY_LEN: usize = 50;
+
+/// Mensaje de chat recibido y el momento en que llegó (para desvanecerlo)
+pub struct ChatEntry {
+    pub message: ChatMessage,
+    pub received_at: f64,
+}
+
+/// Estado del chat en el juego
+#[derive(Resource)]
+pub struct ChatState {
+    /// Caja de texto abierta (mientras tanto el teclado no mueve al jugador)
+    pub open: bool,
+    pub input: String,
+    /// Audiencia del próximo mensaje
+    pub kind: ChatKind,
+    pub messages: VecDeque<ChatEntry>,
+}
+
+impl Default for ChatState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            input: String::new(),
+            kind: ChatKind::All,
+            messages: VecDeque::new(),
+        }
+    }
+}
+
+impl ChatState {
+    /// Agrega un mensaje al historial descartando los más viejos
+    pub fn push(&mut self, message: ChatMessage, received_at: f64) {
+        self.messages.push_back(ChatEntry {
+            message,
+            received_at,
+        });
+        while self.messages.len() > CHAT_HISTORY_LEN {
+            self.messages.pop_front();
+        }
+    }
+}
+
 /// Solicitud para salir de la sala (se procesa en cleanup)
 #[derive(Resource, Default)]
 pub struct LeaveRoomRequest {
@@ -47,11 +107,11 @@ pub struct PreviousInput(pub PlayerInput);
 // ============================================================================
 
 /// Canal de comunicación con el thread de red
-/// El sender ahora envía (player_id, PlayerInput) para soportar múltiples jugadores locales
+/// El sender envía (player_id, secuencia, PlayerInput) para soportar múltiples jugadores locales
 #[derive(Resource, Default)]
 pub struct NetworkChannels {
     pub receiver: Option<Arc<Mutex<mpsc::Receiver<ServerMessage>>>>,
-    pub sender: Option<mpsc::Sender<(u32, PlayerInput)>>,
+    pub sender: Option<mpsc::Sender<(u32, u32, PlayerInput)>>,
     /// Canal para enviar mensajes de control (Leave, etc.)
     pub control_sender: Option<mpsc::Sender<ControlMessage>>,
 }
@@ -59,6 +119,116 @@ pub struct NetworkChannels {
 #[derive(Resource)]
 pub struct MyPlayerId(pub Option<u32>);
 
+// ============================================================================
+// CLIENT-SIDE PREDICTION
+// ============================================================================
+
+/// Inputs sin confirmar que se guardan por jugador (~2 s a 64 Hz)
+const MAX_PENDING_INPUTS: usize = 128;
+
+/// Input enviado al host que todavía no fue confirmado en un GameState
+pub struct PendingInput {
+    pub sequence: u32,
+    pub input: PlayerInput,
+    /// Duración del paso fijo en que se aplicó
+    pub dt: f32,
+}
+
+/// Predicción de los jugadores locales: numera los inputs enviados y guarda los
+/// que el host todavía no procesó para re-aplicarlos sobre cada snapshot
+#[derive(Resource)]
+pub struct ClientPrediction {
+    next_sequence: u32,
+    pub pending: HashMap<u32, VecDeque<PendingInput>>,
+}
+
+impl Default for ClientPrediction {
+    fn default() -> Self {
+        Self {
+            // El host arranca con la secuencia 0 como "ningún input"
+            next_sequence: 1,
+            pending: HashMap::new(),
+        }
+    }
+}
+
+impl ClientPrediction {
+    /// Registra un input a enviar y devuelve su número de secuencia
+    pub fn record(&mut self, player_id: u32, input: PlayerInput, dt: f32) -> u32 {
+        let sequence = self.next_sequence;
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+
+        let pending = self.pending.entry(player_id).or_default();
+        pending.push_back(PendingInput {
+            sequence,
+            input,
+            dt,
+        });
+        // Si el host no confirma (p. ej. el jugador no es titular) no acumular sin límite
+        while pending.len() > MAX_PENDING_INPUTS {
+            pending.pop_front();
+        }
+        sequence
+    }
+}
+
+/// Reloj para dibujar las entidades remotas: estima la diferencia entre el reloj
+/// del host (timestamp de cada GameState) y el local, y la usa para muestrear los
+/// `SnapshotBuffer` con un retardo fijo
+#[derive(Resource)]
+pub struct SnapshotClock {
+    /// timestamp del host - reloj local (ms); None hasta el primer GameState
+    offset_ms: Option<f64>,
+    pub delay_ms: f64,
+}
+
+impl SnapshotClock {
+    pub fn new(delay_ms: u32) -> Self {
+        Self {
+            offset_ms: None,
+            delay_ms: delay_ms as f64,
+        }
+    }
+
+    /// Registra la llegada de un GameState. Los paquetes demorados dan un offset
+    /// menor, así que se sigue rápido al mayor y se baja despacio (deriva de relojes)
+    pub fn observe(&mut self, server_time: f64, local_ms: f64) {
+        let sample = server_time - local_ms;
+        self.offset_ms = Some(match self.offset_ms {
+            None => sample,
+            Some(offset) if sample > offset => offset + (sample - offset) * 0.5,
+            Some(offset) => offset + (sample - offset) * 0.01,
+        });
+    }
+
+    /// Instante del host que hay que dibujar ahora
+    pub fn render_time(&self, local_ms: f64) -> Option<f64> {
+        self.offset_ms
+            .map(|offset| local_ms + offset - self.delay_ms)
+    }
+
+    /// Fija el instante del host que corresponde a `local_ms` (en los replays el
+    /// tiempo lo decide la reproducción, no la llegada de paquetes)
+    pub fn sync_to(&mut self, server_time: f64, local_ms: f64) {
+        self.offset_ms = Some(server_time - local_ms);
+    }
+
+    /// Olvida el offset (nueva conexión, otro host)
+    pub fn reset(&mut self) {
+        self.offset_ms = None;
+    }
+}
+
+/// Latencia de cada jugador de la sala, medida y enviada por el host
+#[derive(Resource, Default)]
+pub struct PlayerLatencies(pub HashMap<u32, PlayerLatency>);
+
+impl PlayerLatencies {
+    pub fn rtt_ms(&self, player_id: u32) -> Option<u16> {
+        self.0.get(&player_id).map(|l| l.rtt_ms)
+    }
+}
+
 // ============================================================================
 // MAP RESOURCES
 // ============================================================================
@@ -93,6 +263,10 @@ pub struct RoomList {
     pub filter_show_available: bool,
     // Conexión directa por UUID
     pub direct_connect_id: String,
+    pub direct_connect_password: String,
+    // Sala con contraseña esperando que el usuario la ingrese
+    pub password_prompt_room: Option<String>,
+    pub password_input: String,
 }
 
 impl Default for RoomList {
@@ -106,6 +280,9 @@ impl Default for RoomList {
             filter_show_full: true,
             filter_show_available: true,
             direct_connect_id: String::new(),
+            direct_connect_password: String::new(),
+            password_prompt_room: None,
+            password_input: String::new(),
         }
     }
 }
@@ -126,6 +303,14 @@ pub struct CreateRoomConfig {
     pub max_players: u8,
     pub map_path: String,
     pub scale: f32,
+    /// Duración del partido en minutos (0 = sin límite)
+    pub time_limit_minutes: u32,
+    /// Goles para ganar (0 = sin límite)
+    pub score_limit: u32,
+    /// Contraseña de la sala (vacía = sin contraseña)
+    pub password: String,
+    /// Sala privada: no aparece en la lista del proxy
+    pub private: bool,
     pub created_room_ids: Vec<String>,
 }
 
@@ -136,11 +321,29 @@ impl Default for CreateRoomConfig {
             max_players: 4,
             map_path: String::new(), // Vacío = usar mapa embebido por defecto
             scale: 1.0,
+            time_limit_minutes: 3,
+            score_limit: 3,
+            password: String::new(),
+            private: false,
             created_room_ids: Vec::new(),
         }
     }
 }
 
+impl CreateRoomConfig {
+    /// Ruta del mapa elegido (None = mapa embebido por defecto)
+    pub fn map(&self) -> Option<String> {
+        Some(self.map_path.clone()).filter(|p| !p.is_empty())
+    }
+
+    pub fn rules(&self) -> MatchRules {
+        MatchRules {
+            time_limit_secs: self.time_limit_minutes * 60,
+            score_limit: self.score_limit,
+        }
+    }
+}
+
 // ============================================================================
 // CONNECTION CONFIG
 // ============================================================================
@@ -149,7 +352,15 @@ impl Default for CreateRoomConfig {
 pub struct ConnectionConfig {
     pub server_host: String, // Host sin protocolo: localhost:3536 o api.example.com
     pub room: String,
+    /// Contraseña para entrar a la sala (si la sala la requiere)
+    pub room_password: Option<String>,
     pub player_name: String,
+    /// Práctica sin conexión: el host corre en este proceso, sin proxy
+    pub offline: bool,
+    /// Partida en red local: UDP directo al host en `lan_host`, sin proxy
+    pub lan: bool,
+    /// Dirección del host en la red local (ej. 192.168.0.10:7777)
+    pub lan_host: String,
 }
 
 impl ConnectionConfig {
@@ -160,7 +371,14 @@ impl ConnectionConfig {
                 .clone()
                 .unwrap_or_else(|| app_config.server.clone()),
             room: args.room.clone(),
+            room_password: None,
             player_name: args.name.clone(),
+            offline: false,
+            lan: false,
+            lan_host: args
+                .lan
+                .clone()
+                .unwrap_or_else(|| "127.0.0.1:7777".to_string()),
         }
     }
 
@@ -247,3 +465,45 @@ impl Material2d for SplitScreenMaterial {
         SPLIT_SCREEN_SHADER_HANDLE.into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_clock_needs_a_first_observation() {
+        let mut clock = SnapshotClock::new(100);
+        assert_eq!(clock.render_time(0.0), None);
+
+        clock.observe(5_000.0, 1_000.0);
+        assert_eq!(clock.render_time(1_000.0), Some(4_900.0));
+        assert_eq!(clock.render_time(1_016.0), Some(4_916.0));
+
+        clock.reset();
+        assert_eq!(clock.render_time(1_000.0), None);
+    }
+
+    #[test]
+    fn test_late_packets_barely_move_the_offset() {
+        let mut clock = SnapshotClock::new(0);
+        clock.observe(5_000.0, 1_000.0);
+
+        // Un paquete que llegó 200 ms tarde
+        clock.observe(5_000.0, 1_200.0);
+        let offset = clock.render_time(0.0).unwrap();
+        assert!((offset - 3_998.0).abs() < 1e-6);
+
+        // Uno que llegó antes de lo esperado se sigue rápido
+        clock.observe(5_400.0, 1_200.0);
+        let offset = clock.render_time(0.0).unwrap();
+        assert!((offset - 4_099.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sync_to_overrides_the_estimate() {
+        let mut clock = SnapshotClock::new(100);
+        clock.observe(5_000.0, 1_000.0);
+        clock.sync_to(20_000.0, 2_000.0);
+        assert_eq!(clock.render_time(2_000.0), Some(19_900.0));
+    }
+}
@@ -0,0 +1,142 @@
+// ============================================================================
+// MIGRACIÓN DE HOST - ESTADO REPLICADO AL SUCESOR
+// ============================================================================
+//
+// El host envía periódicamente el estado autoritativo del partido a un sucesor.
+// Si el host se va, el sucesor levanta su propio host con este estado y toma la
+// sala en el proxy; los clientes vuelven con sus tokens de reconexión (el sucesor
+// solo conoce sus hashes).
+
+use serde::{Deserialize, Serialize};
+
+use super::match_slots::MatchSlots;
+use super::protocol::{GameConfig, MatchRules};
+
+/// Cada cuánto el host envía el estado a su sucesor
+pub const HANDOFF_INTERVAL_SECS: f32 = 1.0;
+
+/// Un estado más viejo que esto no alcanza para tomar la sala (el host eligió otro sucesor)
+pub const HANDOFF_STALE_SECS: f64 = 3.0;
+
+/// Fase del partido en el host
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum HandoffPhase {
+    PreKickoff,
+    Kickoff { kicking_team: u8 },
+    Playing,
+    GoalCelebration { timer: f32, conceding_team: u8 },
+    Ended { timer: f32, winner: u8 },
+}
+
+/// Posición y velocidad de un cuerpo (pelota o disco móvil)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct HandoffBody {
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+    pub angular_velocity: f32,
+}
+
+/// Jugador tal como lo tiene el host
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HandoffPlayer {
+    pub id: u32,
+    pub name: String,
+    pub team_index: u8,
+    pub body: HandoffBody,
+    pub stamina: f32,
+}
+
+/// Todo lo que el sucesor necesita para seguir el partido donde quedó
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HostHandoff {
+    pub room_id: String,
+    pub room_name: String,
+    pub max_players: u8,
+    pub map_name: Option<String>,
+    /// Token de toma que el proxy emitió para este sucesor: solo sirve para
+    /// tomar la sala una vez que el host se fue
+    pub takeover_token: String,
+    pub config: GameConfig,
+    pub rules: MatchRules,
+    pub tick: u32,
+    pub next_player_id: u32,
+    /// Sesiones de reconexión vigentes (hash del token, player_id): el sucesor
+    /// reconoce a cada cliente que vuelve, pero no recibe los tokens
+    pub sessions: Vec<(u64, u32)>,
+    pub slots: MatchSlots,
+    pub score: [u32; 2],
+    pub clock_elapsed_secs: f32,
+    pub overtime: bool,
+    pub phase: HandoffPhase,
+    pub players: Vec<HandoffPlayer>,
+    pub ball: HandoffBody,
+    /// Discos móviles del mapa (índice en `Map::discs`, cuerpo)
+    pub discs: Vec<(u32, HandoffBody)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::protocol::ControlMessage;
+
+    #[test]
+    fn handoff_roundtrips_through_bincode() {
+        let body = HandoffBody {
+            position: (10.0, -5.0),
+            velocity: (1.0, 2.0),
+            angular_velocity: 0.5,
+        };
+        let mut slots = MatchSlots::default();
+        slots.add_starter(1, 0);
+        slots.add_admin(1);
+        let handoff = HostHandoff {
+            room_id: "room_1".into(),
+            room_name: "Sala".into(),
+            max_players: 8,
+            map_name: None,
+            takeover_token: "token".into(),
+            config: GameConfig::default(),
+            rules: MatchRules {
+                time_limit_secs: 300,
+                score_limit: 3,
+            },
+            tick: 42,
+            next_player_id: 2,
+            sessions: vec![(7, 1)],
+            slots,
+            score: [1, 0],
+            clock_elapsed_secs: 12.5,
+            overtime: false,
+            phase: HandoffPhase::GoalCelebration {
+                timer: 1.5,
+                conceding_team: 1,
+            },
+            players: vec![HandoffPlayer {
+                id: 1,
+                name: "Uno".into(),
+                team_index: 0,
+                body,
+                stamina: 0.8,
+            }],
+            ball: body,
+            discs: vec![(3, body)],
+        };
+
+        let data = bincode::serialize(&ControlMessage::HostHandoff(Box::new(handoff))).unwrap();
+        let ControlMessage::HostHandoff(decoded) = bincode::deserialize(&data).unwrap() else {
+            panic!("expected a HostHandoff");
+        };
+        assert_eq!(decoded.tick, 42);
+        assert_eq!(decoded.sessions, vec![(7, 1)]);
+        assert!(decoded.slots.is_admin(1));
+        assert_eq!(decoded.players[0].body, body);
+        assert_eq!(decoded.discs, vec![(3, body)]);
+        assert_eq!(
+            decoded.phase,
+            HandoffPhase::GoalCelebration {
+                timer: 1.5,
+                conceding_team: 1
+            }
+        );
+    }
+}
@@ -1,5 +1,19 @@
+use super::protocol::GameConfig;
 use serde::{Deserialize, Serialize};
 
+/// Ticks por segundo de la simulación de HaxBall (sus velocidades son por tick)
+const HAXBALL_TICK_RATE: f32 = 60.0;
+/// kickStrength por defecto de HaxBall (equivale a nuestro kick_force por defecto)
+const HAXBALL_DEFAULT_KICK_STRENGTH: f32 = 5.0;
+/// invMass por defecto de la pelota en HaxBall (equivale a nuestro ball_mass por defecto)
+const HAXBALL_DEFAULT_BALL_INV_MASS: f32 = 1.0;
+/// Damping por defecto de los jugadores en HaxBall
+const HAXBALL_DEFAULT_PLAYER_DAMPING: f32 = 0.96;
+
+/// Escalas de mapa que se pueden elegir al crear una sala (y con `--scale`)
+pub const MIN_MAP_SCALE: f32 = 0.5;
+pub const MAX_MAP_SCALE: f32 = 2.0;
+
 /// Mapa completo de HaxBall
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Map {
@@ -18,6 +32,25 @@ pub struct Map {
     pub discs: Vec<Disc>,
     #[serde(default)]
     pub goals: Vec<Goal>,
+    #[serde(default)]
+    pub planes: Vec<Plane>,
+    #[serde(default)]
+    pub joints: Vec<Joint>,
+    #[serde(default)]
+    #[serde(rename = "playerPhysics")]
+    pub player_physics: Option<PlayerPhysics>,
+    #[serde(default)]
+    #[serde(rename = "ballPhysics")]
+    pub ball_physics: Option<BallPhysics>,
+    #[serde(default)]
+    #[serde(rename = "spawnDistance")]
+    pub spawn_distance: Option<f32>,
+    #[serde(default)]
+    #[serde(rename = "redSpawnPoints")]
+    pub red_spawn_points: Vec<[f32; 2]>,
+    #[serde(default)]
+    #[serde(rename = "blueSpawnPoints")]
+    pub blue_spawn_points: Vec<[f32; 2]>,
 }
 
 impl Map {
@@ -60,6 +93,8 @@ impl Map {
             disc.pos[0] *= factor;
             disc.pos[1] *= factor;
             disc.radius *= factor;
+            disc.speed[0] *= factor;
+            disc.speed[1] *= factor;
         }
 
         // Escalar posiciones de goles
@@ -70,8 +105,118 @@ impl Map {
             goal.p1[1] *= factor;
         }
 
+        // Escalar planos (distancia al origen)
+        for plane in &mut self.planes {
+            plane.dist *= factor;
+        }
+
+        // Escalar largo de joints
+        for joint in &mut self.joints {
+            if let Some(length) = &mut joint.length {
+                length[0] *= factor;
+                length[1] *= factor;
+            }
+        }
+
+        // Escalar física (radios y velocidades en unidades del mapa)
+        if let Some(pp) = &mut self.player_physics {
+            pp.radius = pp.radius.map(|r| r * factor);
+            pp.acceleration = pp.acceleration.map(|a| a * factor);
+            pp.kick_strength = pp.kick_strength.map(|k| k * factor);
+        }
+        if let Some(bp) = &mut self.ball_physics {
+            bp.radius = bp.radius.map(|r| r * factor);
+        }
+        if let Some(d) = self.spawn_distance {
+            self.spawn_distance = Some(d * factor);
+        }
+        for point in self
+            .red_spawn_points
+            .iter_mut()
+            .chain(self.blue_spawn_points.iter_mut())
+        {
+            point[0] *= factor;
+            point[1] *= factor;
+        }
+
         println!("✨ Mapa escalado por factor {}", factor);
     }
+
+    /// Puntos de aparición definidos por el mapa para un equipo (0 = red, 1 = blue)
+    pub fn spawn_points(&self, team_index: u8) -> &[[f32; 2]] {
+        if team_index == 0 {
+            &self.red_spawn_points
+        } else {
+            &self.blue_spawn_points
+        }
+    }
+
+    /// Aplicar `playerPhysics` y `ballPhysics` del mapa sobre la configuración del juego.
+    /// Los valores de HaxBall son por tick; se convierten a nuestras unidades por segundo.
+    pub fn apply_physics(&self, config: &mut GameConfig) {
+        let defaults = GameConfig::default();
+
+        if let Some(pp) = &self.player_physics {
+            if let Some(radius) = pp.radius {
+                config.sphere_radius = radius;
+            }
+            if let Some(b_coef) = pp.b_coef {
+                config.sphere_restitution = b_coef;
+            }
+            if let Some(linear) = pp.damping.and_then(haxball_damping_to_linear) {
+                config.sphere_linear_damping = linear;
+            }
+            if let Some(acceleration) = pp.acceleration {
+                // Velocidad terminal de HaxBall: a * d / (1 - d) por tick
+                let damping = pp.damping.unwrap_or(HAXBALL_DEFAULT_PLAYER_DAMPING);
+                if damping < 1.0 {
+                    config.player_speed_walking =
+                        acceleration * damping / (1.0 - damping) * HAXBALL_TICK_RATE;
+                }
+            }
+            if let Some(kick_strength) = pp.kick_strength {
+                config.kick_force =
+                    defaults.kick_force * kick_strength / HAXBALL_DEFAULT_KICK_STRENGTH;
+            }
+        }
+
+        if let Some(bp) = &self.ball_physics {
+            if let Some(radius) = bp.radius {
+                config.ball_radius = radius;
+            }
+            if let Some(b_coef) = bp.b_coef {
+                config.ball_restitution = b_coef;
+            }
+            if let Some(inv_mass) = bp.inv_mass.filter(|m| *m > 0.0) {
+                config.ball_mass = defaults.ball_mass * HAXBALL_DEFAULT_BALL_INV_MASS / inv_mass;
+            }
+            if let Some(linear) = bp.damping.and_then(haxball_damping_to_linear) {
+                config.ball_linear_damping = linear;
+            }
+        }
+
+        if self.player_physics.is_some() || self.ball_physics.is_some() {
+            println!(
+                "⚙️  Física del mapa aplicada: jugador r={:.1} v={:.0}, pelota r={:.1} m={:.3}",
+                config.sphere_radius,
+                config.player_speed_walking,
+                config.ball_radius,
+                config.ball_mass
+            );
+        }
+    }
+}
+
+/// Convierte el damping de HaxBall (multiplicador de velocidad por tick)
+/// al damping lineal de Rapier: v *= 1 / (1 + dt * d)
+fn haxball_damping_to_linear(damping: f32) -> Option<f32> {
+    if damping <= 0.0 {
+        return None;
+    }
+    if damping >= 1.0 {
+        return Some(0.0);
+    }
+    Some(HAXBALL_TICK_RATE * (1.0 / damping - 1.0))
 }
 
 /// Configuración del fondo (opcional, para futura renderización del cliente)
@@ -134,11 +279,19 @@ impl Segment {
     }
 }
 
-/// Disco: objeto circular estático
+/// Disco: objeto circular
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Disc {
     pub pos: [f32; 2], // Posición [x, y]
+    #[serde(default = "default_disc_radius")]
     pub radius: f32,
+    #[serde(default)]
+    #[serde(rename = "invMass")]
+    pub inv_mass: f32, // Inversa de la masa (0 = inamovible)
+    #[serde(default = "default_damping")]
+    pub damping: f32, // Multiplicador de velocidad por tick
+    #[serde(default)]
+    pub speed: [f32; 2], // Velocidad inicial por tick
     #[serde(default = "default_bcoef")]
     #[serde(rename = "bCoef")]
     pub b_coef: f32,
@@ -152,7 +305,27 @@ pub struct Disc {
     pub color: Option<String>,
 }
 
-/// Gol: línea de gol para detección de puntuación (futura implementación)
+impl Disc {
+    /// Retorna true si el disco se mueve (invMass > 0); si no, es un obstáculo fijo
+    pub fn is_dynamic(&self) -> bool {
+        self.inv_mass > 0.0
+    }
+
+    /// Velocidad inicial en unidades por segundo
+    pub fn initial_velocity(&self) -> [f32; 2] {
+        [
+            self.speed[0] * HAXBALL_TICK_RATE,
+            self.speed[1] * HAXBALL_TICK_RATE,
+        ]
+    }
+
+    /// Damping lineal de Rapier equivalente al damping de HaxBall
+    pub fn linear_damping(&self) -> f32 {
+        haxball_damping_to_linear(self.damping).unwrap_or(0.0)
+    }
+}
+
+/// Gol: línea de gol para detección de puntuación
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Goal {
     pub p0: [f32; 2],
@@ -160,6 +333,123 @@ pub struct Goal {
     pub team: String, // "red" o "blue"
 }
 
+impl Goal {
+    /// Índice del equipo que defiende este arco (0 = red, 1 = blue)
+    pub fn defending_team(&self) -> Option<u8> {
+        match self.team.as_str() {
+            "red" => Some(0),
+            "blue" => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Índice del equipo que anota cuando la pelota entra en este arco
+    pub fn scoring_team(&self) -> Option<u8> {
+        self.defending_team().map(|team| 1 - team)
+    }
+
+    /// Retorna true si un círculo (centro, radio) cruzó completamente la línea de gol.
+    /// El lado "dentro de la cancha" es el que contiene al centro del campo (0, 0).
+    pub fn is_fully_crossed(&self, center: [f32; 2], radius: f32) -> bool {
+        let (ax, ay) = (self.p0[0], self.p0[1]);
+        let (dx, dy) = (self.p1[0] - ax, self.p1[1] - ay);
+        let len = (dx * dx + dy * dy).sqrt();
+        if len < f32::EPSILON {
+            return false;
+        }
+
+        // Proyección del centro sobre la línea: debe caer entre los postes
+        let t = ((center[0] - ax) * dx + (center[1] - ay) * dy) / (len * len);
+        if !(0.0..=1.0).contains(&t) {
+            return false;
+        }
+
+        // Distancia con signo a la línea (positivo = lado del centro del campo)
+        let signed = |px: f32, py: f32| (dx * (py - ay) - dy * (px - ax)) / len;
+        let field_side = signed(0.0, 0.0).signum();
+        let ball_dist = signed(center[0], center[1]) * field_side;
+
+        ball_dist < -radius
+    }
+}
+
+/// Plano: pared infinita. Los objetos quedan del lado `pos · normal >= dist`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Plane {
+    pub normal: [f32; 2],
+    pub dist: f32,
+    #[serde(default = "default_bcoef")]
+    #[serde(rename = "bCoef")]
+    pub b_coef: f32,
+    #[serde(default)]
+    #[serde(rename = "cMask")]
+    pub c_mask: Option<Vec<String>>,
+    #[serde(default)]
+    #[serde(rename = "cGroup")]
+    pub c_group: Option<Vec<String>>,
+}
+
+/// Joint: une dos discos. El índice 0 es la pelota; el disco `i` del mapa es el índice `i + 1`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Joint {
+    pub d0: usize,
+    pub d1: usize,
+    /// Largo [mínimo, máximo] (None = distancia inicial entre los discos)
+    #[serde(default)]
+    pub length: Option<[f32; 2]>,
+    /// Rigidez del resorte (None = rígido)
+    #[serde(default)]
+    pub strength: Option<f32>,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// Física de los jugadores definida por el mapa (`playerPhysics`)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct PlayerPhysics {
+    #[serde(default)]
+    #[serde(rename = "bCoef")]
+    pub b_coef: Option<f32>,
+    #[serde(default)]
+    #[serde(rename = "invMass")]
+    pub inv_mass: Option<f32>,
+    #[serde(default)]
+    pub damping: Option<f32>,
+    #[serde(default)]
+    pub radius: Option<f32>,
+    #[serde(default)]
+    pub acceleration: Option<f32>,
+    #[serde(default)]
+    #[serde(rename = "kickingAcceleration")]
+    pub kicking_acceleration: Option<f32>,
+    #[serde(default)]
+    #[serde(rename = "kickStrength")]
+    pub kick_strength: Option<f32>,
+}
+
+/// Física de la pelota definida por el mapa (`ballPhysics`)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BallPhysics {
+    #[serde(default)]
+    pub radius: Option<f32>,
+    #[serde(default)]
+    #[serde(rename = "bCoef")]
+    pub b_coef: Option<f32>,
+    #[serde(default)]
+    #[serde(rename = "invMass")]
+    pub inv_mass: Option<f32>,
+    #[serde(default)]
+    pub damping: Option<f32>,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    #[serde(rename = "cMask")]
+    pub c_mask: Option<Vec<String>>,
+    #[serde(default)]
+    #[serde(rename = "cGroup")]
+    pub c_group: Option<Vec<String>>,
+}
+
 /// Configuración para aproximación de curvas
 #[derive(Debug, Clone)]
 pub struct CurveConfig {
@@ -177,3 +467,60 @@ impl Default for CurveConfig {
 fn default_bcoef() -> f32 {
     1.0 // Coeficiente de rebote por defecto
 }
+
+fn default_disc_radius() -> f32 {
+    10.0 // Radio por defecto de HaxBall
+}
+
+fn default_damping() -> f32 {
+    0.99 // Damping por defecto de los discos en HaxBall
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal(p0: [f32; 2], p1: [f32; 2], team: &str) -> Goal {
+        Goal {
+            p0,
+            p1,
+            team: team.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_ball_must_fully_cross_the_goal_line() {
+        let red = goal([-700.0, -100.0], [-700.0, 100.0], "red");
+        let radius = 10.0;
+
+        assert!(!red.is_fully_crossed([-690.0, 0.0], radius));
+        // Sobre la línea o justo tocándola desde adentro del arco: todavía no es gol
+        assert!(!red.is_fully_crossed([-705.0, 0.0], radius));
+        assert!(!red.is_fully_crossed([-710.0, 0.0], radius));
+        assert!(red.is_fully_crossed([-710.1, 0.0], radius));
+        assert!(red.is_fully_crossed([-750.0, 99.0], radius));
+    }
+
+    #[test]
+    fn test_goal_line_ignores_balls_outside_the_posts() {
+        let red = goal([-700.0, -100.0], [-700.0, 100.0], "red");
+        assert!(!red.is_fully_crossed([-750.0, 100.5], 10.0));
+        assert!(!red.is_fully_crossed([-750.0, -150.0], 10.0));
+
+        let degenerate = goal([-700.0, 0.0], [-700.0, 0.0], "red");
+        assert!(!degenerate.is_fully_crossed([-750.0, 0.0], 10.0));
+    }
+
+    #[test]
+    fn test_goal_side_does_not_depend_on_vertex_order() {
+        let blue = goal([700.0, 100.0], [700.0, -100.0], "blue");
+        let reversed = goal([700.0, -100.0], [700.0, 100.0], "blue");
+        for g in [&blue, &reversed] {
+            assert!(g.is_fully_crossed([711.0, 0.0], 10.0));
+            assert!(!g.is_fully_crossed([689.0, 0.0], 10.0));
+        }
+        assert_eq!(blue.defending_team(), Some(1));
+        assert_eq!(blue.scoring_team(), Some(0));
+        assert_eq!(goal([0.0, 0.0], [0.0, 1.0], "spectators").scoring_team(), None);
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+use super::protocol::BotDifficulty;
 
 #[derive(Default, Clone, Serialize, Deserialize, Debug)]
 pub struct Team {
@@ -12,6 +14,8 @@ pub struct MatchSlots {
     pub teams: [Team; 2],
     pub spectators: HashSet<u32>,
     pub admins: HashSet<u32>,
+    /// Players controlled by the host's AI, with their difficulty
+    pub bots: HashMap<u32, BotDifficulty>,
 }
 
 impl MatchSlots {
@@ -88,6 +92,16 @@ impl MatchSlots {
         self.admins.insert(player_id);
     }
 
+    /// Marks a player as a bot (it still needs a slot)
+    pub fn add_bot(&mut self, player_id: u32, difficulty: BotDifficulty) {
+        self.bots.insert(player_id, difficulty);
+    }
+
+    /// Checks if a player is a bot
+    pub fn is_bot(&self, player_id: u32) -> bool {
+        self.bots.contains_key(&player_id)
+    }
+
     /// Checks if a player is a starter (on field)
     pub fn is_starter(&self, player_id: u32) -> bool {
         self.teams
@@ -120,6 +134,26 @@ impl MatchSlots {
     pub fn get_team_index(&self, player_id: u32) -> Option<u8> {
         self.find_player(player_id).0
     }
+
+    /// Position of a starter within their team, ordered by player ID.
+    /// Used to pick a stable spawn point for each starter.
+    pub fn starter_slot(&self, player_id: u32) -> Option<usize> {
+        let team_index = self.get_team_index(player_id)?;
+        let team = &self.teams[team_index as usize];
+        if !team.starters.contains(&player_id) {
+            return None;
+        }
+        Some(team.starters.iter().filter(|id| **id < player_id).count())
+    }
+
+    /// Team with fewer starters (red on ties), used to balance new players
+    pub fn team_with_fewer_starters(&self) -> u8 {
+        if self.teams[1].starters.len() < self.teams[0].starters.len() {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 #[cfg(test)]
@@ -162,4 +196,38 @@ mod tests {
         assert!(slots.is_spectator(1));
         assert!(!slots.is_starter(1));
     }
+
+    #[test]
+    fn test_starter_slot_ordered_by_id() {
+        let mut slots = MatchSlots::default();
+        slots.add_starter(5, 0);
+        slots.add_starter(2, 0);
+        slots.add_starter(3, 1);
+        slots.move_player(4, Some(0), Some(false));
+        assert_eq!(slots.starter_slot(2), Some(0));
+        assert_eq!(slots.starter_slot(5), Some(1));
+        assert_eq!(slots.starter_slot(3), Some(0));
+        assert_eq!(slots.starter_slot(4), None);
+    }
+
+    #[test]
+    fn test_team_with_fewer_starters() {
+        let mut slots = MatchSlots::default();
+        assert_eq!(slots.team_with_fewer_starters(), 0);
+        slots.add_starter(1, 0);
+        assert_eq!(slots.team_with_fewer_starters(), 1);
+        slots.add_starter(2, 1);
+        assert_eq!(slots.team_with_fewer_starters(), 0);
+    }
+
+    #[test]
+    fn test_bot_stays_a_bot_when_moved() {
+        let mut slots = MatchSlots::default();
+        slots.add_bot(7, BotDifficulty::Hard);
+        slots.add_starter(7, 1);
+        slots.move_player(7, Some(0), Some(false));
+        assert!(slots.is_bot(7));
+        assert_eq!(slots.bots.get(&7), Some(&BotDifficulty::Hard));
+        assert!(!slots.is_bot(8));
+    }
 }
@@ -1,7 +1,10 @@
+pub mod handoff;
 pub mod map;
 pub mod match_slots;
 pub mod movements;
 pub mod protocol;
+pub mod replay;
+pub mod snapshot;
 
 pub use match_slots::MatchSlots;
 pub use protocol::*;
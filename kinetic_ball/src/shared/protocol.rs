@@ -1,5 +1,7 @@
+use super::handoff::HostHandoff;
 use super::map::Map;
 use super::match_slots::MatchSlots;
+use super::snapshot::{PlayerInfo, SnapshotDelta};
 use bevy::{
     math::UVec2,
     prelude::{Component, Vec2},
@@ -69,6 +71,10 @@ pub enum ControlMessage {
         /// Versión del cliente (opcional para compatibilidad con clientes antiguos)
         #[serde(default)]
         client_version: Option<ProtocolVersion>,
+        /// Token del WELCOME anterior: si el período de gracia sigue vigente,
+        /// el host devuelve el mismo player_id, lugar y permisos de admin
+        #[serde(default)]
+        reconnect_token: Option<u64>,
     },
     Ready,
     /// El cliente notifica que está saliendo de la sala
@@ -80,6 +86,8 @@ pub enum ControlMessage {
     Welcome {
         player_id: u32,
         map: Option<Map>,
+        /// Para recuperar este jugador si se corta la conexión (ver `Join`)
+        reconnect_token: u64,
     },
     PlayerDisconnected {
         player_id: u32,
@@ -114,6 +122,11 @@ pub enum ControlMessage {
     KickPlayer {
         player_id: u32,
     },
+    /// Sent by the host to the peer whose player was kicked; its reconnect token
+    /// no longer works
+    Kicked {
+        player_id: u32,
+    },
     /// Toggle admin status for a player
     ToggleAdmin {
         player_id: u32,
@@ -121,6 +134,96 @@ pub enum ControlMessage {
     },
     /// Slots updated - sent by server when players are moved between slots
     SlotsUpdated(MatchSlots),
+    /// Score updated - sent by server when a goal is scored and to players that just joined
+    ScoreUpdated {
+        /// Goals per team [red, blue]
+        score: [u32; 2],
+        /// Team that just scored (None = plain sync, no new goal)
+        scoring_team: Option<u8>,
+    },
+    /// Chat message from a client (the host validates and relays it)
+    SendChat {
+        player_id: u32,
+        kind: ChatKind,
+        text: String,
+    },
+    /// Chat message relayed by the host
+    Chat(ChatMessage),
+    /// Name and team of every player on the field; sent whenever it changes so
+    /// snapshots don't carry them
+    PlayerRoster(Vec<PlayerInfo>),
+    /// Round-trip time of every player, measured by the host
+    LatencyUpdated(Vec<PlayerLatency>),
+    /// The client can take over as host if the current one leaves
+    /// (not sent by clients running in the host's own process)
+    CanHost,
+    /// Authoritative match state, sent periodically to the designated successor
+    HostHandoff(Box<HostHandoff>),
+    /// Admin asks every client to show an instant replay of the last seconds
+    RequestReplay,
+    /// Show an instant replay now (relayed by the host after checking the admin)
+    InstantReplay,
+    /// Admin adds a bot (AI-controlled player) as a starter on a team
+    AddBot {
+        team_index: u8,
+        difficulty: BotDifficulty,
+    },
+    /// Admin removes a bot from the room
+    RemoveBot {
+        player_id: u32,
+    },
+}
+
+/// Maximum chat message length (in characters)
+pub const CHAT_MAX_LEN: usize = 140;
+
+/// Audience of a chat message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChatKind {
+    /// Everyone in the room
+    All,
+    /// Only the sender's team (red/blue)
+    Team,
+    /// Admin announcement to everyone
+    Announcement,
+    /// Host notice (rate limit, permission errors, ...)
+    System,
+}
+
+/// Skill level of a bot: reaction time, aim and which moves it uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum BotDifficulty {
+    /// Slow to react, walks, plain kicks
+    Easy,
+    /// Sprints and curves its shots
+    #[default]
+    Normal,
+    /// Fast and accurate, also slide-tackles
+    Hard,
+}
+
+impl BotDifficulty {
+    pub const ALL: [BotDifficulty; 3] = [Self::Easy, Self::Normal, Self::Hard];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Easy => "Fácil",
+            Self::Normal => "Normal",
+            Self::Hard => "Difícil",
+        }
+    }
+}
+
+/// Chat message as relayed by the host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    /// None = message from the host itself
+    pub sender_id: Option<u32>,
+    pub sender_name: String,
+    /// Sender's team at the time of sending (None = spectator)
+    pub team_index: Option<u8>,
+    pub kind: ChatKind,
+    pub text: String,
 }
 
 /// Mensajes de alta frecuencia que toleran pérdida (Canal Unreliable)
@@ -129,25 +232,36 @@ pub enum GameDataMessage {
     // Del cliente
     Input {
         player_id: u32,
+        /// Número de secuencia creciente por cliente (para la reconciliación)
+        sequence: u32,
         input: PlayerInput,
     },
-    Ping {
-        timestamp: u64,
-    },
+    /// Medición de RTT: el host hace ping a cada peer y también responde los de los clientes
+    Ping { timestamp: u64 },
+    /// Último snapshot decodificado (el host lo usa como baseline del próximo delta)
+    SnapshotAck { tick: u32 },
 
     // Del servidor
-    GameState {
-        tick: u32,
-        timestamp: u64,
-        players: Vec<PlayerState>,
-        ball: BallState,
-    },
+    /// GameState cuantizado y codificado como delta
+    Snapshot(SnapshotDelta),
+    /// Respuesta a un Ping (la envía quien lo recibe, host o cliente)
     Pong {
+        /// Timestamp del Ping que se responde
         client_timestamp: u64,
         server_timestamp: u64,
     },
 }
 
+/// Latencia de un jugador medida por el host
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PlayerLatency {
+    pub player_id: u32,
+    /// RTT suavizado en ms
+    pub rtt_ms: u16,
+    /// Variación media del RTT en ms
+    pub jitter_ms: u16,
+}
+
 // ============================================================================
 // MENSAJES ORIGINALES (Mantener por compatibilidad durante transición)
 // ============================================================================
@@ -192,6 +306,26 @@ pub struct PlayerInput {
     pub mode: bool,
 }
 
+impl PlayerInput {
+    /// Dirección de movimiento según las flechas (sin normalizar)
+    pub fn move_direction(&self) -> Vec2 {
+        let mut direction = Vec2::ZERO;
+        if self.move_up {
+            direction.y += 1.0;
+        }
+        if self.move_down {
+            direction.y -= 1.0;
+        }
+        if self.move_left {
+            direction.x -= 1.0;
+        }
+        if self.move_right {
+            direction.x += 1.0;
+        }
+        direction
+    }
+}
+
 /// Mensajes que el servidor envía al cliente
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ServerMessage {
@@ -205,6 +339,8 @@ pub enum ServerMessage {
         timestamp: u64,
         players: Vec<PlayerState>,
         ball: BallState,
+        discs: Vec<DiscState>,
+        clock: MatchClockState,
     },
 
     Pong {
@@ -233,10 +369,30 @@ pub enum ServerMessage {
 
     /// Slots updated - internal message for client processing
     SlotsUpdated(MatchSlots),
+
+    /// Score updated - internal message for client processing
+    ScoreUpdated {
+        score: [u32; 2],
+        scoring_team: Option<u8>,
+    },
+
+    /// Chat message relayed by the host
+    Chat(ChatMessage),
+
+    /// Players' latency as measured by the host
+    LatencyUpdated(Vec<PlayerLatency>),
+
+    /// Connection to the host lost; the network thread is retrying
+    Reconnecting {
+        attempt: u32,
+    },
+
+    /// An admin asked for an instant replay
+    InstantReplay,
 }
 
 /// Movimiento activo de un jugador
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct PlayerMovement {
     pub movement_id: u8,
     pub start_tick: u32,
@@ -263,6 +419,8 @@ pub struct PlayerState {
     pub team_index: u8,
     // Modo cubo activo
     pub mode_cube_active: bool,
+    /// Última secuencia de input procesada por el host (para reconciliar la predicción)
+    pub last_input_sequence: u32,
 }
 
 /// Estado de la pelota
@@ -273,6 +431,56 @@ pub struct BallState {
     pub angular_velocity: f32,
 }
 
+/// Estado de un disco móvil del mapa
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscState {
+    /// Índice del disco en `Map::discs`
+    pub index: u32,
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+}
+
+/// Reloj y resultado del partido (viaja en cada GameState)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct MatchClockState {
+    /// Segundos jugados (el reloj se detiene entre saques)
+    pub elapsed_secs: f32,
+    /// Límite de tiempo en segundos (0 = sin límite)
+    pub time_limit_secs: u32,
+    /// Prórroga con gol de oro en curso
+    pub overtime: bool,
+    /// Equipo ganador cuando el partido terminó (None = en juego)
+    pub winner: Option<u8>,
+}
+
+impl MatchClockState {
+    /// Segundos restantes del tiempo reglamentario (None = sin límite)
+    pub fn remaining_secs(&self) -> Option<f32> {
+        if self.time_limit_secs == 0 {
+            return None;
+        }
+        Some((self.time_limit_secs as f32 - self.elapsed_secs).max(0.0))
+    }
+}
+
+/// Reglas del partido elegidas al crear la sala
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, bevy::prelude::Resource)]
+pub struct MatchRules {
+    /// Duración del partido en segundos (0 = sin límite)
+    pub time_limit_secs: u32,
+    /// Goles necesarios para ganar (0 = sin límite)
+    pub score_limit: u32,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            time_limit_secs: 180,
+            score_limit: 3,
+        }
+    }
+}
+
 /// Configuración completa del juego (del código original)
 #[derive(Debug, Clone, Serialize, Deserialize, bevy::prelude::Resource)]
 pub struct GameConfig {
@@ -403,3 +611,24 @@ impl Default for GameConfig {
         }
     }
 }
+
+impl GameConfig {
+    /// Multiplicador de velocidad: correr en modo cubo, correr o modo cubo, o caminar
+    pub fn move_coeficient(&self, sprint: bool, cube_mode: bool) -> f32 {
+        if cube_mode && sprint {
+            self.run_cube_coeficient
+        } else if cube_mode || sprint {
+            self.run_coeficient
+        } else {
+            1.0
+        }
+    }
+
+    /// Velocidad de un jugador según su input. El host la aplica en `move_players`
+    /// y el cliente la usa para predecir a sus jugadores locales.
+    pub fn player_velocity(&self, input: &PlayerInput, cube_mode: bool) -> Vec2 {
+        input.move_direction().normalize_or_zero()
+            * self.player_speed_walking
+            * self.move_coeficient(input.sprint, cube_mode)
+    }
+}
@@ -0,0 +1,201 @@
+// ============================================================================
+// REPLAYS - FORMATO DE ARCHIVO
+// ============================================================================
+//
+// Un replay guarda los mensajes del host que hacen falta para reproducir un
+// partido (GameState, slots, marcador y colores) junto con el mapa. El archivo
+// es: `REPLAY_MAGIC` + versión de formato (u16 LE) + bincode comprimido con deflate.
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+use super::map::Map;
+use super::protocol::{ProtocolVersion, ServerMessage};
+
+/// Firma al inicio de cada archivo de replay
+pub const REPLAY_MAGIC: &[u8; 4] = b"KBRP";
+
+/// Versión del formato; subirla si cambia `Replay` o los mensajes que contiene
+/// (2: `PlayerDisconnected` y `MatchSlots::bots`)
+pub const REPLAY_FORMAT_VERSION: u16 = 2;
+
+/// Extensión de los archivos de replay
+pub const REPLAY_EXTENSION: &str = "kbreplay";
+
+/// Tramo repetido tras un gol: segundos antes y después del gol
+pub const GOAL_REPLAY_BEFORE_SECS: f64 = 3.0;
+pub const GOAL_REPLAY_AFTER_SECS: f64 = 1.0;
+
+/// Velocidad de la repetición instantánea
+pub const INSTANT_REPLAY_SLOW_MOTION: f64 = 0.5;
+
+/// Segundos desde el gol hasta que termina su repetición en los clientes: esperan
+/// los snapshots de después del gol y repiten el tramo en cámara lenta. El host
+/// festeja al menos esto antes del saque
+pub const GOAL_REPLAY_SECS: f32 = (GOAL_REPLAY_AFTER_SECS
+    + (GOAL_REPLAY_BEFORE_SECS + GOAL_REPLAY_AFTER_SECS) / INSTANT_REPLAY_SLOW_MOTION)
+    as f32;
+
+/// Un mensaje del host y cuándo llegó (segundos desde el inicio de la grabación)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ReplayFrame {
+    pub time_secs: f32,
+    pub message: ServerMessage,
+}
+
+/// Partido grabado
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Replay {
+    /// Versión del juego que grabó el partido
+    pub game_version: ProtocolVersion,
+    /// Momento de inicio de la grabación (segundos Unix)
+    pub recorded_at: u64,
+    pub map: Option<Map>,
+    /// Colores de equipo al empezar (los cambios posteriores van como frames)
+    pub team_colors: Vec<(f32, f32, f32)>,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    /// Mensajes del host que se guardan en un replay
+    pub fn is_recorded(message: &ServerMessage) -> bool {
+        matches!(
+            message,
+            ServerMessage::GameState { .. }
+                | ServerMessage::SlotsUpdated(_)
+                | ServerMessage::ScoreUpdated { .. }
+                | ServerMessage::ChangeTeamColor { .. }
+                | ServerMessage::PlayerDisconnected { .. }
+        )
+    }
+
+    /// Duración del partido grabado en segundos
+    pub fn duration_secs(&self) -> f32 {
+        self.frames.last().map_or(0.0, |f| f.time_secs)
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, String> {
+        let body =
+            bincode::serialize(self).map_err(|e| format!("Error serializando replay: {}", e))?;
+
+        let mut bytes = Vec::with_capacity(body.len() / 4);
+        bytes.extend_from_slice(REPLAY_MAGIC);
+        bytes.extend_from_slice(&REPLAY_FORMAT_VERSION.to_le_bytes());
+        let mut encoder = DeflateEncoder::new(bytes, Compression::default());
+        encoder
+            .write_all(&body)
+            .and_then(|_| encoder.finish())
+            .map_err(|e| format!("Error comprimiendo replay: {}", e))
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let header_len = REPLAY_MAGIC.len() + 2;
+        if bytes.len() < header_len || &bytes[..REPLAY_MAGIC.len()] != REPLAY_MAGIC {
+            return Err("No es un archivo de replay".to_string());
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != REPLAY_FORMAT_VERSION {
+            return Err(format!(
+                "Versión de replay {} no soportada (se esperaba {})",
+                version, REPLAY_FORMAT_VERSION
+            ));
+        }
+
+        let mut body = Vec::new();
+        DeflateDecoder::new(&bytes[header_len..])
+            .read_to_end(&mut body)
+            .map_err(|e| format!("Error descomprimiendo replay: {}", e))?;
+        bincode::deserialize(&body).map_err(|e| format!("Replay corrupto: {}", e))
+    }
+}
+
+/// GameState de prueba con la pelota en x = `tick` (tests de grabación y reproducción)
+#[cfg(test)]
+pub(crate) fn game_state(
+    tick: u32,
+    players: Vec<crate::shared::protocol::PlayerState>,
+    discs: Vec<crate::shared::protocol::DiscState>,
+) -> ServerMessage {
+    ServerMessage::GameState {
+        tick,
+        timestamp: tick as u64 * 16,
+        players,
+        ball: crate::shared::protocol::BallState {
+            position: (tick as f32, 0.0),
+            velocity: (0.0, 0.0),
+            angular_velocity: 0.0,
+        },
+        discs,
+        clock: crate::shared::protocol::MatchClockState::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::MatchSlots;
+
+    fn replay() -> Replay {
+        let mut frames = vec![ReplayFrame {
+            time_secs: 0.0,
+            message: ServerMessage::SlotsUpdated(MatchSlots::default()),
+        }];
+        frames.extend((1..=600).map(|tick| ReplayFrame {
+            time_secs: tick as f32 / 60.0,
+            message: game_state(tick, Vec::new(), Vec::new()),
+        }));
+        Replay {
+            game_version: ProtocolVersion::current(),
+            recorded_at: 1_700_000_000,
+            map: None,
+            team_colors: vec![(1.0, 0.0, 0.0), (0.0, 0.0, 1.0)],
+            frames,
+        }
+    }
+
+    #[test]
+    fn replay_roundtrips_and_is_compressed() {
+        let original = replay();
+        let bytes = original.encode().unwrap();
+        let raw = bincode::serialize(&original).unwrap();
+        assert!(bytes.len() < raw.len() / 2);
+
+        let decoded = Replay::decode(&bytes).unwrap();
+        assert_eq!(decoded.recorded_at, original.recorded_at);
+        assert_eq!(decoded.frames.len(), 601);
+        assert_eq!(decoded.duration_secs(), 10.0);
+        let ServerMessage::GameState { tick, ball, .. } = &decoded.frames[600].message else {
+            panic!("expected a GameState");
+        };
+        assert_eq!(*tick, 600);
+        assert_eq!(ball.position, (600.0, 0.0));
+    }
+
+    #[test]
+    fn rejects_foreign_files_and_unknown_versions() {
+        assert!(Replay::decode(b"not a replay").is_err());
+        assert!(Replay::decode(b"KB").is_err());
+
+        let mut bytes = replay().encode().unwrap();
+        bytes[4..6].copy_from_slice(&(REPLAY_FORMAT_VERSION + 1).to_le_bytes());
+        assert!(Replay::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn only_match_messages_are_recorded() {
+        assert!(Replay::is_recorded(&game_state(1, Vec::new(), Vec::new())));
+        assert!(Replay::is_recorded(&ServerMessage::ChangeTeamColor {
+            team_index: 0,
+            color: (1.0, 1.0, 1.0),
+        }));
+        assert!(Replay::is_recorded(&ServerMessage::PlayerDisconnected {
+            player_id: 2
+        }));
+        assert!(!Replay::is_recorded(&ServerMessage::Reconnecting {
+            attempt: 1
+        }));
+    }
+}
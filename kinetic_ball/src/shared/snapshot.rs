@@ -0,0 +1,577 @@
+// ============================================================================
+// SNAPSHOTS CUANTIZADOS Y COMPRIMIDOS POR DELTA
+// ============================================================================
+//
+// El host cuantiza cada GameState y lo envía como delta contra el último tick
+// que el peer confirmó (`GameDataMessage::SnapshotAck`). El cliente guarda los
+// estados decodificados para poder aplicar los deltas siguientes. Los datos que
+// casi no cambian (nombre, equipo) viajan por el canal reliable en `PlayerRoster`.
+
+use bevy::prelude::Vec2;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+use super::protocol::{BallState, DiscState, MatchClockState, PlayerMovement, PlayerState};
+
+/// Precisión de posiciones: 1/4 de unidad. Van en i32: con i16 (±8191) un mapa
+/// grande escalado saturaba
+const POSITION_SCALE: f32 = 4.0;
+
+/// Precisión de velocidades: 1/2 unidad/s (rango ±16383)
+const VELOCITY_SCALE: f32 = 2.0;
+
+/// Ticks guardados como posible baseline (~1 s a 60 Hz)
+pub const SNAPSHOT_HISTORY_LEN: usize = 64;
+
+const FLAG_KICK_CHARGING: u8 = 1 << 0;
+const FLAG_SLIDING: u8 = 1 << 1;
+const FLAG_NOT_INTERACTING: u8 = 1 << 2;
+const FLAG_CUBE_MODE: u8 = 1 << 3;
+
+fn quantize(value: f32, scale: f32) -> i16 {
+    // `as` satura fuera de rango (p. ej. jugadores fuera del campo)
+    (value * scale).round() as i16
+}
+
+fn quantize_vec(value: (f32, f32), scale: f32) -> (i16, i16) {
+    (quantize(value.0, scale), quantize(value.1, scale))
+}
+
+fn dequantize_vec(value: (i16, i16), scale: f32) -> (f32, f32) {
+    (value.0 as f32 / scale, value.1 as f32 / scale)
+}
+
+fn quantize_position(value: (f32, f32)) -> (i32, i32) {
+    (
+        (value.0 * POSITION_SCALE).round() as i32,
+        (value.1 * POSITION_SCALE).round() as i32,
+    )
+}
+
+fn dequantize_position(value: (i32, i32)) -> (f32, f32) {
+    (
+        value.0 as f32 / POSITION_SCALE,
+        value.1 as f32 / POSITION_SCALE,
+    )
+}
+
+fn quantize_unit(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn quantize_angle(angle: f32) -> u16 {
+    let turns = angle.rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU;
+    (turns * 65536.0).round() as u32 as u16
+}
+
+fn dequantize_angle(angle: u16) -> f32 {
+    let angle = angle as f32 / 65536.0 * std::f32::consts::TAU;
+    // Mismo rango que to_euler: (-PI, PI]
+    if angle > std::f32::consts::PI {
+        angle - std::f32::consts::TAU
+    } else {
+        angle
+    }
+}
+
+/// Datos de un jugador que solo cambian al entrar o cambiar de equipo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerInfo {
+    pub id: u32,
+    pub name: String,
+    pub team_index: u8,
+}
+
+/// Estado de un jugador cuantizado
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuantizedPlayer {
+    pub id: u32,
+    pub position: (i32, i32),
+    pub velocity: (i16, i16),
+    pub rotation: u16,
+    /// Potencia de la patada (0-255)
+    pub kick_power: u8,
+    /// Dirección de la curva (-1, 0, 1)
+    pub kick_curve: i8,
+    pub flags: u8,
+    pub ball_target_position: Option<(i32, i32)>,
+    pub stamin_charge: u8,
+    pub active_movement: Option<PlayerMovement>,
+    pub last_input_sequence: u32,
+}
+
+impl QuantizedPlayer {
+    pub fn from_state(state: &PlayerState) -> Self {
+        let mut flags = 0;
+        if state.kick_charging {
+            flags |= FLAG_KICK_CHARGING;
+        }
+        if state.is_sliding {
+            flags |= FLAG_SLIDING;
+        }
+        if state.not_interacting {
+            flags |= FLAG_NOT_INTERACTING;
+        }
+        if state.mode_cube_active {
+            flags |= FLAG_CUBE_MODE;
+        }
+        Self {
+            id: state.id,
+            position: quantize_position((state.position.x, state.position.y)),
+            velocity: quantize_vec(state.velocity, VELOCITY_SCALE),
+            rotation: quantize_angle(state.rotation),
+            kick_power: quantize_unit(state.kick_charge.x),
+            kick_curve: state.kick_charge.y.round().clamp(-1.0, 1.0) as i8,
+            flags,
+            ball_target_position: state
+                .ball_target_position
+                .map(|p| quantize_position((p.x, p.y))),
+            stamin_charge: quantize_unit(state.stamin_charge),
+            active_movement: state.active_movement.clone(),
+            last_input_sequence: state.last_input_sequence,
+        }
+    }
+
+    /// Reconstruye el estado completo con los datos del roster
+    pub fn to_state(&self, info: &PlayerInfo) -> PlayerState {
+        let (x, y) = dequantize_position(self.position);
+        PlayerState {
+            id: self.id,
+            name: info.name.clone(),
+            position: Vec2::new(x, y),
+            velocity: dequantize_vec(self.velocity, VELOCITY_SCALE),
+            rotation: dequantize_angle(self.rotation),
+            kick_charge: Vec2::new(self.kick_power as f32 / 255.0, self.kick_curve as f32),
+            kick_charging: self.flags & FLAG_KICK_CHARGING != 0,
+            is_sliding: self.flags & FLAG_SLIDING != 0,
+            not_interacting: self.flags & FLAG_NOT_INTERACTING != 0,
+            ball_target_position: self.ball_target_position.map(|p| {
+                let (x, y) = dequantize_position(p);
+                Vec2::new(x, y)
+            }),
+            stamin_charge: self.stamin_charge as f32 / 255.0,
+            active_movement: self.active_movement.clone(),
+            team_index: info.team_index,
+            mode_cube_active: self.flags & FLAG_CUBE_MODE != 0,
+            last_input_sequence: self.last_input_sequence,
+        }
+    }
+}
+
+/// Estado de la pelota cuantizado
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuantizedBall {
+    pub position: (i32, i32),
+    pub velocity: (i16, i16),
+    /// Solo se usa en el host, pero se mantiene para no perder el dato
+    pub angular_velocity: i16,
+}
+
+impl QuantizedBall {
+    pub fn from_state(state: &BallState) -> Self {
+        Self {
+            position: quantize_position(state.position),
+            velocity: quantize_vec(state.velocity, VELOCITY_SCALE),
+            angular_velocity: quantize(state.angular_velocity, VELOCITY_SCALE),
+        }
+    }
+
+    pub fn to_state(&self) -> BallState {
+        BallState {
+            position: dequantize_position(self.position),
+            velocity: dequantize_vec(self.velocity, VELOCITY_SCALE),
+            angular_velocity: self.angular_velocity as f32 / VELOCITY_SCALE,
+        }
+    }
+}
+
+/// Estado de un disco móvil cuantizado
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuantizedDisc {
+    pub index: u32,
+    pub position: (i32, i32),
+    pub velocity: (i16, i16),
+}
+
+impl QuantizedDisc {
+    pub fn from_state(state: &DiscState) -> Self {
+        Self {
+            index: state.index,
+            position: quantize_position(state.position),
+            velocity: quantize_vec(state.velocity, VELOCITY_SCALE),
+        }
+    }
+
+    pub fn to_state(&self) -> DiscState {
+        DiscState {
+            index: self.index,
+            position: dequantize_position(self.position),
+            velocity: dequantize_vec(self.velocity, VELOCITY_SCALE),
+        }
+    }
+}
+
+/// Estado completo de un tick, ya cuantizado (lo que se usa como baseline)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuantizedGameState {
+    pub tick: u32,
+    pub timestamp: u64,
+    pub players: Vec<QuantizedPlayer>,
+    pub ball: QuantizedBall,
+    pub discs: Vec<QuantizedDisc>,
+    pub clock: MatchClockState,
+}
+
+/// GameState codificado contra un baseline confirmado por el peer.
+/// Solo viajan las entidades que cambiaron respecto del baseline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotDelta {
+    pub tick: u32,
+    /// Tick contra el que se codificó (None = snapshot completo)
+    pub baseline_tick: Option<u32>,
+    pub timestamp: u64,
+    /// Jugadores nuevos o que cambiaron
+    pub players: Vec<QuantizedPlayer>,
+    /// Jugadores del baseline que ya no están en la cancha
+    pub removed_players: Vec<u32>,
+    /// None = igual que en el baseline
+    pub ball: Option<QuantizedBall>,
+    pub discs: Vec<QuantizedDisc>,
+    pub clock: Option<MatchClockState>,
+}
+
+impl QuantizedGameState {
+    /// Codifica este estado contra `baseline` (o completo si no hay)
+    pub fn encode(&self, baseline: Option<&QuantizedGameState>) -> SnapshotDelta {
+        let Some(baseline) = baseline else {
+            return SnapshotDelta {
+                tick: self.tick,
+                baseline_tick: None,
+                timestamp: self.timestamp,
+                players: self.players.clone(),
+                removed_players: Vec::new(),
+                ball: Some(self.ball.clone()),
+                discs: self.discs.clone(),
+                clock: Some(self.clock),
+            };
+        };
+
+        SnapshotDelta {
+            tick: self.tick,
+            baseline_tick: Some(baseline.tick),
+            timestamp: self.timestamp,
+            players: self
+                .players
+                .iter()
+                .filter(|p| !baseline.players.contains(p))
+                .cloned()
+                .collect(),
+            removed_players: baseline
+                .players
+                .iter()
+                .filter(|b| !self.players.iter().any(|p| p.id == b.id))
+                .map(|b| b.id)
+                .collect(),
+            ball: (self.ball != baseline.ball).then(|| self.ball.clone()),
+            discs: self
+                .discs
+                .iter()
+                .filter(|d| !baseline.discs.contains(d))
+                .cloned()
+                .collect(),
+            clock: (self.clock != baseline.clock).then_some(self.clock),
+        }
+    }
+
+    /// Reconstruye el estado completo aplicando `delta` sobre este baseline
+    pub fn apply(&self, delta: &SnapshotDelta) -> QuantizedGameState {
+        let mut players: Vec<QuantizedPlayer> = self
+            .players
+            .iter()
+            .filter(|p| !delta.removed_players.contains(&p.id))
+            .cloned()
+            .collect();
+        for changed in &delta.players {
+            match players.iter_mut().find(|p| p.id == changed.id) {
+                Some(player) => *player = changed.clone(),
+                None => players.push(changed.clone()),
+            }
+        }
+
+        let mut discs = self.discs.clone();
+        for changed in &delta.discs {
+            match discs.iter_mut().find(|d| d.index == changed.index) {
+                Some(disc) => *disc = changed.clone(),
+                None => discs.push(changed.clone()),
+            }
+        }
+
+        QuantizedGameState {
+            tick: delta.tick,
+            timestamp: delta.timestamp,
+            players,
+            ball: delta.ball.clone().unwrap_or_else(|| self.ball.clone()),
+            discs,
+            clock: delta.clock.unwrap_or(self.clock),
+        }
+    }
+}
+
+impl SnapshotDelta {
+    /// Estado completo de un snapshot sin baseline
+    fn into_full(self) -> Option<QuantizedGameState> {
+        Some(QuantizedGameState {
+            tick: self.tick,
+            timestamp: self.timestamp,
+            players: self.players,
+            ball: self.ball?,
+            discs: self.discs,
+            clock: self.clock?,
+        })
+    }
+}
+
+/// Lado host: estados enviados recientemente y último tick confirmado por cada peer
+pub struct SnapshotEncoder<P> {
+    history: VecDeque<QuantizedGameState>,
+    acked: HashMap<P, u32>,
+}
+
+impl<P> Default for SnapshotEncoder<P> {
+    fn default() -> Self {
+        Self {
+            history: VecDeque::new(),
+            acked: HashMap::new(),
+        }
+    }
+}
+
+impl<P: Eq + std::hash::Hash> SnapshotEncoder<P> {
+    /// Guarda el estado del tick actual como posible baseline
+    pub fn push(&mut self, state: QuantizedGameState) {
+        self.history.push_back(state);
+        while self.history.len() > SNAPSHOT_HISTORY_LEN {
+            self.history.pop_front();
+        }
+    }
+
+    /// Registra la confirmación de un peer (las viejas o desordenadas se ignoran)
+    pub fn ack(&mut self, peer: P, tick: u32) {
+        let acked = self.acked.entry(peer).or_insert(tick);
+        *acked = (*acked).max(tick);
+    }
+
+    pub fn forget(&mut self, peer: &P) {
+        self.acked.remove(peer);
+    }
+
+    /// Codifica el último estado para un peer: delta contra lo que confirmó, o
+    /// completo si todavía no confirmó nada o su baseline ya salió del historial
+    pub fn encode_for(&self, peer: &P) -> Option<SnapshotDelta> {
+        let current = self.history.back()?;
+        let baseline = self
+            .acked
+            .get(peer)
+            .and_then(|tick| self.history.iter().find(|s| s.tick == *tick));
+        Some(current.encode(baseline))
+    }
+}
+
+/// Lado cliente: estados decodificados que el host puede usar como baseline
+#[derive(Default)]
+pub struct SnapshotDecoder {
+    history: VecDeque<QuantizedGameState>,
+}
+
+impl SnapshotDecoder {
+    /// Decodifica un snapshot. None si su baseline ya no está (se espera al próximo)
+    pub fn decode(&mut self, delta: SnapshotDelta) -> Option<QuantizedGameState> {
+        if self.history.iter().any(|s| s.tick == delta.tick) {
+            return None;
+        }
+        let state = match delta.baseline_tick {
+            None => delta.into_full()?,
+            Some(baseline_tick) => self
+                .history
+                .iter()
+                .find(|s| s.tick == baseline_tick)?
+                .apply(&delta),
+        };
+        self.history.push_back(state.clone());
+        while self.history.len() > SNAPSHOT_HISTORY_LEN {
+            self.history.pop_front();
+        }
+        Some(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::map::MAX_MAP_SCALE;
+
+    fn player(id: u32, x: f32) -> PlayerState {
+        PlayerState {
+            id,
+            name: format!("P{}", id),
+            position: Vec2::new(x, -20.5),
+            velocity: (120.0, -35.5),
+            rotation: -2.0,
+            kick_charge: Vec2::new(0.5, -1.0),
+            kick_charging: true,
+            is_sliding: false,
+            not_interacting: false,
+            ball_target_position: None,
+            stamin_charge: 0.75,
+            active_movement: None,
+            team_index: 1,
+            mode_cube_active: true,
+            last_input_sequence: 42,
+        }
+    }
+
+    fn state(tick: u32, players: &[PlayerState], ball_x: f32) -> QuantizedGameState {
+        QuantizedGameState {
+            tick,
+            timestamp: tick as u64 * 16,
+            players: players.iter().map(QuantizedPlayer::from_state).collect(),
+            ball: QuantizedBall::from_state(&BallState {
+                position: (ball_x, 0.0),
+                velocity: (0.0, 0.0),
+                angular_velocity: 0.0,
+            }),
+            discs: Vec::new(),
+            clock: MatchClockState::default(),
+        }
+    }
+
+    #[test]
+    fn test_player_quantization_roundtrip() {
+        let original = player(3, 101.3);
+        let info = PlayerInfo {
+            id: 3,
+            name: original.name.clone(),
+            team_index: original.team_index,
+        };
+        let restored = QuantizedPlayer::from_state(&original).to_state(&info);
+
+        assert!((restored.position - original.position).length() <= 0.25);
+        assert!((restored.velocity.0 - original.velocity.0).abs() <= 0.5);
+        assert!((restored.rotation - original.rotation).abs() < 0.001);
+        assert!((restored.stamin_charge - original.stamin_charge).abs() < 0.01);
+        assert_eq!(restored.kick_charge.y, -1.0);
+        assert!(restored.kick_charging);
+        assert!(restored.mode_cube_active);
+        assert_eq!(restored.last_input_sequence, 42);
+        assert_eq!(restored.team_index, 1);
+    }
+
+    #[test]
+    fn test_scaled_map_positions_do_not_saturate() {
+        // Esquinas de cancha_grande (±4500, ±2500) con la escala máxima, y algo
+        // más afuera (jugadores fuera del campo)
+        let x = 4500.0 * MAX_MAP_SCALE + 100.25;
+        let y = -2500.0 * MAX_MAP_SCALE - 100.5;
+
+        let mut original = player(1, x);
+        original.position.y = y;
+        original.ball_target_position = Some(Vec2::new(-x, -y));
+        let info = PlayerInfo {
+            id: 1,
+            name: original.name.clone(),
+            team_index: original.team_index,
+        };
+        let restored = QuantizedPlayer::from_state(&original).to_state(&info);
+        assert_eq!(restored.position, original.position);
+        assert_eq!(restored.ball_target_position, original.ball_target_position);
+
+        let ball = BallState {
+            position: (-x, y),
+            velocity: (0.0, 0.0),
+            angular_velocity: 0.0,
+        };
+        assert_eq!(
+            QuantizedBall::from_state(&ball).to_state().position,
+            (-x, y)
+        );
+
+        let disc = DiscState {
+            index: 0,
+            position: (x, -y),
+            velocity: (0.0, 0.0),
+        };
+        assert_eq!(
+            QuantizedDisc::from_state(&disc).to_state().position,
+            (x, -y)
+        );
+    }
+
+    #[test]
+    fn test_delta_only_contains_changes() {
+        let baseline = state(1, &[player(1, 0.0), player(2, 50.0)], 10.0);
+        let current = state(2, &[player(1, 0.0), player(2, 60.0)], 10.0);
+
+        let delta = current.encode(Some(&baseline));
+        assert_eq!(delta.baseline_tick, Some(1));
+        assert_eq!(delta.players.len(), 1);
+        assert_eq!(delta.players[0].id, 2);
+        assert!(delta.ball.is_none());
+        assert!(delta.clock.is_none());
+
+        assert_eq!(baseline.apply(&delta), current);
+    }
+
+    #[test]
+    fn test_delta_removes_players() {
+        let baseline = state(1, &[player(1, 0.0), player(2, 50.0)], 10.0);
+        let current = state(2, &[player(2, 50.0)], 12.0);
+
+        let delta = current.encode(Some(&baseline));
+        assert_eq!(delta.removed_players, vec![1]);
+        assert_eq!(baseline.apply(&delta), current);
+    }
+
+    #[test]
+    fn test_encoder_falls_back_to_full_snapshot() {
+        let mut encoder = SnapshotEncoder::default();
+        encoder.push(state(1, &[player(1, 0.0)], 0.0));
+        assert_eq!(encoder.encode_for(&"peer").unwrap().baseline_tick, None);
+
+        encoder.ack("peer", 1);
+        encoder.push(state(2, &[player(1, 5.0)], 0.0));
+        assert_eq!(encoder.encode_for(&"peer").unwrap().baseline_tick, Some(1));
+
+        // Un ack viejo no retrocede el baseline
+        encoder.ack("peer", 0);
+        assert_eq!(encoder.encode_for(&"peer").unwrap().baseline_tick, Some(1));
+
+        // Baseline fuera del historial: snapshot completo
+        for tick in 3..(3 + SNAPSHOT_HISTORY_LEN as u32) {
+            encoder.push(state(tick, &[player(1, tick as f32)], 0.0));
+        }
+        assert_eq!(encoder.encode_for(&"peer").unwrap().baseline_tick, None);
+    }
+
+    #[test]
+    fn test_decoder_roundtrip() {
+        let mut encoder = SnapshotEncoder::default();
+        let mut decoder = SnapshotDecoder::default();
+
+        let first = state(1, &[player(1, 0.0)], 0.0);
+        encoder.push(first.clone());
+        let decoded = decoder.decode(encoder.encode_for(&1).unwrap()).unwrap();
+        assert_eq!(decoded, first);
+        encoder.ack(1, decoded.tick);
+
+        let second = state(2, &[player(1, 8.0), player(2, 3.0)], 4.0);
+        encoder.push(second.clone());
+        assert_eq!(
+            decoder.decode(encoder.encode_for(&1).unwrap()).unwrap(),
+            second
+        );
+
+        // Un delta cuyo baseline el cliente no tiene no se puede decodificar
+        let orphan = state(5, &[], 0.0).encode(Some(&state(4, &[], 0.0)));
+        assert!(decoder.decode(orphan).is_none());
+    }
+}
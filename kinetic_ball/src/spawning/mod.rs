@@ -8,14 +8,14 @@ use crate::assets::EmbeddedAssets;
 use crate::color_utils::{generate_unique_player_color, get_team_colors};
 use crate::components::{
     CurveAction, InGameEntity, Interpolated, KickChargeBar, KickChargeBarCurveLeft,
-    KickChargeBarCurveRight, PlayerNameText, PlayerOutline, PlayerSprite, RemoteBall,
-    RemotePlayer, SlideCubeVisual, StaminChargeBar,
+    KickChargeBarCurveRight, PlayerNameText, PlayerOutline, PlayerSprite, RemoteBall, RemoteDisc,
+    RemotePlayer, SlideCubeVisual, SnapshotBuffer, StaminChargeBar,
 };
-use crate::events::{SpawnBallEvent, SpawnPlayerEvent};
+use crate::events::{SpawnBallEvent, SpawnDiscEvent, SpawnPlayerEvent};
 use crate::game::spawn_key_visual_2d;
 use crate::keybindings::{key_code_display_name, GamepadBindingsMap, KeyBindingsConfig};
 use crate::local_players::{InputDevice, LocalPlayers};
-use crate::resources::PlayerColors;
+use crate::resources::{LoadedMap, PlayerColors};
 use crate::shared::protocol::GameConfig;
 
 /// Handler para eventos de spawn de pelota
@@ -41,6 +41,7 @@ pub fn handle_spawn_ball(
                     target_rotation: 0.0,
                     smoothing: 20.0,
                 },
+                SnapshotBuffer::default(),
                 RenderLayers::layer(0),
             ))
             .with_children(|parent| {
@@ -57,6 +58,53 @@ pub fn handle_spawn_ball(
     }
 }
 
+/// Handler para eventos de spawn de discos móviles del mapa
+pub fn handle_spawn_disc(
+    mut events: MessageReader<SpawnDiscEvent>,
+    mut commands: Commands,
+    loaded_map: Res<LoadedMap>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let Some(map) = &loaded_map.0 else {
+        return;
+    };
+
+    for event in events.read() {
+        let Some(disc) = map.discs.get(event.index as usize) else {
+            continue;
+        };
+        println!(
+            "🥏 [Bevy] Spawneando disco móvil {} en {:?}",
+            event.index, event.position
+        );
+
+        let color = disc
+            .color
+            .as_deref()
+            .and_then(|hex| Srgba::hex(hex).ok())
+            .map(Color::Srgba)
+            .unwrap_or(Color::srgb(0.7, 0.7, 0.7));
+
+        commands.spawn((
+            InGameEntity,
+            RemoteDisc { index: event.index },
+            Mesh2d(meshes.add(Circle::new(disc.radius))),
+            MeshMaterial2d(materials.add(color)),
+            Transform::from_xyz(event.position.0, event.position.1, 5.0),
+            Visibility::default(),
+            Interpolated {
+                target_position: Vec2::new(event.position.0, event.position.1),
+                target_velocity: Vec2::new(event.velocity.0, event.velocity.1),
+                target_rotation: 0.0,
+                smoothing: 20.0,
+            },
+            SnapshotBuffer::default(),
+            RenderLayers::layer(0),
+        ));
+    }
+}
+
 /// Handler para eventos de spawn de jugador
 pub fn handle_spawn_player(
     mut events: MessageReader<SpawnPlayerEvent>,
@@ -127,6 +175,8 @@ pub fn handle_spawn_player(
                     stamin_charge: ps.stamin_charge,
                     active_movement: ps.active_movement.clone(),
                     mode_cube_active: ps.mode_cube_active,
+                    server_position: ps.position,
+                    last_input_sequence: ps.last_input_sequence,
                 },
                 bevy_rapier2d::prelude::Collider::ball(config.sphere_radius),
                 Interpolated {
@@ -342,6 +392,8 @@ pub fn handle_spawn_player(
                     &mut materials,
                     private_player_layers.clone(),
                 );
-            });
+            })
+            // Los jugadores locales usan predicción; los remotos se dibujan con retardo
+            .insert_if(SnapshotBuffer::default(), || !is_local);
     }
 }
@@ -16,6 +16,13 @@ pub enum AppState {
     HostingRoom,
     Connecting,
     InGame,
+    /// Reproduciendo un partido grabado
+    Replay,
+}
+
+/// Hay un partido en pantalla: en vivo o reproduciendo un replay
+pub fn match_on_screen(state: Res<State<AppState>>) -> bool {
+    matches!(state.get(), AppState::InGame | AppState::Replay)
 }
 
 // ============================================================================
@@ -40,4 +47,9 @@ pub struct RoomInfo {
     pub status: RoomStatus,
     #[serde(default)]
     pub min_version: Option<String>,
+    #[serde(default)]
+    pub has_password: bool,
+    /// Peer de matchbox del host (None hasta que el host se conecta al proxy)
+    #[serde(default)]
+    pub host_peer_id: Option<String>,
 }
@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::sync::mpsc;
+
+use super::{next_peer_id, Channel, PeerEvent, PeerId, Transport};
+
+/// Lo que un extremo del loopback le manda a otro (junto con su PeerId)
+enum Envelope {
+    /// Un cliente nuevo se presenta con el canal para responderle
+    Hello(mpsc::Sender<(PeerId, Envelope)>),
+    Data(Channel, Vec<u8>),
+    /// El extremo se cerró
+    Bye,
+}
+
+/// Transporte en memoria dentro del proceso: un host y los clientes que se
+/// conectan con su `LoopbackConnector`. Los dos canales son confiables
+pub struct LoopbackTransport {
+    id: PeerId,
+    inbox: mpsc::Receiver<(PeerId, Envelope)>,
+    peers: HashMap<PeerId, mpsc::Sender<(PeerId, Envelope)>>,
+    events: Vec<PeerEvent>,
+    received: [Vec<(PeerId, Vec<u8>)>; 2],
+    closed: bool,
+}
+
+/// Conecta clientes nuevos al host de un loopback. El host se cierra cuando ya no
+/// quedan clientes ni connectors
+#[derive(Clone)]
+pub struct LoopbackConnector {
+    host_id: PeerId,
+    host_tx: mpsc::Sender<(PeerId, Envelope)>,
+}
+
+/// Crea el extremo del host y el connector para sumarle clientes
+pub fn loopback() -> (LoopbackTransport, LoopbackConnector) {
+    let (host_tx, inbox) = mpsc::channel();
+    let host = LoopbackTransport::new(inbox);
+    let connector = LoopbackConnector {
+        host_id: host.id,
+        host_tx,
+    };
+    (host, connector)
+}
+
+impl LoopbackConnector {
+    /// Extremo de un cliente nuevo, ya conectado al host
+    pub fn connect(&self) -> LoopbackTransport {
+        let (tx, inbox) = mpsc::channel();
+        let mut client = LoopbackTransport::new(inbox);
+        if self.host_tx.send((client.id, Envelope::Hello(tx))).is_ok() {
+            client.peers.insert(self.host_id, self.host_tx.clone());
+            client.events.push(PeerEvent::Connected(self.host_id));
+        } else {
+            client.closed = true;
+        }
+        client
+    }
+}
+
+impl LoopbackTransport {
+    fn new(inbox: mpsc::Receiver<(PeerId, Envelope)>) -> Self {
+        Self {
+            id: next_peer_id(),
+            inbox,
+            peers: HashMap::new(),
+            events: Vec::new(),
+            received: [Vec::new(), Vec::new()],
+            closed: false,
+        }
+    }
+
+    /// Procesa lo que llegó de los otros extremos
+    fn pump(&mut self) {
+        loop {
+            match self.inbox.try_recv() {
+                Ok((from, Envelope::Hello(reply))) => {
+                    self.peers.insert(from, reply);
+                    self.events.push(PeerEvent::Connected(from));
+                }
+                Ok((from, Envelope::Data(channel, data))) => {
+                    if self.peers.contains_key(&from) {
+                        self.received[channel.index()].push((from, data));
+                    }
+                }
+                Ok((from, Envelope::Bye)) => {
+                    if self.peers.remove(&from).is_some() {
+                        self.events.push(PeerEvent::Disconnected(from));
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    for (peer, _) in self.peers.drain() {
+                        self.events.push(PeerEvent::Disconnected(peer));
+                    }
+                    self.closed = true;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for LoopbackTransport {
+    fn drop(&mut self) {
+        for tx in self.peers.values() {
+            let _ = tx.send((self.id, Envelope::Bye));
+        }
+    }
+}
+
+impl Transport for LoopbackTransport {
+    fn update_peers(&mut self) -> Vec<PeerEvent> {
+        self.pump();
+        std::mem::take(&mut self.events)
+    }
+
+    fn connected_peers(&self) -> Vec<PeerId> {
+        self.peers.keys().copied().collect()
+    }
+
+    fn send(&mut self, channel: Channel, peer: PeerId, data: Vec<u8>) {
+        let Some(tx) = self.peers.get(&peer) else {
+            return;
+        };
+        if tx.send((self.id, Envelope::Data(channel, data))).is_err() {
+            self.peers.remove(&peer);
+            self.events.push(PeerEvent::Disconnected(peer));
+        }
+    }
+
+    fn receive(&mut self, channel: Channel) -> Vec<(PeerId, Vec<u8>)> {
+        self.pump();
+        std::mem::take(&mut self.received[channel.index()])
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_and_host_see_each_other() {
+        let (mut host, connector) = loopback();
+        let mut client = connector.connect();
+
+        let client_events = client.update_peers();
+        let host_events = host.update_peers();
+        assert_eq!(client_events.len(), 1);
+        assert_eq!(host_events.len(), 1);
+        let PeerEvent::Connected(host_id) = client_events[0] else {
+            panic!("se esperaba Connected");
+        };
+        let PeerEvent::Connected(client_id) = host_events[0] else {
+            panic!("se esperaba Connected");
+        };
+
+        client.send(Channel::Reliable, host_id, vec![1, 2, 3]);
+        client.send(Channel::Unreliable, host_id, vec![4]);
+        assert_eq!(
+            host.receive(Channel::Reliable),
+            vec![(client_id, vec![1, 2, 3])]
+        );
+        assert_eq!(
+            host.receive(Channel::Unreliable),
+            vec![(client_id, vec![4])]
+        );
+
+        host.broadcast(Channel::Reliable, vec![5]);
+        assert_eq!(client.receive(Channel::Reliable), vec![(host_id, vec![5])]);
+    }
+
+    #[test]
+    fn test_dropping_a_client_disconnects_it() {
+        let (mut host, connector) = loopback();
+        let client = connector.connect();
+        let client_id = client.id;
+        host.update_peers();
+
+        drop(client);
+        assert_eq!(
+            host.update_peers(),
+            vec![PeerEvent::Disconnected(client_id)]
+        );
+        assert!(host.connected_peers().is_empty());
+        // El connector sigue vivo: pueden llegar más clientes
+        assert!(!host.is_closed());
+
+        drop(connector);
+        host.update_peers();
+        assert!(host.is_closed());
+    }
+
+    #[test]
+    fn test_dropping_the_host_closes_clients() {
+        let (host, connector) = loopback();
+        let mut client = connector.connect();
+        let host_id = host.id;
+        drop(connector);
+
+        drop(host);
+        let events = client.update_peers();
+        assert_eq!(events.last(), Some(&PeerEvent::Disconnected(host_id)));
+        assert!(client.connected_peers().is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+// ============================================================================
+// TRANSPORTE ENTRE HOST Y CLIENTES
+// ============================================================================
+//
+// El host y los clientes no dependen de cómo viajan los mensajes: WebRTC a través
+// del proxy (partidas online), UDP directo (red local, sin señalización) o un
+// loopback en memoria (práctica sin conexión y tests en un solo proceso).
+
+mod loopback;
+mod udp;
+mod webrtc;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub use loopback::{loopback, LoopbackTransport};
+// Solo el harness de integración suma clientes por fuera del host
+#[cfg(test)]
+pub use loopback::LoopbackConnector;
+pub use matchbox_socket::PeerId;
+pub use udp::UdpTransport;
+pub use webrtc::WebRtcTransport;
+
+/// Canal por el que viaja un mensaje
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Mensajes de control (`ControlMessage`): llegan todos y en orden
+    Reliable,
+    /// Datos de juego (`GameDataMessage`): pueden perderse o llegar desordenados
+    Unreliable,
+}
+
+impl Channel {
+    fn index(self) -> usize {
+        match self {
+            Channel::Reliable => 0,
+            Channel::Unreliable => 1,
+        }
+    }
+}
+
+/// Conexión o desconexión de un peer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerEvent {
+    Connected(PeerId),
+    Disconnected(PeerId),
+}
+
+/// Conexión con uno o más peers. Todas las operaciones son no bloqueantes: el loop
+/// de red las llama en cada vuelta
+pub trait Transport {
+    /// Conexiones y desconexiones desde la última llamada
+    fn update_peers(&mut self) -> Vec<PeerEvent>;
+
+    /// Peers conectados en este momento
+    fn connected_peers(&self) -> Vec<PeerId>;
+
+    fn send(&mut self, channel: Channel, peer: PeerId, data: Vec<u8>);
+
+    /// Mensajes recibidos por el canal desde la última llamada
+    fn receive(&mut self, channel: Channel) -> Vec<(PeerId, Vec<u8>)>;
+
+    /// El transporte ya no puede hablar con nadie (señalización caída, socket cerrado...)
+    fn is_closed(&self) -> bool;
+
+    /// Envía lo mismo a todos los peers conectados
+    fn broadcast(&mut self, channel: Channel, data: Vec<u8>) {
+        for peer in self.connected_peers() {
+            self.send(channel, peer, data.clone());
+        }
+    }
+}
+
+/// PeerId para los transportes sin señalización (loopback, UDP), único en el
+/// proceso. El nil queda reservado para los bots
+pub fn next_peer_id() -> PeerId {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    PeerId(
+        format!("00000000-0000-4000-8000-{:012x}", n)
+            .parse()
+            .expect("UUID válido"),
+    )
+}
@@ -0,0 +1,528 @@
+// Red local por UDP, sin proxy ni señalización: el host escucha en un puerto y los
+// clientes se conectan directo a su dirección. El canal confiable parte cada
+// mensaje en fragmentos, reenvía cada fragmento hasta recibir su ack y los entrega
+// en orden ya unidos; el otro manda datagramas sueltos.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use super::{next_peer_id, Channel, PeerEvent, PeerId, Transport};
+
+/// Tamaño máximo de un datagrama UDP
+const MAX_DATAGRAM: usize = 65_507;
+
+/// Datos de cada fragmento confiable: entra en el MTU de una red local, así un
+/// fragmento perdido no obliga a reenviar el mensaje entero
+const MAX_FRAGMENT: usize = 1_200;
+
+/// Lo que bincode agrega a los datos en un `Datagram::Unreliable`
+const UNRELIABLE_OVERHEAD: usize = 12;
+
+/// Cada cuánto el cliente repite el pedido de conexión hasta que el host responde
+const HELLO_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Cada cuánto se reenvía un mensaje confiable sin ack
+const RESEND_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Si no se envió nada en este tiempo, se manda un heartbeat
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Sin noticias de un peer en este tiempo, se lo da por desconectado
+const PEER_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Serialize, Deserialize)]
+enum Datagram {
+    /// Pedido de conexión del cliente
+    Hello,
+    /// El host aceptó la conexión
+    Accept,
+    /// El peer cierra la conexión
+    Bye,
+    Heartbeat,
+    /// Fragmento de un mensaje confiable (`last` cierra el mensaje)
+    Reliable {
+        seq: u32,
+        last: bool,
+        data: Vec<u8>,
+    },
+    Ack {
+        seq: u32,
+    },
+    Unreliable(Vec<u8>),
+}
+
+struct UdpPeer {
+    id: PeerId,
+    /// El host aceptó la conexión (del lado del host, siempre)
+    connected: bool,
+    last_heard: Instant,
+    last_sent: Option<Instant>,
+    /// Próxima secuencia confiable a enviar
+    next_seq: u32,
+    /// Fragmentos confiables enviados sin ack (datos, último, hora del último envío)
+    unacked: BTreeMap<u32, (Vec<u8>, bool, Instant)>,
+    /// Próxima secuencia confiable a entregar
+    expected_seq: u32,
+    /// Fragmentos confiables que llegaron antes que alguno anterior
+    out_of_order: BTreeMap<u32, (Vec<u8>, bool)>,
+    /// Fragmentos ya entregados del mensaje confiable en curso
+    partial: Vec<u8>,
+}
+
+impl UdpPeer {
+    fn new(connected: bool, now: Instant) -> Self {
+        Self {
+            id: next_peer_id(),
+            connected,
+            last_heard: now,
+            last_sent: None,
+            next_seq: 0,
+            unacked: BTreeMap::new(),
+            expected_seq: 0,
+            out_of_order: BTreeMap::new(),
+            partial: Vec::new(),
+        }
+    }
+
+    fn idle_for(&self, now: Instant) -> Duration {
+        self.last_sent.map_or(Duration::MAX, |sent| now - sent)
+    }
+}
+
+/// Transporte UDP directo: `listen` para el host, `connect` para los clientes
+pub struct UdpTransport {
+    socket: UdpSocket,
+    /// Acepta conexiones nuevas (host)
+    listening: bool,
+    peers: HashMap<SocketAddr, UdpPeer>,
+    events: Vec<PeerEvent>,
+    received: [Vec<(PeerId, Vec<u8>)>; 2],
+    closed: bool,
+}
+
+impl UdpTransport {
+    /// Host: escucha conexiones en `addr` (ej. `0.0.0.0:7777`)
+    pub fn listen(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::bind(addr, true)
+    }
+
+    /// Cliente: se conecta al host en `addr` (ej. `192.168.0.10:7777`)
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let host = addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "dirección del host vacía")
+        })?;
+        let local = if host.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let mut transport = Self::bind(local, false)?;
+        transport
+            .peers
+            .insert(host, UdpPeer::new(false, Instant::now()));
+        transport.pump();
+        Ok(transport)
+    }
+
+    fn bind(addr: impl ToSocketAddrs, listening: bool) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            listening,
+            peers: HashMap::new(),
+            events: Vec::new(),
+            received: [Vec::new(), Vec::new()],
+            closed: false,
+        })
+    }
+
+    /// Dirección en la que escucha el socket (útil con el puerto 0)
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    fn send_datagram(&mut self, addr: SocketAddr, datagram: &Datagram) {
+        let Ok(bytes) = bincode::serialize(datagram) else {
+            return;
+        };
+        if let Err(e) = self.socket.send_to(&bytes, addr) {
+            eprintln!("⚠️ [UDP] Error enviando a {}: {}", addr, e);
+        }
+        if let Some(peer) = self.peers.get_mut(&addr) {
+            peer.last_sent = Some(Instant::now());
+        }
+    }
+
+    /// Lee los datagramas pendientes y hace el mantenimiento de las conexiones:
+    /// handshake, reenvíos, heartbeats y timeouts
+    fn pump(&mut self) {
+        let now = Instant::now();
+        let mut buf = vec![0u8; MAX_DATAGRAM];
+        loop {
+            match self.socket.recv_from(&mut buf) {
+                Ok((len, addr)) => {
+                    if let Ok(datagram) = bincode::deserialize(&buf[..len]) {
+                        self.handle(addr, datagram, now);
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                // En Windows, un ICMP "port unreachable" de un envío anterior
+                Err(e) if e.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(e) => {
+                    eprintln!("❌ [UDP] Error del socket: {}", e);
+                    self.closed = true;
+                    break;
+                }
+            }
+        }
+
+        let mut timed_out = Vec::new();
+        let mut outgoing = Vec::new();
+        for (addr, peer) in &mut self.peers {
+            if now - peer.last_heard > PEER_TIMEOUT {
+                timed_out.push(*addr);
+                continue;
+            }
+            if !peer.connected {
+                if peer.idle_for(now) >= HELLO_INTERVAL {
+                    outgoing.push((*addr, Datagram::Hello));
+                }
+                continue;
+            }
+            let before = outgoing.len();
+            for (seq, (data, last, sent_at)) in &mut peer.unacked {
+                if now - *sent_at >= RESEND_INTERVAL {
+                    *sent_at = now;
+                    outgoing.push((
+                        *addr,
+                        Datagram::Reliable {
+                            seq: *seq,
+                            last: *last,
+                            data: data.clone(),
+                        },
+                    ));
+                }
+            }
+            // Los reenvíos ya sirven de heartbeat
+            if outgoing.len() == before && peer.idle_for(now) >= HEARTBEAT_INTERVAL {
+                outgoing.push((*addr, Datagram::Heartbeat));
+            }
+        }
+        for (addr, datagram) in outgoing {
+            self.send_datagram(addr, &datagram);
+        }
+        for addr in timed_out {
+            println!("⌛ [UDP] Sin noticias de {}, desconectado", addr);
+            self.drop_peer(addr);
+        }
+    }
+
+    fn handle(&mut self, addr: SocketAddr, datagram: Datagram, now: Instant) {
+        if !self.peers.contains_key(&addr) {
+            // Solo el host acepta peers nuevos, y solo con un Hello
+            if !self.listening || !matches!(datagram, Datagram::Hello) {
+                return;
+            }
+            let peer = UdpPeer::new(true, now);
+            println!("🔗 [UDP] Peer conectado desde {}", addr);
+            self.events.push(PeerEvent::Connected(peer.id));
+            self.peers.insert(addr, peer);
+        }
+        let Some(peer) = self.peers.get_mut(&addr) else {
+            return;
+        };
+        peer.last_heard = now;
+
+        // Cualquier respuesta del host confirma la conexión (aunque el Accept se pierda)
+        if !peer.connected && !matches!(datagram, Datagram::Bye) {
+            peer.connected = true;
+            self.events.push(PeerEvent::Connected(peer.id));
+        }
+
+        match datagram {
+            // El cliente repite el Hello si no le llegó el Accept
+            Datagram::Hello => self.send_datagram(addr, &Datagram::Accept),
+            Datagram::Accept | Datagram::Heartbeat => {}
+            Datagram::Bye => self.drop_peer(addr),
+            Datagram::Ack { seq } => {
+                peer.unacked.remove(&seq);
+            }
+            Datagram::Reliable { seq, last, data } => {
+                if seq >= peer.expected_seq {
+                    peer.out_of_order.insert(seq, (data, last));
+                }
+                while let Some((data, last)) = peer.out_of_order.remove(&peer.expected_seq) {
+                    peer.expected_seq += 1;
+                    if !last {
+                        peer.partial.extend_from_slice(&data);
+                        continue;
+                    }
+                    let message = if peer.partial.is_empty() {
+                        data
+                    } else {
+                        peer.partial.extend_from_slice(&data);
+                        std::mem::take(&mut peer.partial)
+                    };
+                    self.received[Channel::Reliable.index()].push((peer.id, message));
+                }
+                // Ack también de los repetidos: el ack anterior pudo perderse
+                self.send_datagram(addr, &Datagram::Ack { seq });
+            }
+            Datagram::Unreliable(data) => {
+                self.received[Channel::Unreliable.index()].push((peer.id, data));
+            }
+        }
+    }
+
+    fn drop_peer(&mut self, addr: SocketAddr) {
+        let Some(peer) = self.peers.remove(&addr) else {
+            return;
+        };
+        if peer.connected {
+            self.events.push(PeerEvent::Disconnected(peer.id));
+        }
+        // Un cliente sin host ya no tiene con quién hablar
+        if !self.listening {
+            self.closed = true;
+        }
+    }
+
+    fn peer_addr(&self, peer_id: PeerId) -> Option<SocketAddr> {
+        self.peers
+            .iter()
+            .find(|(_, peer)| peer.id == peer_id)
+            .map(|(addr, _)| *addr)
+    }
+}
+
+impl Drop for UdpTransport {
+    fn drop(&mut self) {
+        let addrs: Vec<SocketAddr> = self.peers.keys().copied().collect();
+        for addr in addrs {
+            self.send_datagram(addr, &Datagram::Bye);
+        }
+    }
+}
+
+impl Transport for UdpTransport {
+    fn update_peers(&mut self) -> Vec<PeerEvent> {
+        self.pump();
+        std::mem::take(&mut self.events)
+    }
+
+    fn connected_peers(&self) -> Vec<PeerId> {
+        self.peers
+            .values()
+            .filter(|peer| peer.connected)
+            .map(|peer| peer.id)
+            .collect()
+    }
+
+    fn send(&mut self, channel: Channel, peer_id: PeerId, data: Vec<u8>) {
+        let Some(addr) = self.peer_addr(peer_id) else {
+            return;
+        };
+        let Some(peer) = self.peers.get_mut(&addr) else {
+            return;
+        };
+        match channel {
+            Channel::Reliable => {
+                let now = Instant::now();
+                let count = data.len().div_ceil(MAX_FRAGMENT).max(1);
+                let mut fragments = Vec::with_capacity(count);
+                for index in 0..count {
+                    let start = index * MAX_FRAGMENT;
+                    let end = (start + MAX_FRAGMENT).min(data.len());
+                    let seq = peer.next_seq;
+                    peer.next_seq += 1;
+                    let last = index + 1 == count;
+                    let chunk = data[start..end].to_vec();
+                    peer.unacked.insert(seq, (chunk.clone(), last, now));
+                    fragments.push(Datagram::Reliable {
+                        seq,
+                        last,
+                        data: chunk,
+                    });
+                }
+                // Antes de conectar queda en cola: se envía con los reenvíos
+                if !peer.connected {
+                    return;
+                }
+                for fragment in &fragments {
+                    self.send_datagram(addr, fragment);
+                }
+            }
+            Channel::Unreliable => {
+                if !peer.connected {
+                    return;
+                }
+                // Sin reenvíos no hay fragmentos: lo que no entra en un datagrama se descarta
+                if data.len() + UNRELIABLE_OVERHEAD > MAX_DATAGRAM {
+                    eprintln!(
+                        "⚠️ [UDP] Mensaje no confiable de {} bytes descartado: no entra en un datagrama",
+                        data.len()
+                    );
+                    return;
+                }
+                self.send_datagram(addr, &Datagram::Unreliable(data));
+            }
+        }
+    }
+
+    fn receive(&mut self, channel: Channel) -> Vec<(PeerId, Vec<u8>)> {
+        self.pump();
+        std::mem::take(&mut self.received[channel.index()])
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bombea los dos extremos hasta que `done` se cumple (o se agota el tiempo)
+    fn pump_until(
+        host: &mut UdpTransport,
+        client: &mut UdpTransport,
+        mut done: impl FnMut(&mut UdpTransport, &mut UdpTransport) -> bool,
+    ) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while Instant::now() < deadline {
+            host.pump();
+            client.pump();
+            if done(host, client) {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        false
+    }
+
+    fn connected_pair() -> (UdpTransport, UdpTransport) {
+        let mut host = UdpTransport::listen("127.0.0.1:0").unwrap();
+        let mut client = UdpTransport::connect(host.local_addr().unwrap()).unwrap();
+        assert!(pump_until(&mut host, &mut client, |h, c| {
+            !h.connected_peers().is_empty() && !c.connected_peers().is_empty()
+        }));
+        (host, client)
+    }
+
+    #[test]
+    fn test_handshake_connects_both_ends() {
+        let (mut host, mut client) = connected_pair();
+        assert!(matches!(
+            host.update_peers().as_slice(),
+            [PeerEvent::Connected(_)]
+        ));
+        assert!(matches!(
+            client.update_peers().as_slice(),
+            [PeerEvent::Connected(_)]
+        ));
+    }
+
+    #[test]
+    fn test_reliable_messages_arrive_in_order() {
+        let (mut host, mut client) = connected_pair();
+        let host_id = client.connected_peers()[0];
+        for i in 0..20u8 {
+            client.send(Channel::Reliable, host_id, vec![i]);
+        }
+
+        let mut received = Vec::new();
+        assert!(pump_until(&mut host, &mut client, |h, _| {
+            received.extend(h.receive(Channel::Reliable).into_iter().map(|(_, d)| d[0]));
+            received.len() == 20
+        }));
+        assert_eq!(received, (0..20).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_out_of_order_reliable_is_held_back() {
+        let (mut host, _client) = connected_pair();
+        let client_addr = *host.peers.keys().next().unwrap();
+        let now = Instant::now();
+
+        // Llega la secuencia 1 antes que la 0
+        host.handle(
+            client_addr,
+            Datagram::Reliable {
+                seq: 1,
+                last: true,
+                data: vec![1],
+            },
+            now,
+        );
+        assert!(host.received[Channel::Reliable.index()].is_empty());
+        host.handle(
+            client_addr,
+            Datagram::Reliable {
+                seq: 0,
+                last: true,
+                data: vec![0],
+            },
+            now,
+        );
+        let data: Vec<Vec<u8>> = host.received[Channel::Reliable.index()]
+            .drain(..)
+            .map(|(_, d)| d)
+            .collect();
+        assert_eq!(data, vec![vec![0], vec![1]]);
+
+        // Un repetido no se entrega dos veces
+        host.handle(
+            client_addr,
+            Datagram::Reliable {
+                seq: 0,
+                last: true,
+                data: vec![0],
+            },
+            now,
+        );
+        assert!(host.received[Channel::Reliable.index()].is_empty());
+    }
+
+    #[test]
+    fn test_reliable_messages_larger_than_a_datagram_are_fragmented() {
+        let (mut host, mut client) = connected_pair();
+        let host_id = client.connected_peers()[0];
+        let big: Vec<u8> = (0..3 * MAX_DATAGRAM).map(|i| (i % 251) as u8).collect();
+        client.send(Channel::Reliable, host_id, big.clone());
+        client.send(Channel::Reliable, host_id, Vec::new());
+        client.send(Channel::Reliable, host_id, vec![7]);
+
+        let mut received = Vec::new();
+        assert!(pump_until(&mut host, &mut client, |h, _| {
+            received.extend(h.receive(Channel::Reliable).into_iter().map(|(_, d)| d));
+            received.len() == 3
+        }));
+        assert_eq!(received, vec![big, Vec::new(), vec![7]]);
+        // Todos los fragmentos quedaron confirmados
+        assert!(pump_until(&mut host, &mut client, |_, c| c
+            .peers
+            .values()
+            .all(|peer| peer.unacked.is_empty())));
+    }
+
+    #[test]
+    fn test_dropping_the_client_disconnects_it() {
+        let (mut host, client) = connected_pair();
+        host.update_peers();
+        drop(client);
+
+        let mut events = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while events.is_empty() && Instant::now() < deadline {
+            events = host.update_peers();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(matches!(events.as_slice(), [PeerEvent::Disconnected(_)]));
+        assert!(host.connected_peers().is_empty());
+    }
+}
@@ -0,0 +1,69 @@
+use matchbox_socket::{ChannelConfig, PeerState, WebRtcSocket};
+
+use super::{Channel, PeerEvent, PeerId, Transport};
+
+/// WebRTC con matchbox, a través de la señalización del proxy
+pub struct WebRtcTransport {
+    socket: WebRtcSocket,
+    /// Loop de señalización de matchbox (si termina, se perdió la conexión con el proxy)
+    socket_loop: tokio::task::JoinHandle<()>,
+}
+
+impl WebRtcTransport {
+    /// Conecta a la sala del proxy en `room_url`. Necesita un runtime de Tokio
+    pub fn connect(room_url: &str) -> Self {
+        let (socket, loop_fut) = WebRtcSocket::builder(room_url)
+            .add_channel(ChannelConfig::reliable()) // Channel::Reliable
+            .add_channel(ChannelConfig::unreliable()) // Channel::Unreliable
+            .build();
+        let socket_loop = tokio::spawn(async move {
+            let _ = loop_fut.await;
+        });
+        Self {
+            socket,
+            socket_loop,
+        }
+    }
+}
+
+impl Drop for WebRtcTransport {
+    fn drop(&mut self) {
+        self.socket_loop.abort();
+    }
+}
+
+impl Transport for WebRtcTransport {
+    fn update_peers(&mut self) -> Vec<PeerEvent> {
+        self.socket
+            .update_peers()
+            .into_iter()
+            .map(|(peer_id, state)| match state {
+                PeerState::Connected => PeerEvent::Connected(peer_id),
+                PeerState::Disconnected => PeerEvent::Disconnected(peer_id),
+            })
+            .collect()
+    }
+
+    fn connected_peers(&self) -> Vec<PeerId> {
+        self.socket.connected_peers().collect()
+    }
+
+    fn send(&mut self, channel: Channel, peer: PeerId, data: Vec<u8>) {
+        self.socket
+            .channel_mut(channel.index())
+            .send(data.into(), peer);
+    }
+
+    fn receive(&mut self, channel: Channel) -> Vec<(PeerId, Vec<u8>)> {
+        self.socket
+            .channel_mut(channel.index())
+            .receive()
+            .into_iter()
+            .map(|(peer_id, packet)| (peer_id, packet.to_vec()))
+            .collect()
+    }
+
+    fn is_closed(&self) -> bool {
+        self.socket_loop.is_finished()
+    }
+}
@@ -5,10 +5,16 @@ use bevy_egui::{egui, EguiContexts};
 
 use crate::components::RemotePlayer;
 use crate::local_players::LocalPlayers;
-use crate::resources::{AdminPanelState, ClientMatchSlots, ConnectionConfig, NetworkChannels};
-use crate::shared::protocol::ControlMessage;
+use crate::resources::{
+    AdminPanelState, ChatState, ClientMatchSlots, ConnectionConfig, NetworkChannels,
+    PlayerLatencies,
+};
+use crate::shared::match_slots::MatchSlots;
+use crate::shared::protocol::{BotDifficulty, ControlMessage};
 use crate::states::AppState;
 
+use super::scoreboard::ping_color;
+
 // Background colors for drop zones
 const RED_STARTER_BG: egui::Color32 = egui::Color32::from_rgb(120, 40, 40);
 const RED_SUB_BG: egui::Color32 = egui::Color32::from_rgb(80, 30, 30);
@@ -17,11 +23,13 @@ const BLUE_SUB_BG: egui::Color32 = egui::Color32::from_rgb(30, 40, 80);
 const SPECTATOR_BG: egui::Color32 = egui::Color32::from_rgb(60, 60, 60);
 
 /// Sistema que detecta Escape para toggle del panel de admin
+/// (con el chat abierto, Escape cierra el chat)
 pub fn toggle_admin_panel(
     keyboard: Res<ButtonInput<KeyCode>>,
+    chat: Res<ChatState>,
     mut admin_state: ResMut<AdminPanelState>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if keyboard.just_pressed(KeyCode::Escape) && !chat.open {
         admin_state.is_open = !admin_state.is_open;
     }
 }
@@ -52,6 +60,7 @@ pub fn admin_panel_ui(
     local_players: Res<LocalPlayers>,
     channels: Res<NetworkChannels>,
     match_slots: Res<ClientMatchSlots>,
+    latencies: Res<PlayerLatencies>,
 ) {
     if !admin_state.is_open {
         return;
@@ -79,11 +88,17 @@ pub fn admin_panel_ui(
         .show(ctx, |ui| {
             // Room ID header
             ui.horizontal(|ui| {
-                ui.label("Room:");
-                ui.label(egui::RichText::new(&config.room).monospace().small());
-                if ui.small_button("📋").on_hover_text("Copiar").clicked() {
-                    if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                        let _ = clipboard.set_text(&config.room);
+                if config.offline {
+                    ui.label("Práctica sin conexión");
+                } else if config.lan {
+                    ui.label(format!("Red local: {}", config.lan_host));
+                } else {
+                    ui.label("Room:");
+                    ui.label(egui::RichText::new(&config.room).monospace().small());
+                    if ui.small_button("📋").on_hover_text("Copiar").clicked() {
+                        if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                            let _ = clipboard.set_text(&config.room);
+                        }
                     }
                 }
                 if is_admin {
@@ -115,10 +130,11 @@ pub fn admin_panel_ui(
                                 "Titulares",
                                 &slots.teams[0].starters,
                                 &all_players,
-                                &slots.admins,
+                                slots,
                                 is_admin,
                                 RED_STARTER_BG,
                                 &channels,
+                                &latencies,
                             ) {
                                 send_move_player(&channels, player_id, Some(0), Some(true));
                             }
@@ -132,10 +148,11 @@ pub fn admin_panel_ui(
                                 "Suplentes",
                                 &slots.teams[0].substitutes,
                                 &all_players,
-                                &slots.admins,
+                                slots,
                                 is_admin,
                                 RED_SUB_BG,
                                 &channels,
+                                &latencies,
                             ) {
                                 send_move_player(&channels, player_id, Some(0), Some(false));
                             }
@@ -156,10 +173,11 @@ pub fn admin_panel_ui(
                                 "",
                                 &slots.spectators,
                                 &all_players,
-                                &slots.admins,
+                                slots,
                                 is_admin,
                                 SPECTATOR_BG,
                                 &channels,
+                                &latencies,
                             ) {
                                 send_move_player(&channels, player_id, None, None);
                             }
@@ -184,10 +202,11 @@ pub fn admin_panel_ui(
                                 "Titulares",
                                 &slots.teams[1].starters,
                                 &all_players,
-                                &slots.admins,
+                                slots,
                                 is_admin,
                                 BLUE_STARTER_BG,
                                 &channels,
+                                &latencies,
                             ) {
                                 send_move_player(&channels, player_id, Some(1), Some(true));
                             }
@@ -201,10 +220,11 @@ pub fn admin_panel_ui(
                                 "Suplentes",
                                 &slots.teams[1].substitutes,
                                 &all_players,
-                                &slots.admins,
+                                slots,
                                 is_admin,
                                 BLUE_SUB_BG,
                                 &channels,
+                                &latencies,
                             ) {
                                 send_move_player(&channels, player_id, Some(1), Some(false));
                             }
@@ -214,6 +234,37 @@ pub fn admin_panel_ui(
 
             ui.separator();
 
+            // Bots: se agregan como titulares del equipo elegido
+            if is_admin {
+                ui.horizontal(|ui| {
+                    ui.label("Bots:");
+                    egui::ComboBox::from_id_salt("bot_difficulty")
+                        .selected_text(admin_state.bot_difficulty.label())
+                        .show_ui(ui, |ui| {
+                            for difficulty in BotDifficulty::ALL {
+                                ui.selectable_value(
+                                    &mut admin_state.bot_difficulty,
+                                    difficulty,
+                                    difficulty.label(),
+                                );
+                            }
+                        });
+                    if ui.button("🤖 + Rojo").clicked() {
+                        send_add_bot(&channels, 0, admin_state.bot_difficulty);
+                    }
+                    if ui.button("🤖 + Azul").clicked() {
+                        send_add_bot(&channels, 1, admin_state.bot_difficulty);
+                    }
+                });
+                ui.label(
+                    egui::RichText::new("Clic derecho en un bot para quitarlo")
+                        .small()
+                        .color(egui::Color32::GRAY),
+                );
+
+                ui.separator();
+            }
+
             // Room actions
             ui.horizontal(|ui| {
                 if ui.button("Salir").clicked() {
@@ -225,7 +276,22 @@ pub fn admin_panel_ui(
                         }
                     }
                     admin_state.is_open = false;
-                    next_state.set(AppState::RoomSelection);
+                    // Sin proxy no hay salas que listar
+                    next_state.set(if config.offline || config.lan {
+                        AppState::Menu
+                    } else {
+                        AppState::RoomSelection
+                    });
+                }
+
+                if is_admin
+                    && ui
+                        .button("⏪ Repetición")
+                        .on_hover_text("Repetir los últimos segundos a todos (con la pelota detenida)")
+                        .clicked()
+                {
+                    send_request_replay(&channels);
+                    admin_state.is_open = false;
                 }
 
                 if ui.button("Cerrar (Esc)").clicked() {
@@ -242,10 +308,11 @@ fn render_drop_zone(
     label: &str,
     player_ids: &HashSet<u32>,
     all_players: &[PlayerInfo],
-    admins: &HashSet<u32>,
+    slots: &MatchSlots,
     is_admin: bool,
     bg_color: egui::Color32,
     channels: &NetworkChannels,
+    latencies: &PlayerLatencies,
 ) -> Option<u32> {
     let frame = egui::Frame::new()
         .fill(bg_color)
@@ -270,7 +337,15 @@ fn render_drop_zone(
             sorted_ids.sort();
 
             for player_id in sorted_ids {
-                render_draggable_player(ui, player_id, all_players, admins, is_admin, channels);
+                render_draggable_player(
+                    ui,
+                    player_id,
+                    all_players,
+                    slots,
+                    is_admin,
+                    channels,
+                    latencies,
+                );
             }
         }
     });
@@ -284,12 +359,14 @@ fn render_draggable_player(
     ui: &mut egui::Ui,
     player_id: u32,
     all_players: &[PlayerInfo],
-    admins: &HashSet<u32>,
+    slots: &MatchSlots,
     is_admin: bool,
     channels: &NetworkChannels,
+    latencies: &PlayerLatencies,
 ) {
     let player_name = get_player_name(player_id, all_players);
-    let is_player_admin = admins.contains(&player_id);
+    let is_player_admin = slots.is_admin(player_id);
+    let is_bot = slots.is_bot(player_id);
 
     if is_admin {
         // Admin can drag players
@@ -301,22 +378,34 @@ fn render_draggable_player(
                     if is_player_admin {
                         ui.label(egui::RichText::new("👑").small());
                     }
+                    if is_bot {
+                        ui.label(egui::RichText::new("🤖").small());
+                    }
                     ui.label(egui::RichText::new(&player_name).small());
+                    render_ping(ui, player_id, latencies);
                 });
             })
             .response;
 
         // Context menu for admin actions
         response.context_menu(|ui| {
+            if is_bot {
+                if ui.button("🤖 Quitar bot").clicked() {
+                    send_remove_bot(channels, player_id);
+                    ui.close();
+                }
+                return;
+            }
+
             if is_player_admin {
                 if ui.button("👑 Quitar Admin").clicked() {
                     send_toggle_admin(channels, player_id, false);
-                    ui.close_menu();
+                    ui.close();
                 }
             } else {
                 if ui.button("👑 Dar Admin").clicked() {
                     send_toggle_admin(channels, player_id, true);
-                    ui.close_menu();
+                    ui.close();
                 }
             }
 
@@ -325,7 +414,7 @@ fn render_draggable_player(
                 .clicked()
             {
                 send_kick_player(channels, player_id);
-                ui.close_menu();
+                ui.close();
             }
         });
     } else {
@@ -334,11 +423,28 @@ fn render_draggable_player(
             if is_player_admin {
                 ui.label(egui::RichText::new("👑").small());
             }
+            if is_bot {
+                ui.label(egui::RichText::new("🤖").small());
+            }
             ui.label(egui::RichText::new(&player_name).small());
+            render_ping(ui, player_id, latencies);
         });
     }
 }
 
+/// Ping del jugador (el jitter se ve al pasar el mouse)
+fn render_ping(ui: &mut egui::Ui, player_id: u32, latencies: &PlayerLatencies) {
+    let Some(latency) = latencies.0.get(&player_id) else {
+        return;
+    };
+    ui.label(
+        egui::RichText::new(format!("{} ms", latency.rtt_ms))
+            .color(ping_color(latency.rtt_ms))
+            .small(),
+    )
+    .on_hover_text(format!("Jitter: ±{} ms", latency.jitter_ms));
+}
+
 /// Sends a MovePlayer control message
 fn send_move_player(
     channels: &NetworkChannels,
@@ -371,3 +477,29 @@ fn send_toggle_admin(channels: &NetworkChannels, player_id: u32, is_admin: bool)
         let _ = control_tx.send(msg);
     }
 }
+
+/// Sends a RequestReplay control message
+fn send_request_replay(channels: &NetworkChannels) {
+    if let Some(ref control_tx) = channels.control_sender {
+        let _ = control_tx.send(ControlMessage::RequestReplay);
+    }
+}
+
+/// Sends an AddBot control message
+fn send_add_bot(channels: &NetworkChannels, team_index: u8, difficulty: BotDifficulty) {
+    if let Some(ref control_tx) = channels.control_sender {
+        let msg = ControlMessage::AddBot {
+            team_index,
+            difficulty,
+        };
+        let _ = control_tx.send(msg);
+    }
+}
+
+/// Sends a RemoveBot control message
+fn send_remove_bot(channels: &NetworkChannels, player_id: u32) {
+    if let Some(ref control_tx) = channels.control_sender {
+        let msg = ControlMessage::RemoveBot { player_id };
+        let _ = control_tx.send(msg);
+    }
+}
@@ -0,0 +1,190 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::local_players::LocalPlayers;
+use crate::resources::{AdminPanelState, ChatState, MyPlayerId, NetworkChannels};
+use crate::shared::protocol::{ChatKind, ControlMessage, GameConfig, CHAT_MAX_LEN};
+
+/// Mensajes visibles en el overlay
+const CHAT_VISIBLE_LINES: usize = 8;
+
+/// Segundos que se ve un mensaje con el chat cerrado
+const CHAT_FADE_SECS: f64 = 10.0;
+
+/// Enter abre la caja de chat (enviar y cerrar lo maneja la UI)
+pub fn toggle_chat(keyboard: Res<ButtonInput<KeyCode>>, mut chat: ResMut<ChatState>) {
+    if chat.open {
+        return;
+    }
+    if keyboard.just_pressed(KeyCode::Enter) || keyboard.just_pressed(KeyCode::NumpadEnter) {
+        chat.open = true;
+        chat.input.clear();
+    }
+}
+
+/// Etiqueta y color de la audiencia del mensaje
+fn kind_label(kind: ChatKind) -> (&'static str, egui::Color32) {
+    match kind {
+        ChatKind::All => ("Todos", egui::Color32::WHITE),
+        ChatKind::Team => ("Equipo", egui::Color32::LIGHT_GREEN),
+        ChatKind::Announcement => ("📢 Anuncio", egui::Color32::GOLD),
+        ChatKind::System => ("Host", egui::Color32::GRAY),
+    }
+}
+
+/// Siguiente audiencia al apretar Tab (los anuncios solo para admins)
+fn next_kind(kind: ChatKind, is_admin: bool) -> ChatKind {
+    match kind {
+        ChatKind::All => ChatKind::Team,
+        ChatKind::Team if is_admin => ChatKind::Announcement,
+        _ => ChatKind::All,
+    }
+}
+
+/// Color del nombre según el equipo del que escribe (gris = espectador)
+fn team_color32(config: &GameConfig, team_index: Option<u8>) -> egui::Color32 {
+    let Some((r, g, b)) = team_index.and_then(|t| config.team_colors.get(t as usize).copied())
+    else {
+        return egui::Color32::LIGHT_GRAY;
+    };
+    egui::Color32::from_rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
+}
+
+/// Canal de control y jugador con el que se firman los mensajes
+#[derive(SystemParam)]
+pub struct ChatSender<'w> {
+    pub channels: Res<'w, NetworkChannels>,
+    pub local_players: Res<'w, LocalPlayers>,
+    pub my_player_id: Res<'w, MyPlayerId>,
+}
+
+impl ChatSender<'_> {
+    /// Envía el mensaje como el primer jugador local (o el único, en modo legacy)
+    fn send(&self, kind: ChatKind, text: String) {
+        let player_id = self
+            .local_players
+            .players
+            .iter()
+            .find_map(|p| p.server_player_id)
+            .or(self.my_player_id.0);
+        if let (Some(player_id), Some(control_tx)) = (player_id, &self.channels.control_sender) {
+            let _ = control_tx.send(ControlMessage::SendChat {
+                player_id,
+                kind,
+                text,
+            });
+        }
+    }
+}
+
+/// Overlay de chat (abajo a la izquierda): últimos mensajes y caja de texto.
+/// Enter envía, Esc cierra y Tab cambia entre Todos / Equipo / Anuncio.
+pub fn chat_ui(
+    mut contexts: EguiContexts,
+    mut chat: ResMut<ChatState>,
+    admin_state: Res<AdminPanelState>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+    sender: ChatSender,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+    let now = time.elapsed_secs_f64();
+
+    if chat.kind == ChatKind::Announcement && !admin_state.is_admin {
+        chat.kind = ChatKind::All;
+    }
+
+    let mut send = false;
+    let mut close = false;
+
+    egui::Area::new(egui::Id::new("chat"))
+        .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
+        .show(ctx, |ui| {
+            ui.set_max_width(420.0);
+
+            let start = chat.messages.len().saturating_sub(CHAT_VISIBLE_LINES);
+            for entry in chat.messages.iter().skip(start) {
+                if !chat.open && now - entry.received_at > CHAT_FADE_SECS {
+                    continue;
+                }
+                let message = &entry.message;
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 4.0;
+                    match message.kind {
+                        ChatKind::System => {
+                            ui.label(
+                                egui::RichText::new(&message.text)
+                                    .italics()
+                                    .color(egui::Color32::GRAY),
+                            );
+                        }
+                        kind => {
+                            if kind != ChatKind::All {
+                                let (label, color) = kind_label(kind);
+                                ui.label(egui::RichText::new(format!("[{}]", label)).color(color));
+                            }
+                            ui.label(
+                                egui::RichText::new(format!("{}:", message.sender_name))
+                                    .color(team_color32(&config, message.team_index))
+                                    .strong(),
+                            );
+                            let text_color = if kind == ChatKind::Announcement {
+                                egui::Color32::GOLD
+                            } else {
+                                egui::Color32::WHITE
+                            };
+                            ui.label(egui::RichText::new(&message.text).color(text_color));
+                        }
+                    }
+                });
+            }
+
+            if !chat.open {
+                return;
+            }
+
+            // Tab cambia la audiencia (consumirlo evita que egui mueva el foco)
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+                chat.kind = next_kind(chat.kind, admin_state.is_admin);
+            }
+
+            ui.horizontal(|ui| {
+                let (label, color) = kind_label(chat.kind);
+                if ui
+                    .button(egui::RichText::new(label).color(color))
+                    .on_hover_text("Tab para cambiar")
+                    .clicked()
+                {
+                    chat.kind = next_kind(chat.kind, admin_state.is_admin);
+                }
+
+                let response = ui.add_sized(
+                    [320.0, 22.0],
+                    egui::TextEdit::singleline(&mut chat.input)
+                        .char_limit(CHAT_MAX_LEN)
+                        .hint_text("Escribí un mensaje..."),
+                );
+                response.request_focus();
+
+                if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    close = true;
+                } else if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    send = true;
+                }
+            });
+        });
+
+    if send {
+        let text = chat.input.trim().to_string();
+        if !text.is_empty() {
+            sender.send(chat.kind, text);
+        }
+        close = true;
+    }
+
+    if close {
+        chat.open = false;
+        chat.input.clear();
+    }
+}
@@ -1,12 +1,14 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 
-use crate::resources::CreateRoomConfig;
+use crate::resources::{ConnectionConfig, CreateRoomConfig};
+use crate::shared::map::{MAX_MAP_SCALE, MIN_MAP_SCALE};
 use crate::states::AppState;
 
 pub fn create_room_ui(
     mut contexts: EguiContexts,
     mut create_config: ResMut<CreateRoomConfig>,
+    config: Res<ConnectionConfig>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
     let Ok(ctx) = contexts.ctx_mut() else { return };
@@ -14,7 +16,13 @@ pub fn create_room_ui(
     egui::CentralPanel::default().show(ctx, |ui| {
         ui.vertical_centered(|ui| {
             ui.add_space(30.0);
-            ui.heading(egui::RichText::new("Crear Sala").size(36.0));
+            // La práctica sin conexión usa la misma pantalla, sin los datos de la sala
+            let title = if config.offline {
+                "Práctica"
+            } else {
+                "Crear Sala"
+            };
+            ui.heading(egui::RichText::new(title).size(36.0));
             ui.add_space(20.0);
 
             // Botón volver
@@ -25,7 +33,11 @@ pub fn create_room_ui(
                 )
                 .clicked()
             {
-                next_state.set(AppState::RoomSelection);
+                next_state.set(if config.offline {
+                    AppState::Menu
+                } else {
+                    AppState::RoomSelection
+                });
             }
 
             ui.add_space(30.0);
@@ -35,22 +47,24 @@ pub fn create_room_ui(
                 ui.set_width(400.0);
                 ui.add_space(10.0);
 
-                ui.horizontal(|ui| {
-                    ui.label("Nombre de la sala:");
-                    ui.add_sized(
-                        [250.0, 24.0],
-                        egui::TextEdit::singleline(&mut create_config.room_name),
-                    );
-                });
+                if !config.offline {
+                    ui.horizontal(|ui| {
+                        ui.label("Nombre de la sala:");
+                        ui.add_sized(
+                            [250.0, 24.0],
+                            egui::TextEdit::singleline(&mut create_config.room_name),
+                        );
+                    });
 
-                ui.add_space(10.0);
+                    ui.add_space(10.0);
 
-                ui.horizontal(|ui| {
-                    ui.label("Máximo de jugadores:");
-                    ui.add(egui::Slider::new(&mut create_config.max_players, 2..=16));
-                });
+                    ui.horizontal(|ui| {
+                        ui.label("Máximo de jugadores:");
+                        ui.add(egui::Slider::new(&mut create_config.max_players, 2..=16));
+                    });
 
-                ui.add_space(10.0);
+                    ui.add_space(10.0);
+                }
 
                 ui.horizontal(|ui| {
                     ui.label("Mapa:");
@@ -74,14 +88,71 @@ pub fn create_room_ui(
 
                 ui.horizontal(|ui| {
                     ui.label("Escala del mapa:");
-                    ui.add(egui::Slider::new(&mut create_config.scale, 0.5..=2.0).step_by(0.1));
+                    ui.add(
+                        egui::Slider::new(&mut create_config.scale, MIN_MAP_SCALE..=MAX_MAP_SCALE)
+                            .step_by(0.1),
+                    );
                 });
 
                 ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("Tiempo (minutos):");
+                    ui.add(egui::Slider::new(&mut create_config.time_limit_minutes, 0..=20))
+                        .on_hover_text("0 = sin límite");
+                });
+
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("Goles para ganar:");
+                    ui.add(egui::Slider::new(&mut create_config.score_limit, 0..=15))
+                        .on_hover_text("0 = sin límite");
+                });
+
+                ui.add_space(10.0);
+
+                if !config.offline {
+                    ui.horizontal(|ui| {
+                        ui.label("Contraseña:");
+                        ui.add_sized(
+                            [200.0, 24.0],
+                            egui::TextEdit::singleline(&mut create_config.password)
+                                .password(true)
+                                .hint_text("(sin contraseña)"),
+                        );
+                    });
+
+                    ui.add_space(10.0);
+
+                    ui.checkbox(&mut create_config.private, "Sala privada")
+                        .on_hover_text("No aparece en la lista; se entra con el Room ID");
+
+                    ui.add_space(10.0);
+                }
             });
 
             ui.add_space(30.0);
 
+            if config.offline {
+                ui.label(
+                    egui::RichText::new("Sin servidor: agrega bots desde el panel de admin (Esc)")
+                        .color(egui::Color32::GRAY),
+                );
+                ui.add_space(10.0);
+                if ui
+                    .add_sized(
+                        [200.0, 50.0],
+                        egui::Button::new(egui::RichText::new("▶ Jugar").size(20.0)),
+                    )
+                    .clicked()
+                {
+                    println!("⚽ Iniciando práctica sin conexión");
+                    next_state.set(AppState::Connecting);
+                }
+                return;
+            }
+
             // Botón crear
             if ui
                 .add_sized(
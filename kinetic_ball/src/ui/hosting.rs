@@ -2,7 +2,7 @@ use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 
 use crate::assets::DEFAULT_MAP;
-use crate::host;
+use crate::host::{self, RoomAccess};
 use crate::resources::{ConnectionConfig, CreateRoomConfig};
 use crate::states::AppState;
 
@@ -10,12 +10,13 @@ pub fn start_hosting(config: Res<ConnectionConfig>, mut create_config: ResMut<Cr
     let server_host = config.server_host.clone();
     let room_name = create_config.room_name.clone();
     let max_players = create_config.max_players;
-    let map_path = if create_config.map_path.is_empty() {
-        None
-    } else {
-        Some(create_config.map_path.clone())
-    };
+    let map_path = create_config.map();
     let scale = create_config.scale;
+    let rules = create_config.rules();
+    let access = RoomAccess {
+        password: Some(create_config.password.clone()).filter(|p| !p.is_empty()),
+        private: create_config.private,
+    };
 
     // Generar room_id único
     let room_id = format!(
@@ -44,6 +45,8 @@ pub fn start_hosting(config: Res<ConnectionConfig>, mut create_config: ResMut<Cr
             server_host,
             room_name,
             max_players,
+            rules,
+            access,
         );
     });
 }
@@ -87,6 +90,8 @@ pub fn hosting_ui(
             {
                 if let Some(room_id) = create_config.created_room_ids.last() {
                     connection_config.room = room_id.clone();
+                    connection_config.room_password =
+                        Some(create_config.password.clone()).filter(|p| !p.is_empty());
                     println!("🎮 Entrando a sala propia: {}", room_id);
                     next_state.set(AppState::Connecting);
                 }
@@ -2,7 +2,9 @@ use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts, PrimaryEguiContext};
 
 use crate::components::MenuCamera;
+use crate::replay::{get_replays_dir, ReplayPlayback};
 use crate::resources::ConnectionConfig;
+use crate::shared::replay::REPLAY_EXTENSION;
 use crate::states::AppState;
 
 pub fn setup_menu_camera_if_needed(mut commands: Commands, menu_camera: Query<&MenuCamera>) {
@@ -22,6 +24,7 @@ pub fn menu_ui(
     mut contexts: EguiContexts,
     mut config: ResMut<ConnectionConfig>,
     mut next_state: ResMut<NextState<AppState>>,
+    mut playback: ResMut<ReplayPlayback>,
 ) {
     let Ok(ctx) = contexts.ctx_mut() else { return };
     egui::CentralPanel::default().show(ctx, |ui| {
@@ -87,6 +90,8 @@ pub fn menu_ui(
                     .clicked()
                 {
                     println!("📋 Buscando salas en {}", config.server_host);
+                    config.offline = false;
+                    config.lan = false;
                     next_state.set(AppState::RoomSelection);
                 }
 
@@ -114,9 +119,76 @@ pub fn menu_ui(
                     .clicked()
                 {
                     println!("🏗️ Crear nueva sala");
+                    config.offline = false;
+                    config.lan = false;
                     next_state.set(AppState::CreateRoom);
                 }
             });
+
+            // Práctica sin conexión: el host corre en este proceso, sin servidor
+            ui.add_space(20.0);
+            if ui
+                .add_sized(
+                    [150.0, 40.0],
+                    egui::Button::new(egui::RichText::new("⚽ Práctica").size(18.0)),
+                )
+                .on_hover_text("Jugar sin conexión, con jugadores locales y bots")
+                .clicked()
+            {
+                println!("⚽ Práctica sin conexión");
+                config.offline = true;
+                config.lan = false;
+                next_state.set(AppState::CreateRoom);
+            }
+
+            // Red local: directo a la dirección del host, sin proxy ni lista de salas
+            ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.add_space(ui.available_width() / 2.0 - 180.0);
+                ui.label("Red local:");
+                ui.add_sized(
+                    [170.0, 24.0],
+                    egui::TextEdit::singleline(&mut config.lan_host).hint_text("ip:puerto"),
+                );
+                if ui
+                    .button("🏠 Unirse")
+                    .on_hover_text("Entrar a un host de la red local (host --lan)")
+                    .clicked()
+                    && !config.lan_host.trim().is_empty()
+                {
+                    println!("🏠 Uniéndose por red local a {}", config.lan_host);
+                    config.offline = false;
+                    config.lan = true;
+                    next_state.set(AppState::Connecting);
+                }
+            });
+
+            // Replays de partidos guardados
+            ui.add_space(10.0);
+            if ui
+                .add_sized(
+                    [150.0, 40.0],
+                    egui::Button::new(egui::RichText::new("🎬 Replays").size(18.0)),
+                )
+                .clicked()
+            {
+                let mut dialog = rfd::FileDialog::new().add_filter("Replays", &[REPLAY_EXTENSION]);
+                if let Some(dir) = get_replays_dir() {
+                    dialog = dialog.set_directory(dir);
+                }
+                if let Some(path) = dialog.pick_file() {
+                    match playback.load_file(&path) {
+                        Ok(()) => next_state.set(AppState::Replay),
+                        Err(e) => {
+                            println!("❌ [Replay] {}", e);
+                            playback.load_error = Some(e);
+                        }
+                    }
+                }
+            }
+            if let Some(error) = &playback.load_error {
+                ui.colored_label(egui::Color32::LIGHT_RED, error);
+            }
         });
     });
 }
@@ -6,6 +6,9 @@ mod hosting;
 mod local_players_setup;
 mod gamepad_config;
 mod admin_panel;
+mod scoreboard;
+mod chat;
+mod replay;
 
 pub use menu::{setup_menu_camera_if_needed, cleanup_menu_camera, menu_ui};
 pub use settings::settings_ui;
@@ -15,3 +18,6 @@ pub use hosting::{start_hosting, hosting_ui};
 pub use local_players_setup::local_players_setup_ui;
 pub use gamepad_config::gamepad_config_ui;
 pub use admin_panel::{toggle_admin_panel, admin_panel_ui};
+pub use scoreboard::scoreboard_ui;
+pub use chat::{toggle_chat, chat_ui};
+pub use replay::{instant_replay_ui, replay_ui};
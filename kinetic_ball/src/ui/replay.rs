@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::scoreboard::format_clock;
+use crate::components::RemotePlayer;
+use crate::replay::{InstantReplay, ReplayCamera, ReplayPlayback, REPLAY_SPEEDS};
+use crate::states::AppState;
+
+/// Controles del replay (abajo): play/pausa, línea de tiempo, velocidad y cámara
+pub fn replay_ui(
+    mut contexts: EguiContexts,
+    mut playback: ResMut<ReplayPlayback>,
+    players: Query<&RemotePlayer>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+
+    egui::TopBottomPanel::bottom("replay_controls").show(ctx, |ui| {
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            let play_label = if playback.paused { "▶" } else { "⏸" };
+            if ui
+                .button(egui::RichText::new(play_label).size(18.0))
+                .on_hover_text("Espacio")
+                .clicked()
+            {
+                playback.toggle_pause();
+            }
+
+            let duration = playback.duration();
+            let mut time = playback.time();
+            ui.label(format!(
+                "{} / {}",
+                format_clock(time),
+                format_clock(duration)
+            ));
+            ui.spacing_mut().slider_width = (ui.available_width() - 420.0).max(100.0);
+            let slider = ui.add(egui::Slider::new(&mut time, 0.0..=duration).show_value(false));
+            if slider.changed() {
+                playback.seek(time);
+            }
+
+            ui.separator();
+            for speed in REPLAY_SPEEDS {
+                if ui
+                    .selectable_label(playback.speed == speed, format!("{}x", speed))
+                    .clicked()
+                {
+                    playback.speed = speed;
+                }
+            }
+
+            ui.separator();
+            let camera_label = match playback.camera {
+                ReplayCamera::Ball => "Pelota".to_string(),
+                ReplayCamera::Free => "Libre".to_string(),
+                ReplayCamera::Player(id) => players
+                    .iter()
+                    .find(|p| p.id == id)
+                    .map_or_else(|| format!("Jugador {}", id), |p| p.name.clone()),
+            };
+            egui::ComboBox::from_id_salt("replay_camera")
+                .selected_text(format!("🎥 {}", camera_label))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut playback.camera, ReplayCamera::Ball, "Pelota");
+                    ui.selectable_value(&mut playback.camera, ReplayCamera::Free, "Libre (WASD)");
+                    let mut sorted: Vec<&RemotePlayer> = players.iter().collect();
+                    sorted.sort_by_key(|p| (p.team_index, p.id));
+                    for player in sorted {
+                        ui.selectable_value(
+                            &mut playback.camera,
+                            ReplayCamera::Player(player.id),
+                            &player.name,
+                        );
+                    }
+                });
+
+            if ui.button("Salir").clicked() {
+                next_state.set(AppState::Menu);
+            }
+        });
+        ui.add_space(4.0);
+    });
+}
+
+/// Cartel de la repetición instantánea, con un botón para volver al juego
+pub fn instant_replay_ui(mut contexts: EguiContexts, mut instant_replay: ResMut<InstantReplay>) {
+    if !instant_replay.is_playing() {
+        return;
+    }
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+
+    egui::Area::new(egui::Id::new("instant_replay"))
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new("⏪ REPETICIÓN")
+                            .size(20.0)
+                            .strong()
+                            .color(egui::Color32::from_rgb(255, 210, 60)),
+                    );
+                    if ui.button("Saltar").clicked() {
+                        instant_replay.skip();
+                    }
+                });
+            });
+        });
+}
@@ -85,6 +85,78 @@ pub fn check_rooms_fetch(mut room_list: ResMut<RoomList>, mut fetch_channel: Res
     }
 }
 
+/// Entra a una sala; si tiene contraseña primero se la pide al usuario
+fn request_join(
+    room: &RoomInfo,
+    config: &mut ConnectionConfig,
+    room_list: &mut RoomList,
+    next_state: &mut NextState<AppState>,
+) {
+    if room.has_password {
+        room_list.password_prompt_room = Some(room.room_id.clone());
+        room_list.password_input.clear();
+        return;
+    }
+    config.room = room.room_id.clone();
+    config.room_password = None;
+    println!("🎮 Entrando a sala: {}", room.room_id);
+    next_state.set(AppState::Connecting);
+}
+
+/// Ventana para ingresar la contraseña de la sala elegida
+fn password_prompt_ui(
+    ctx: &egui::Context,
+    config: &mut ConnectionConfig,
+    room_list: &mut RoomList,
+    next_state: &mut NextState<AppState>,
+) {
+    let Some(room_id) = room_list.password_prompt_room.clone() else {
+        return;
+    };
+
+    let mut confirmed = false;
+    let mut cancelled = false;
+
+    egui::Window::new("🔒 Sala con contraseña")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(format!("Sala: {}", room_id));
+            ui.add_space(5.0);
+            let response = ui.add_sized(
+                [250.0, 24.0],
+                egui::TextEdit::singleline(&mut room_list.password_input)
+                    .password(true)
+                    .hint_text("contraseña..."),
+            );
+            response.request_focus();
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                confirmed = true;
+            }
+            ui.add_space(5.0);
+            ui.horizontal(|ui| {
+                if ui.button("Entrar").clicked() {
+                    confirmed = true;
+                }
+                if ui.button("Cancelar").clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+
+    if confirmed && !room_list.password_input.is_empty() {
+        config.room = room_id.clone();
+        config.room_password = Some(std::mem::take(&mut room_list.password_input));
+        room_list.password_prompt_room = None;
+        println!("🎮 Entrando a sala con contraseña: {}", room_id);
+        next_state.set(AppState::Connecting);
+    } else if cancelled {
+        room_list.password_prompt_room = None;
+        room_list.password_input.clear();
+    }
+}
+
 pub fn room_selection_ui(
     mut contexts: EguiContexts,
     mut config: ResMut<ConnectionConfig>,
@@ -96,6 +168,11 @@ pub fn room_selection_ui(
 ) {
     let Ok(ctx) = contexts.ctx_mut() else { return };
 
+    password_prompt_ui(ctx, &mut config, &mut room_list, &mut next_state);
+
+    // Sala elegida en la lista (se resuelve después de soltar el préstamo de la lista)
+    let mut join_room: Option<RoomInfo> = None;
+
     egui::CentralPanel::default().show(ctx, |ui| {
         ui.vertical_centered(|ui| {
             ui.add_space(30.0);
@@ -276,6 +353,9 @@ pub fn room_selection_ui(
                             ui.horizontal(|ui| {
                                 // Nombre de la sala
                                 ui.label(egui::RichText::new(&room.name).size(18.0).strong());
+                                if room.has_password {
+                                    ui.label("🔒").on_hover_text("Requiere contraseña");
+                                }
 
                                 ui.with_layout(
                                     egui::Layout::right_to_left(egui::Align::Center),
@@ -326,9 +406,7 @@ pub fn room_selection_ui(
                             }
 
                             if response.double_clicked() && !is_full {
-                                config.room = room.room_id.clone();
-                                println!("🎮 Entrando a sala: {}", room.room_id);
-                                next_state.set(AppState::Connecting);
+                                join_room = Some(room.clone());
                             }
                         });
 
@@ -352,11 +430,11 @@ pub fn room_selection_ui(
                 )
                 .clicked()
             {
-                if let Some(ref room_id) = selected_room.room_id {
-                    config.room = room_id.clone();
-                    println!("🎮 Entrando a sala: {}", room_id);
-                    next_state.set(AppState::Connecting);
-                }
+                join_room = room_list
+                    .rooms
+                    .iter()
+                    .find(|r| Some(&r.room_id) == selected_room.room_id.as_ref())
+                    .cloned();
             }
 
             ui.add_space(20.0);
@@ -373,10 +451,21 @@ pub fn room_selection_ui(
                     );
                     if ui.button("Entrar").clicked() && !room_list.direct_connect_id.is_empty() {
                         config.room = room_list.direct_connect_id.clone();
+                        config.room_password = Some(room_list.direct_connect_password.clone())
+                            .filter(|p| !p.is_empty());
                         println!("🎮 Entrando a sala por ID: {}", config.room);
                         next_state.set(AppState::Connecting);
                     }
                 });
+                ui.horizontal(|ui| {
+                    ui.label("Contraseña:");
+                    ui.add_sized(
+                        [300.0, 24.0],
+                        egui::TextEdit::singleline(&mut room_list.direct_connect_password)
+                            .password(true)
+                            .hint_text("(opcional)"),
+                    );
+                });
             });
 
             ui.add_space(10.0);
@@ -387,4 +476,8 @@ pub fn room_selection_ui(
             );
         });
     });
+
+    if let Some(room) = join_room {
+        request_join(&room, &mut config, &mut room_list, &mut next_state);
+    }
 }
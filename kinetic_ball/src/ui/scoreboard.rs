@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::resources::{ClientMatchScore, MyPlayerId, PlayerLatencies};
+use crate::shared::protocol::GameConfig;
+
+/// Segundos que se muestra el cartel de gol
+const GOAL_BANNER_SECS: f64 = 3.0;
+
+/// Formatea segundos como mm:ss
+pub(super) fn format_clock(secs: f32) -> String {
+    let total = secs.max(0.0) as u32;
+    format!("{:02}:{:02}", total / 60, total % 60)
+}
+
+/// Convierte un color de equipo (r, g, b en 0..1) a egui
+fn team_color32(config: &GameConfig, team_index: usize) -> egui::Color32 {
+    let (r, g, b) = config
+        .team_colors
+        .get(team_index)
+        .copied()
+        .unwrap_or((0.8, 0.8, 0.8));
+    egui::Color32::from_rgb((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
+}
+
+/// Color del ping: verde (bueno), amarillo (aceptable) o rojo (alto)
+pub(super) fn ping_color(rtt_ms: u16) -> egui::Color32 {
+    match rtt_ms {
+        0..80 => egui::Color32::LIGHT_GREEN,
+        80..150 => egui::Color32::YELLOW,
+        _ => egui::Color32::LIGHT_RED,
+    }
+}
+
+/// HUD con el marcador y reloj del partido (arriba al centro), ping propio,
+/// cartel de gol y resultado final
+pub fn scoreboard_ui(
+    mut contexts: EguiContexts,
+    match_score: Res<ClientMatchScore>,
+    config: Res<GameConfig>,
+    latencies: Res<PlayerLatencies>,
+    my_player_id: Res<MyPlayerId>,
+    time: Res<Time>,
+) {
+    let Ok(ctx) = contexts.ctx_mut() else { return };
+
+    let red = team_color32(&config, 0);
+    let blue = team_color32(&config, 1);
+
+    egui::Area::new(egui::Id::new("scoreboard"))
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 8.0])
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::new()
+                .fill(egui::Color32::from_black_alpha(160))
+                .corner_radius(6.0)
+                .inner_margin(6.0)
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new("■").color(red).size(20.0));
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{}  -  {}",
+                                match_score.score[0], match_score.score[1]
+                            ))
+                            .color(egui::Color32::WHITE)
+                            .size(22.0)
+                            .strong(),
+                        );
+                        ui.label(egui::RichText::new("■").color(blue).size(20.0));
+
+                        ui.separator();
+
+                        // Tiempo restante (o jugado si no hay límite)
+                        let clock = &match_score.clock;
+                        let clock_text = if clock.overtime {
+                            format!(
+                                "+{}",
+                                format_clock(clock.elapsed_secs - clock.time_limit_secs as f32)
+                            )
+                        } else {
+                            format_clock(clock.remaining_secs().unwrap_or(clock.elapsed_secs))
+                        };
+                        ui.label(
+                            egui::RichText::new(clock_text)
+                                .color(egui::Color32::WHITE)
+                                .size(20.0)
+                                .monospace(),
+                        );
+                        if clock.overtime {
+                            ui.label(
+                                egui::RichText::new("GOL DE ORO")
+                                    .color(egui::Color32::GOLD)
+                                    .size(14.0)
+                                    .strong(),
+                            );
+                        }
+
+                        // Ping propio (el de todos se ve en el panel con Esc)
+                        if let Some(rtt_ms) = my_player_id.0.and_then(|id| latencies.rtt_ms(id)) {
+                            ui.separator();
+                            ui.label(
+                                egui::RichText::new(format!("📶 {} ms", rtt_ms))
+                                    .color(ping_color(rtt_ms))
+                                    .size(14.0)
+                                    .monospace(),
+                            );
+                        }
+                    });
+                });
+        });
+
+    // Resultado final
+    if let Some(winner) = match_score.clock.winner {
+        let (name, color) = if winner == 0 {
+            ("ROJO", red)
+        } else {
+            ("AZUL", blue)
+        };
+        egui::Area::new(egui::Id::new("match_result"))
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, -120.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.label(
+                        egui::RichText::new("FIN DEL PARTIDO")
+                            .color(egui::Color32::WHITE)
+                            .size(36.0)
+                            .strong(),
+                    );
+                    ui.label(
+                        egui::RichText::new(format!("Gana {}", name))
+                            .color(color)
+                            .size(48.0)
+                            .strong(),
+                    );
+                });
+            });
+        return;
+    }
+
+    // Cartel de gol durante unos segundos
+    if let Some((team, at)) = match_score.last_goal {
+        if time.elapsed_secs_f64() - at < GOAL_BANNER_SECS {
+            let (name, color) = if team == 0 {
+                ("ROJO", red)
+            } else {
+                ("AZUL", blue)
+            };
+            egui::Area::new(egui::Id::new("goal_banner"))
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, -120.0])
+                .interactable(false)
+                .show(ctx, |ui| {
+                    ui.label(
+                        egui::RichText::new(format!("¡GOL {}!", name))
+                            .color(color)
+                            .size(48.0)
+                            .strong(),
+                    );
+                });
+        }
+    }
+}
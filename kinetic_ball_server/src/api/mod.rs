@@ -4,7 +4,9 @@ pub use rooms::rooms_router;
 
 use utoipa::OpenApi;
 
-use crate::state::{CreateRoomRequest, CreateRoomResponse, RoomInfo, RoomStatus};
+use crate::state::{
+    CreateRoomRequest, CreateRoomResponse, RoomInfo, RoomStatus, TakeoverTokenResponse,
+};
 
 #[derive(OpenApi)]
 #[openapi(
@@ -19,8 +21,15 @@ use crate::state::{CreateRoomRequest, CreateRoomResponse, RoomInfo, RoomStatus};
         rooms::get_room,
         rooms::create_room,
         rooms::delete_room,
+        rooms::issue_takeover_token,
     ),
-    components(schemas(RoomInfo, RoomStatus, CreateRoomRequest, CreateRoomResponse)),
+    components(schemas(
+        RoomInfo,
+        RoomStatus,
+        CreateRoomRequest,
+        CreateRoomResponse,
+        TakeoverTokenResponse
+    )),
     tags(
         (name = "rooms", description = "Game room management operations")
     )
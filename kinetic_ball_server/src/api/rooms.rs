@@ -7,7 +7,9 @@ use axum::{
 };
 use serde::Deserialize;
 
-use crate::state::{AppState, CreateRoomRequest, CreateRoomResponse, RoomInfo};
+use crate::state::{
+    AppState, CreateRoomRequest, CreateRoomResponse, RoomInfo, TakeoverTokenResponse,
+};
 
 /// Query params for delete endpoint
 #[derive(Deserialize, utoipa::IntoParams)]
@@ -16,6 +18,21 @@ pub struct DeleteRoomQuery {
     token: String,
 }
 
+/// Query params for the room details endpoint
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct GetRoomQuery {
+    /// Join password to check before connecting (omit to skip the check)
+    #[serde(default)]
+    password: Option<String>,
+}
+
+/// Query params for the takeover token endpoint
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct TakeoverTokenQuery {
+    /// Authentication token for the room
+    token: String,
+}
+
 /// Create the rooms router
 pub fn rooms_router() -> Router<AppState> {
     Router::new()
@@ -23,9 +40,10 @@ pub fn rooms_router() -> Router<AppState> {
         .route("/rooms", post(create_room))
         .route("/rooms/:id", get(get_room))
         .route("/rooms/:id", delete(delete_room))
+        .route("/rooms/:id/takeover", post(issue_takeover_token))
 }
 
-/// List all open rooms
+/// List all open public rooms (private rooms are joined by ID only)
 #[utoipa::path(
     get,
     path = "/api/rooms",
@@ -47,7 +65,8 @@ pub(crate) async fn list_rooms(State(state): State<AppState>) -> Json<Vec<RoomIn
     Json(rooms)
 }
 
-/// Get a specific room by ID
+/// Get a specific room by ID. With `?password=` it also checks the join
+/// password, so clients can reject a wrong one before opening the WebSocket
 #[utoipa::path(
     get,
     path = "/api/rooms/{id}",
@@ -56,11 +75,13 @@ pub(crate) async fn list_rooms(State(state): State<AppState>) -> Json<Vec<RoomIn
         ("X-Client-Version" = String, Header, description = "Client semver version"),
         ("X-Client-Time" = String, Header, description = "Current unix time in minutes"),
         ("X-Client-Token" = String, Header, description = "HMAC-SHA256 hex token"),
+        GetRoomQuery,
     ),
     responses(
         (status = 200, description = "Room details", body = RoomInfo),
         (status = 400, description = "Missing HMAC authentication headers"),
         (status = 401, description = "Invalid token or expired timestamp"),
+        (status = 403, description = "Wrong room password"),
         (status = 404, description = "Room not found"),
         (status = 426, description = "Client version too old"),
     ),
@@ -69,11 +90,17 @@ pub(crate) async fn list_rooms(State(state): State<AppState>) -> Json<Vec<RoomIn
 pub(crate) async fn get_room(
     State(state): State<AppState>,
     Path(id): Path<String>,
+    Query(query): Query<GetRoomQuery>,
 ) -> Result<Json<RoomInfo>, (StatusCode, String)> {
-    match state.get_room(&id).await {
-        Some(room) => Ok(Json(room)),
-        None => Err((StatusCode::NOT_FOUND, format!("Room '{}' not found", id))),
+    let Some(room) = state.get_room(&id).await else {
+        return Err((StatusCode::NOT_FOUND, format!("Room '{}' not found", id)));
+    };
+    if let Some(password) = query.password.as_deref() {
+        if !state.check_room_password(&id, Some(password)).await {
+            return Err((StatusCode::FORBIDDEN, "Invalid room password".to_string()));
+        }
     }
+    Ok(Json(room))
 }
 
 /// Create a new room
@@ -158,3 +185,38 @@ pub(crate) async fn delete_room(
         Err(e) => Err((StatusCode::FORBIDDEN, e)),
     }
 }
+
+/// Issue a takeover token for the host's successor (requires the room token).
+/// The previous takeover token of the room stops being valid.
+#[utoipa::path(
+    post,
+    path = "/api/rooms/{id}/takeover",
+    params(
+        ("id" = String, Path, description = "The room ID"),
+        ("X-Client-Version" = String, Header, description = "Client semver version"),
+        ("X-Client-Time" = String, Header, description = "Current unix time in minutes"),
+        ("X-Client-Token" = String, Header, description = "HMAC-SHA256 hex token"),
+        TakeoverTokenQuery,
+    ),
+    responses(
+        (status = 200, description = "New takeover token", body = TakeoverTokenResponse),
+        (status = 400, description = "Missing HMAC authentication headers"),
+        (status = 401, description = "Invalid HMAC token or expired timestamp"),
+        (status = 403, description = "Invalid or missing room token"),
+        (status = 426, description = "Client version too old"),
+    ),
+    tag = "rooms"
+)]
+pub(crate) async fn issue_takeover_token(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<TakeoverTokenQuery>,
+) -> Result<Json<TakeoverTokenResponse>, (StatusCode, String)> {
+    match state.issue_takeover_token(&id, &query.token).await {
+        Ok(takeover_token) => {
+            tracing::info!(room_id = %id, "Takeover token issued");
+            Ok(Json(TakeoverTokenResponse { takeover_token }))
+        }
+        Err(e) => Err((StatusCode::FORBIDDEN, e)),
+    }
+}
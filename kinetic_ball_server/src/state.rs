@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// How long a room whose host disconnected waits for a successor to take it over
+pub const HOST_MIGRATION_GRACE: Duration = Duration::from_secs(30);
+
 /// Status of a room
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(rename_all = "lowercase")]
@@ -24,6 +29,29 @@ pub struct RoomInfo {
     /// Versión mínima requerida del cliente (formato "major.minor.patch")
     #[serde(default)]
     pub min_version: Option<String>,
+    /// Whether joining requires a password
+    #[serde(default)]
+    pub has_password: bool,
+    /// Private rooms are hidden from the room list (join by ID only)
+    #[serde(default)]
+    pub private: bool,
+    /// Matchbox peer ID of the host, known once its socket is connected.
+    /// Clients only open a connection to this peer.
+    #[serde(default)]
+    pub host_peer_id: Option<String>,
+    /// SHA-256 of "room_id:password" (never serialized)
+    #[serde(skip)]
+    password_hash: Option<String>,
+    /// Set while the host is gone and the room waits for a successor
+    #[serde(skip)]
+    orphaned_at: Option<Instant>,
+    /// Secret the host hands to its current successor; only valid to take
+    /// over the room once orphaned, never to open a host socket (never serialized)
+    #[serde(skip)]
+    takeover_token: Option<String>,
+    /// Whether a host socket is open for this room (only one at a time)
+    #[serde(skip)]
+    host_connected: bool,
 }
 
 impl RoomInfo {
@@ -42,6 +70,31 @@ impl RoomInfo {
             map_name,
             status: RoomStatus::Open,
             min_version,
+            has_password: false,
+            private: false,
+            host_peer_id: None,
+            password_hash: None,
+            orphaned_at: None,
+            takeover_token: None,
+            host_connected: false,
+        }
+    }
+
+    /// Set or clear the room password (empty = no password)
+    pub fn set_password(&mut self, password: Option<&str>) {
+        self.password_hash = password
+            .filter(|p| !p.is_empty())
+            .map(|p| hash_password(&self.room_id, p));
+        self.has_password = self.password_hash.is_some();
+    }
+
+    /// Check a join password. Rooms without password accept anything.
+    pub fn check_password(&self, password: Option<&str>) -> bool {
+        match &self.password_hash {
+            None => true,
+            Some(hash) => password
+                .map(|p| hash_password(&self.room_id, p) == *hash)
+                .unwrap_or(false),
         }
     }
 
@@ -79,6 +132,23 @@ pub struct CreateRoomRequest {
     /// Versión mínima requerida del cliente (formato "major.minor.patch")
     #[serde(default)]
     pub min_version: Option<String>,
+    /// Optional password required to join
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Hide the room from `GET /api/rooms`
+    #[serde(default)]
+    pub private: bool,
+    /// Takeover token issued to the previous host for its successor: takes over
+    /// a room waiting for a successor (host migration). The room keeps its
+    /// name, password and visibility.
+    #[serde(default)]
+    pub takeover_token: Option<String>,
+}
+
+/// Hash a room password, salted with the room ID
+fn hash_password(room_id: &str, password: &str) -> String {
+    let digest = Sha256::digest(format!("{}:{}", room_id, password).as_bytes());
+    hex::encode(digest)
 }
 
 /// Response for room creation
@@ -87,6 +157,13 @@ pub struct CreateRoomResponse {
     pub token: String,
 }
 
+/// Response for takeover token issuing
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TakeoverTokenResponse {
+    /// Secret for `takeover_token` when registering the room again
+    pub takeover_token: String,
+}
+
 /// Shared application state
 #[derive(Clone)]
 pub struct AppState {
@@ -117,17 +194,42 @@ impl AppState {
     pub async fn register_room(&self, request: CreateRoomRequest) -> Result<String, String> {
         let mut rooms = self.rooms.write().await;
 
-        if rooms.contains_key(&request.room_id) {
-            return Err(format!("Room '{}' already exists", request.room_id));
+        if let Some(room) = rooms.get_mut(&request.room_id) {
+            let is_successor = request.takeover_token.is_some()
+                && room.takeover_token.is_some()
+                && request.takeover_token == room.takeover_token;
+
+            if room.orphaned_at.is_none() || !is_successor {
+                return Err(format!("Room '{}' already exists", request.room_id));
+            }
+
+            room.orphaned_at = None;
+            room.host_peer_id = None;
+            room.takeover_token = None;
+            room.status = if room.current_players >= room.max_players {
+                RoomStatus::Full
+            } else {
+                RoomStatus::Open
+            };
+
+            // The previous host's token is replaced by a new one
+            let token = uuid::Uuid::new_v4().to_string();
+            let mut tokens = self.tokens.write().await;
+            tokens.retain(|_, room_id| *room_id != request.room_id);
+            tokens.insert(token.clone(), request.room_id.clone());
+            tracing::info!(room_id = %request.room_id, "Room taken over by a new host");
+            return Ok(token);
         }
 
-        let room = RoomInfo::new(
+        let mut room = RoomInfo::new(
             request.room_id.clone(),
             request.name,
             request.max_players,
             request.map_name,
             request.min_version,
         );
+        room.set_password(request.password.as_deref());
+        room.private = request.private;
         rooms.insert(request.room_id.clone(), room);
 
         // Generate token for game server
@@ -138,17 +240,38 @@ impl AppState {
         Ok(token)
     }
 
+    /// Issue a new takeover token for a room (requires the host token).
+    /// Any previously issued takeover token stops being valid.
+    pub async fn issue_takeover_token(&self, room_id: &str, token: &str) -> Result<String, String> {
+        let tokens = self.tokens.read().await;
+        let valid = tokens.get(token).map(|id| id == room_id).unwrap_or(false);
+        drop(tokens);
+
+        if !valid {
+            return Err("Invalid token".to_string());
+        }
+
+        let mut rooms = self.rooms.write().await;
+        let room = rooms
+            .get_mut(room_id)
+            .ok_or_else(|| format!("Room '{}' not found", room_id))?;
+        let takeover_token = uuid::Uuid::new_v4().to_string();
+        room.takeover_token = Some(takeover_token.clone());
+        Ok(takeover_token)
+    }
+
     /// Validate token and return the associated room_id
     pub async fn validate_token(&self, token: &str) -> Option<String> {
         let tokens = self.tokens.read().await;
         tokens.get(token).cloned()
     }
 
-    /// Get a list of open rooms
+    /// Get a list of open public rooms
     pub async fn list_rooms(&self) -> Vec<RoomInfo> {
         let rooms = self.rooms.read().await;
         rooms
             .values()
+            .filter(|r| !r.private)
             .filter(|r| r.status == RoomStatus::Open || r.status == RoomStatus::Full)
             .cloned()
             .collect()
@@ -160,12 +283,58 @@ impl AppState {
         rooms.get(room_id).cloned()
     }
 
+    /// Record the matchbox peer ID assigned to the host of a room
+    pub async fn set_host_peer(&self, room_id: &str, peer_id: String) {
+        let mut rooms = self.rooms.write().await;
+        if let Some(room) = rooms.get_mut(room_id) {
+            tracing::info!(room_id = %room_id, peer_id = %peer_id, "Host peer assigned");
+            room.host_peer_id = Some(peer_id);
+        }
+    }
+
+    /// Matchbox peer ID of the host of a room, if already known
+    pub async fn host_peer(&self, room_id: &str) -> Option<String> {
+        let rooms = self.rooms.read().await;
+        rooms
+            .get(room_id)
+            .and_then(|room| room.host_peer_id.clone())
+    }
+
+    /// Whether a host socket is already open for a room
+    pub async fn has_host_socket(&self, room_id: &str) -> bool {
+        let rooms = self.rooms.read().await;
+        rooms.get(room_id).is_some_and(|room| room.host_connected)
+    }
+
+    /// Mark the host socket of a room as open. Fails if the room is gone or
+    /// another host socket is already open.
+    pub async fn claim_host_socket(&self, room_id: &str) -> bool {
+        let mut rooms = self.rooms.write().await;
+        match rooms.get_mut(room_id) {
+            Some(room) if !room.host_connected => {
+                room.host_connected = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Mark the host socket of a room as closed
+    pub async fn release_host_socket(&self, room_id: &str) {
+        let mut rooms = self.rooms.write().await;
+        if let Some(room) = rooms.get_mut(room_id) {
+            room.host_connected = false;
+        }
+    }
+
     /// Check if a room exists and has capacity
     pub async fn can_join_room(&self, room_id: &str) -> Result<(), String> {
         let rooms = self.rooms.read().await;
         match rooms.get(room_id) {
             Some(room) => {
-                if room.has_capacity() {
+                if room.orphaned_at.is_some() {
+                    Err(format!("Room '{}' is waiting for a new host", room_id))
+                } else if room.has_capacity() {
                     Ok(())
                 } else {
                     Err(format!("Room '{}' is full", room_id))
@@ -175,6 +344,40 @@ impl AppState {
         }
     }
 
+    /// The host disconnected: keep the room hidden and closed to new clients so a
+    /// successor can take it over. Returns when it happened, to expire it later.
+    pub async fn orphan_room(&self, room_id: &str) -> Option<Instant> {
+        let mut rooms = self.rooms.write().await;
+        let room = rooms.get_mut(room_id)?;
+        let now = Instant::now();
+        room.orphaned_at = Some(now);
+        room.host_peer_id = None;
+        room.status = RoomStatus::Closed;
+        Some(now)
+    }
+
+    /// Delete a room that nobody took over since it was orphaned at `orphaned_at`
+    pub async fn expire_orphaned_room(&self, room_id: &str, orphaned_at: Instant) {
+        let rooms = self.rooms.read().await;
+        let still_orphaned = rooms
+            .get(room_id)
+            .is_some_and(|room| room.orphaned_at == Some(orphaned_at));
+        drop(rooms);
+
+        if still_orphaned {
+            self.delete_room_by_host(room_id).await;
+        }
+    }
+
+    /// Check the join password of a room (rooms without password always pass)
+    pub async fn check_room_password(&self, room_id: &str, password: Option<&str>) -> bool {
+        let rooms = self.rooms.read().await;
+        rooms
+            .get(room_id)
+            .map(|room| room.check_password(password))
+            .unwrap_or(true)
+    }
+
     /// Increment connection count for a room
     pub async fn add_connection(&self, room_id: &str) {
         let mut connections = self.connections.write().await;
@@ -237,3 +440,225 @@ impl AppState {
         connections.remove(room_id);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room() -> RoomInfo {
+        RoomInfo::new("room_1".into(), "Sala".into(), 4, None, None)
+    }
+
+    #[test]
+    fn room_without_password_accepts_anything() {
+        let room = room();
+        assert!(!room.has_password);
+        assert!(room.check_password(None));
+        assert!(room.check_password(Some("whatever")));
+    }
+
+    #[test]
+    fn room_with_password_requires_match() {
+        let mut room = room();
+        room.set_password(Some("secreto"));
+        assert!(room.has_password);
+        assert!(room.check_password(Some("secreto")));
+        assert!(!room.check_password(Some("otro")));
+        assert!(!room.check_password(None));
+    }
+
+    #[test]
+    fn empty_password_means_no_password() {
+        let mut room = room();
+        room.set_password(Some(""));
+        assert!(!room.has_password);
+        assert!(room.check_password(None));
+    }
+
+    #[test]
+    fn password_hash_is_not_serialized() {
+        let mut room = room();
+        room.set_password(Some("secreto"));
+        let json = serde_json::to_string(&room).unwrap();
+        assert!(!json.contains("password_hash"));
+        assert!(json.contains("\"has_password\":true"));
+    }
+
+    #[tokio::test]
+    async fn private_rooms_are_hidden_from_list() {
+        let state = AppState::new("ws://localhost:3536".into(), "0.1.0".into());
+        let request = |room_id: &str, private: bool| CreateRoomRequest {
+            room_id: room_id.into(),
+            name: room_id.into(),
+            max_players: 4,
+            map_name: None,
+            min_version: None,
+            password: None,
+            private,
+            takeover_token: None,
+        };
+        state.register_room(request("public", false)).await.unwrap();
+        state.register_room(request("hidden", true)).await.unwrap();
+
+        let listed: Vec<String> = state
+            .list_rooms()
+            .await
+            .into_iter()
+            .map(|r| r.room_id)
+            .collect();
+        assert_eq!(listed, vec!["public".to_string()]);
+        assert!(state.get_room("hidden").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn host_peer_is_recorded_and_exposed() {
+        let state = AppState::new("ws://localhost:3536".into(), "0.1.0".into());
+        state
+            .register_room(CreateRoomRequest {
+                room_id: "room_1".into(),
+                name: "Sala".into(),
+                max_players: 4,
+                map_name: None,
+                min_version: None,
+                password: None,
+                private: false,
+                takeover_token: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(state.host_peer("room_1").await, None);
+
+        state.set_host_peer("room_1", "peer-host".into()).await;
+        assert_eq!(
+            state.host_peer("room_1").await.as_deref(),
+            Some("peer-host")
+        );
+
+        let json = serde_json::to_string(&state.get_room("room_1").await.unwrap()).unwrap();
+        assert!(json.contains("\"host_peer_id\":\"peer-host\""));
+
+        // Rooms of unknown IDs are ignored
+        state.set_host_peer("missing", "peer-x".into()).await;
+        assert_eq!(state.host_peer("missing").await, None);
+    }
+
+    fn create_request(room_id: &str, takeover_token: Option<String>) -> CreateRoomRequest {
+        CreateRoomRequest {
+            room_id: room_id.into(),
+            name: room_id.into(),
+            max_players: 4,
+            map_name: None,
+            min_version: None,
+            password: None,
+            private: false,
+            takeover_token,
+        }
+    }
+
+    #[tokio::test]
+    async fn orphaned_room_can_be_taken_over_with_the_takeover_token() {
+        let state = AppState::new("ws://localhost:3536".into(), "0.1.0".into());
+        let token = state
+            .register_room(create_request("room_1", None))
+            .await
+            .unwrap();
+        assert!(state.issue_takeover_token("room_1", "wrong").await.is_err());
+        let takeover = state.issue_takeover_token("room_1", &token).await.unwrap();
+
+        // The takeover token never opens a host socket
+        assert_eq!(state.validate_token(&takeover).await, None);
+
+        // While the host is connected nobody can take the room, not even with the takeover token
+        assert!(state
+            .register_room(create_request("room_1", Some(takeover.clone())))
+            .await
+            .is_err());
+
+        let orphaned_at = state.orphan_room("room_1").await.unwrap();
+        assert!(state.list_rooms().await.is_empty());
+        assert!(state.can_join_room("room_1").await.is_err());
+        assert!(state
+            .register_room(create_request("room_1", Some("wrong".into())))
+            .await
+            .is_err());
+        // The host token is not a takeover token
+        assert!(state
+            .register_room(create_request("room_1", Some(token.clone())))
+            .await
+            .is_err());
+
+        let new_token = state
+            .register_room(create_request("room_1", Some(takeover.clone())))
+            .await
+            .unwrap();
+        assert_ne!(new_token, token);
+        assert_eq!(state.validate_token(&token).await, None);
+        assert_eq!(
+            state.validate_token(&new_token).await.as_deref(),
+            Some("room_1")
+        );
+        assert!(state.can_join_room("room_1").await.is_ok());
+
+        // The expiry of the old orphan period must not delete the taken-over room
+        state.expire_orphaned_room("room_1", orphaned_at).await;
+        assert!(state.get_room("room_1").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn issuing_a_takeover_token_revokes_the_previous_one() {
+        let state = AppState::new("ws://localhost:3536".into(), "0.1.0".into());
+        let token = state
+            .register_room(create_request("room_1", None))
+            .await
+            .unwrap();
+        let old = state.issue_takeover_token("room_1", &token).await.unwrap();
+        let new = state.issue_takeover_token("room_1", &token).await.unwrap();
+        assert_ne!(old, new);
+
+        state.orphan_room("room_1").await.unwrap();
+        assert!(state
+            .register_room(create_request("room_1", Some(old)))
+            .await
+            .is_err());
+        let taken = state
+            .register_room(create_request("room_1", Some(new.clone())))
+            .await;
+        assert!(taken.is_ok());
+
+        // A takeover token is used only once
+        state.orphan_room("room_1").await.unwrap();
+        assert!(state
+            .register_room(create_request("room_1", Some(new)))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn only_one_host_socket_per_room() {
+        let state = AppState::new("ws://localhost:3536".into(), "0.1.0".into());
+        state
+            .register_room(create_request("room_1", None))
+            .await
+            .unwrap();
+        assert!(!state.has_host_socket("room_1").await);
+        assert!(state.claim_host_socket("room_1").await);
+        assert!(state.has_host_socket("room_1").await);
+        assert!(!state.claim_host_socket("room_1").await);
+
+        state.release_host_socket("room_1").await;
+        assert!(state.claim_host_socket("room_1").await);
+        assert!(!state.claim_host_socket("missing").await);
+    }
+
+    #[tokio::test]
+    async fn orphaned_room_expires_without_successor() {
+        let state = AppState::new("ws://localhost:3536".into(), "0.1.0".into());
+        state
+            .register_room(create_request("room_1", None))
+            .await
+            .unwrap();
+        let orphaned_at = state.orphan_room("room_1").await.unwrap();
+        state.expire_orphaned_room("room_1", orphaned_at).await;
+        assert!(state.get_room("room_1").await.is_none());
+    }
+}
@@ -9,7 +9,58 @@ use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
 use tokio_tungstenite::{connect_async, tungstenite::Message as TungsteniteMessage};
 
-use crate::state::AppState;
+use crate::state::{AppState, HOST_MIGRATION_GRACE};
+
+/// Which side of the room a proxied socket belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProxyRole {
+    Host,
+    Client,
+}
+
+/// Matchbox signaling events the proxy cares about
+#[derive(Debug, PartialEq, Eq)]
+enum SignalingEvent {
+    IdAssigned(String),
+    NewPeer(String),
+    PeerLeft(String),
+    Signal { sender: String },
+    Other,
+}
+
+/// Parse a matchbox -> peer text message (`{"NewPeer":"<uuid>"}`, `{"Signal":{"sender":..}}`, ...)
+fn parse_signaling(text: &str) -> SignalingEvent {
+    let Ok(serde_json::Value::Object(map)) = serde_json::from_str::<serde_json::Value>(text) else {
+        return SignalingEvent::Other;
+    };
+    let Some((kind, value)) = map.into_iter().next() else {
+        return SignalingEvent::Other;
+    };
+    let peer = |value: &serde_json::Value| value.as_str().map(str::to_string);
+    match (kind.as_str(), &value) {
+        ("IdAssigned", v) => peer(v).map(SignalingEvent::IdAssigned),
+        ("NewPeer", v) => peer(v).map(SignalingEvent::NewPeer),
+        ("PeerLeft", v) => peer(v).map(SignalingEvent::PeerLeft),
+        ("Signal", v) => v
+            .get("sender")
+            .and_then(peer)
+            .map(|sender| SignalingEvent::Signal { sender }),
+        _ => None,
+    }
+    .unwrap_or(SignalingEvent::Other)
+}
+
+/// Clients only get to know the host: events about any other peer are dropped,
+/// so clients never open connections between themselves.
+/// While the host peer is unknown (not connected yet, or migrating) no peer gets through.
+fn forward_to_client(event: &SignalingEvent, host_peer: Option<&str>) -> bool {
+    match event {
+        SignalingEvent::NewPeer(peer)
+        | SignalingEvent::PeerLeft(peer)
+        | SignalingEvent::Signal { sender: peer } => host_peer == Some(peer.as_str()),
+        SignalingEvent::IdAssigned(_) | SignalingEvent::Other => true,
+    }
+}
 
 /// Query params for server connection
 #[derive(Deserialize)]
@@ -17,6 +68,13 @@ pub struct ServerConnectQuery {
     token: String,
 }
 
+/// Query params for client connection
+#[derive(Deserialize)]
+pub struct ClientConnectQuery {
+    #[serde(default)]
+    password: Option<String>,
+}
+
 /// Handle WebSocket connection from game server
 /// Endpoint: /connect?token=X
 pub async fn handle_server_ws(
@@ -37,23 +95,50 @@ pub async fn handle_server_ws(
         }
     };
 
+    // A room has a single host socket
+    if state.has_host_socket(&room_id).await {
+        tracing::warn!(room_id = %room_id, "❌ Second host socket rejected");
+        return axum::response::Response::builder()
+            .status(axum::http::StatusCode::CONFLICT)
+            .body(axum::body::Body::from("Room already has a host"))
+            .unwrap();
+    }
+
     tracing::info!(room_id = %room_id, "Game server connecting");
 
     let matchbox_url = format!("{}/{}", state.matchbox_url, room_id);
 
     ws.on_upgrade(move |socket| async move {
-        if let Err(e) = proxy_websocket(socket, &matchbox_url, Some((state, room_id))).await {
+        // Another host socket may have won the race since the check above
+        if !state.claim_host_socket(&room_id).await {
+            tracing::warn!(room_id = %room_id, "❌ Second host socket rejected");
+            let _ = socket.close().await;
+            return;
+        }
+
+        if let Err(e) = proxy_websocket(
+            socket,
+            &matchbox_url,
+            state.clone(),
+            room_id.clone(),
+            ProxyRole::Host,
+        )
+        .await
+        {
             tracing::error!("Server WebSocket proxy error: {}", e);
+            // Never reached matchbox: the room is still free for a host socket
+            state.release_host_socket(&room_id).await;
         }
     })
 }
 
 /// Handle WebSocket connection from client
-/// Endpoint: /{room_id}
+/// Endpoint: /{room_id}?password=X
 pub async fn handle_client_ws(
     ws: WebSocketUpgrade,
     State(state): State<AppState>,
     Path(room_id): Path<String>,
+    Query(query): Query<ClientConnectQuery>,
 ) -> Response {
     // Validate room exists and has capacity
     if let Err(e) = state.can_join_room(&room_id).await {
@@ -65,6 +150,18 @@ pub async fn handle_client_ws(
             .unwrap();
     }
 
+    // Validate password before the upgrade
+    if !state
+        .check_room_password(&room_id, query.password.as_deref())
+        .await
+    {
+        tracing::warn!(room_id = %room_id, "Client connection rejected: wrong password");
+        return axum::response::Response::builder()
+            .status(axum::http::StatusCode::UNAUTHORIZED)
+            .body(axum::body::Body::from("Invalid room password"))
+            .unwrap();
+    }
+
     tracing::info!(room_id = %room_id, "Client connecting");
 
     let matchbox_url = format!("{}/{}", state.matchbox_url, room_id);
@@ -74,7 +171,14 @@ pub async fn handle_client_ws(
         // Track connection
         state.add_connection(&room_id_clone).await;
 
-        let result = proxy_websocket(socket, &matchbox_url, None).await;
+        let result = proxy_websocket(
+            socket,
+            &matchbox_url,
+            state.clone(),
+            room_id_clone.clone(),
+            ProxyRole::Client,
+        )
+        .await;
 
         // Untrack connection
         state.remove_connection(&room_id_clone).await;
@@ -85,11 +189,14 @@ pub async fn handle_client_ws(
     })
 }
 
-/// Proxy WebSocket messages bidirectionally between client and matchbox
+/// Proxy WebSocket messages bidirectionally between client and matchbox.
+/// The host's assigned peer ID is recorded in the room; clients only see the host.
 async fn proxy_websocket(
     client_ws: WebSocket,
     matchbox_url: &str,
-    server_tracking: Option<(AppState, String)>,
+    state: AppState,
+    room_id: String,
+    role: ProxyRole,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Connect to matchbox server
     let (matchbox_ws, _response) = connect_async(matchbox_url).await.map_err(|e| {
@@ -100,8 +207,8 @@ async fn proxy_websocket(
     tracing::debug!(url = %matchbox_url, "Connected to matchbox server");
 
     // If this is a server connection, track it
-    if let Some((ref state, ref room_id)) = server_tracking {
-        state.add_connection(room_id).await;
+    if role == ProxyRole::Host {
+        state.add_connection(&room_id).await;
     }
 
     let (mut client_sink, mut client_stream) = client_ws.split();
@@ -155,9 +262,31 @@ async fn proxy_websocket(
 
     // Forward matchbox -> client
     let matchbox_to_client = async {
+        let mut host_peer: Option<String> = None;
         while let Some(msg) = matchbox_stream.next().await {
             match msg {
                 Ok(TungsteniteMessage::Text(text)) => {
+                    let event = parse_signaling(&text);
+                    match role {
+                        ProxyRole::Host => {
+                            if let SignalingEvent::IdAssigned(peer_id) = event {
+                                state.set_host_peer(&room_id, peer_id).await;
+                            }
+                        }
+                        ProxyRole::Client => {
+                            if host_peer.is_none() {
+                                host_peer = state.host_peer(&room_id).await;
+                            }
+                            if !forward_to_client(&event, host_peer.as_deref()) {
+                                tracing::debug!(
+                                    room_id = %room_id,
+                                    ?event,
+                                    "Dropped signaling from non-host peer"
+                                );
+                                continue;
+                            }
+                        }
+                    }
                     if client_sink
                         .send(Message::Text(text.to_string()))
                         .await
@@ -210,11 +339,65 @@ async fn proxy_websocket(
         }
     }
 
-    // If this was a server (host) connection, delete the room entirely
-    if let Some((state, room_id)) = server_tracking {
-        tracing::info!(room_id = %room_id, "Host disconnected, deleting room");
-        state.delete_room_by_host(&room_id).await;
+    // If this was a server (host) connection, keep the room for a while so a
+    // successor can take it over; delete it if nobody does
+    if role == ProxyRole::Host {
+        tracing::info!(room_id = %room_id, "Host disconnected, waiting for a successor");
+        state.remove_connection(&room_id).await;
+        state.release_host_socket(&room_id).await;
+        if let Some(orphaned_at) = state.orphan_room(&room_id).await {
+            tokio::spawn(async move {
+                tokio::time::sleep(HOST_MIGRATION_GRACE).await;
+                state.expire_orphaned_room(&room_id, orphaned_at).await;
+            });
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_matchbox_events() {
+        assert_eq!(
+            parse_signaling(r#"{"IdAssigned":"host"}"#),
+            SignalingEvent::IdAssigned("host".into())
+        );
+        assert_eq!(
+            parse_signaling(r#"{"NewPeer":"a"}"#),
+            SignalingEvent::NewPeer("a".into())
+        );
+        assert_eq!(
+            parse_signaling(r#"{"Signal":{"sender":"a","data":{"IceCandidate":"x"}}}"#),
+            SignalingEvent::Signal { sender: "a".into() }
+        );
+        assert_eq!(parse_signaling("not json"), SignalingEvent::Other);
+    }
+
+    #[test]
+    fn clients_only_see_the_host() {
+        let forwards = |event: SignalingEvent| forward_to_client(&event, Some("host"));
+        assert!(forwards(SignalingEvent::NewPeer("host".into())));
+        assert!(!forwards(SignalingEvent::NewPeer("other".into())));
+        assert!(!forwards(SignalingEvent::Signal {
+            sender: "other".into()
+        }));
+        assert!(forwards(SignalingEvent::IdAssigned("me".into())));
+        // Host still unknown: no peer gets through
+        assert!(!forward_to_client(
+            &SignalingEvent::NewPeer("x".into()),
+            None
+        ));
+        assert!(!forward_to_client(
+            &SignalingEvent::Signal { sender: "x".into() },
+            None
+        ));
+        assert!(forward_to_client(
+            &SignalingEvent::IdAssigned("me".into()),
+            None
+        ));
+    }
+}